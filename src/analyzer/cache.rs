@@ -13,28 +13,65 @@
 // limitations under the License.
 
 use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, BTreeSet},
     path::PathBuf,
+    pin::Pin,
     sync::{Arc, RwLock},
     time::{Duration, Instant},
 };
 
 use crate::common::storage::{DocumentStorage, DocumentVersion};
 
-const VERIFY_INTERVAL: Duration = Duration::from_secs(5);
+pub const DEFAULT_VERIFY_INTERVAL: Duration = Duration::from_secs(5);
 
-fn compute_next_verify(t: Instant, version: DocumentVersion) -> Instant {
+/// Parameters controlling cache freshness checks and retention for a request.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheConfig {
+    pub request_time: Instant,
+    /// How long on-disk files are assumed to be unchanged after verification.
+    /// Zero means files are verified on every request.
+    pub verify_interval: Duration,
+    /// Whether on-disk files are assumed to be unchanged once verified, so
+    /// that analysis sees a consistent snapshot.
+    pub frozen: bool,
+    /// Maximum number of files kept in each cache. Zero means unlimited.
+    pub capacity: usize,
+}
+
+impl CacheConfig {
+    pub fn new(request_time: Instant, verify_interval: Duration) -> Self {
+        Self {
+            request_time,
+            verify_interval,
+            frozen: false,
+            capacity: 0,
+        }
+    }
+}
+
+fn compute_next_verify(
+    verified: Instant,
+    version: DocumentVersion,
+    cache_config: CacheConfig,
+) -> Option<Instant> {
     match version {
         DocumentVersion::OnDisk { .. }
         | DocumentVersion::IoError
-        | DocumentVersion::AnalysisError => t + VERIFY_INTERVAL,
-        // Do not skip verification for in-memory documents.
-        DocumentVersion::InMemory { .. } => t,
+        | DocumentVersion::AnalysisError => Some(if cache_config.frozen {
+            cache_config.request_time.max(verified)
+        } else {
+            verified + cache_config.verify_interval
+        }),
+        // Do not skip verification for in-memory documents, which can change
+        // within a request, e.g. when an imported file is edited.
+        DocumentVersion::InMemory { .. } => None,
     }
 }
 
 enum CacheState {
     Stale,
-    Fresh { expires: Instant },
+    Fresh { verified: Instant },
 }
 
 pub struct CacheNode {
@@ -49,26 +86,44 @@ impl CacheNode {
         path: PathBuf,
         version: DocumentVersion,
         deps: Vec<Arc<CacheNode>>,
-        request_time: Instant,
+        cache_config: CacheConfig,
     ) -> Arc<Self> {
         Arc::new(Self {
             path,
             version,
             deps,
             state: RwLock::new(CacheState::Fresh {
-                expires: compute_next_verify(request_time, version),
+                verified: cache_config.request_time,
             }),
         })
     }
 
-    pub fn verify(&self, request_time: Instant, storage: &DocumentStorage) -> bool {
+    /// Returns the paths of files this node depends on, directly or through
+    /// other files.
+    pub fn dependencies(&self) -> BTreeSet<PathBuf> {
+        let mut paths = BTreeSet::new();
+        let mut stack: Vec<&CacheNode> = self.deps.iter().map(Arc::as_ref).collect();
+        while let Some(node) = stack.pop() {
+            if node.path != self.path && paths.insert(node.path.clone()) {
+                stack.extend(node.deps.iter().map(Arc::as_ref));
+            }
+        }
+        paths
+    }
+
+    pub fn verify(&self, cache_config: CacheConfig, storage: &DocumentStorage) -> bool {
+        // A file opened since it was analyzed from disk may already differ.
+        let opened = storage.is_in_memory(&self.path);
+
         // Fast path with a read lock.
         let expires = match &*self.state.read().unwrap() {
             CacheState::Stale => return false,
-            CacheState::Fresh { expires } => *expires,
+            CacheState::Fresh { verified } => {
+                compute_next_verify(*verified, self.version, cache_config).filter(|_| !opened)
+            }
         };
-        if request_time <= expires {
-            if !self.verify_deps(request_time, storage) {
+        if expires.is_some_and(|expires| cache_config.request_time <= expires) {
+            if !self.verify_deps(cache_config, storage) {
                 *self.state.write().unwrap() = CacheState::Stale;
                 return false;
             }
@@ -79,10 +134,12 @@ impl CacheNode {
         let mut state_guard = self.state.write().unwrap();
         let expires = match &*state_guard {
             CacheState::Stale => return false,
-            CacheState::Fresh { expires } => *expires,
+            CacheState::Fresh { verified } => {
+                compute_next_verify(*verified, self.version, cache_config).filter(|_| !opened)
+            }
         };
-        if request_time <= expires {
-            if !self.verify_deps(request_time, storage) {
+        if expires.is_some_and(|expires| cache_config.request_time <= expires) {
+            if !self.verify_deps(cache_config, storage) {
                 *state_guard = CacheState::Stale;
                 return false;
             }
@@ -95,23 +152,103 @@ impl CacheNode {
             return false;
         }
 
-        if !self.verify_deps(request_time, storage) {
+        if !self.verify_deps(cache_config, storage) {
             *state_guard = CacheState::Stale;
             return false;
         }
 
         *state_guard = CacheState::Fresh {
-            expires: compute_next_verify(request_time, self.version),
+            verified: cache_config.request_time,
         };
         true
     }
 
-    fn verify_deps(&self, request_time: Instant, storage: &DocumentStorage) -> bool {
+    fn verify_deps(&self, cache_config: CacheConfig, storage: &DocumentStorage) -> bool {
         for dep in &self.deps {
-            if !dep.verify(request_time, storage) {
+            if !dep.verify(cache_config, storage) {
                 return false;
             }
         }
         true
     }
 }
+
+/// Analysis results that can be stored in an `LruCache`.
+pub trait CachedFile {
+    fn node(&self) -> &Arc<CacheNode>;
+}
+
+struct LruEntry<T> {
+    file: Pin<Arc<T>>,
+    last_used: u64,
+}
+
+/// Analyzed files evicted in least recently used order once they exceed a
+/// capacity.
+///
+/// Files whose cache nodes are shared, i.e. files other live analysis
+/// results depend on, are kept until their dependents are gone, so the cache
+/// may temporarily hold more files than its capacity.
+pub struct LruCache<K, T> {
+    entries: BTreeMap<K, LruEntry<T>>,
+    clock: u64,
+}
+
+impl<K, T> Default for LruCache<K, T> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            clock: 0,
+        }
+    }
+}
+
+impl<K: Ord + Clone, T: CachedFile> LruCache<K, T> {
+    pub fn files(&self) -> impl Iterator<Item = &Pin<Arc<T>>> {
+        self.entries.values().map(|entry| &entry.file)
+    }
+
+    /// Returns a cached file and marks it as recently used.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<Pin<Arc<T>>>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.file.clone())
+    }
+
+    /// Inserts a file and evicts least recently used files beyond `capacity`.
+    /// Zero `capacity` means unlimited.
+    pub fn insert(&mut self, key: K, file: Pin<Arc<T>>, capacity: usize) {
+        self.clock += 1;
+        self.entries.insert(
+            key.clone(),
+            LruEntry {
+                file,
+                last_used: self.clock,
+            },
+        );
+        if capacity == 0 {
+            return;
+        }
+        // Dependents are re-checked after each eviction, since evicting a file
+        // may release the files it depends on.
+        while self.entries.len() > capacity {
+            let Some(victim) = self
+                .entries
+                .iter()
+                .filter(|(other_key, entry)| {
+                    **other_key != key && Arc::strong_count(entry.file.node()) == 1
+                })
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(other_key, _)| other_key.clone())
+            else {
+                break;
+            };
+            self.entries.remove(&victim);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use either::Either;
+use pest::Span;
+
+use crate::parser::{BinaryOp, Block, Expr, Node, PrimaryExpr, Statement, UnaryOp};
+
+/// Operating systems whose `is_<os>` flags are folded.
+const KNOWN_OSES: [&str; 3] = ["linux", "mac", "win"];
+
+/// Returns the GN name of the operating system the analysis runs on.
+fn analysis_os() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "mac",
+        "windows" => "win",
+        os => os,
+    }
+}
+
+fn evaluate_os_comparison(lhs: &Expr, rhs: &Expr) -> Option<bool> {
+    let (identifier, os) = match (lhs.as_primary_identifier(), rhs.as_primary_identifier()) {
+        (Some(identifier), None) => (identifier, rhs.as_simple_string()?),
+        (None, Some(identifier)) => (identifier, lhs.as_simple_string()?),
+        _ => return None,
+    };
+    (identifier.name == "host_os").then(|| os == analysis_os())
+}
+
+/// Evaluates a condition that is obviously constant on the platform the
+/// analysis runs on. Returns `None` if the condition can not be folded.
+pub fn evaluate_condition(expr: &Expr) -> Option<bool> {
+    match expr {
+        Expr::Primary(primary_expr) => match primary_expr.as_ref() {
+            PrimaryExpr::Identifier(identifier) => match identifier.name {
+                "true" => Some(true),
+                "false" => Some(false),
+                name => {
+                    let os = name.strip_prefix("is_")?;
+                    KNOWN_OSES.contains(&os).then(|| os == analysis_os())
+                }
+            },
+            PrimaryExpr::ParenExpr(paren_expr) => evaluate_condition(&paren_expr.expr),
+            _ => None,
+        },
+        Expr::Unary(unary_expr) => match unary_expr.op {
+            UnaryOp::Not => evaluate_condition(&unary_expr.expr).map(|value| !value),
+        },
+        Expr::Binary(binary_expr) => {
+            let lhs = || evaluate_condition(&binary_expr.lhs);
+            let rhs = || evaluate_condition(&binary_expr.rhs);
+            match binary_expr.op {
+                BinaryOp::And => match (lhs(), rhs()) {
+                    (Some(false), _) | (_, Some(false)) => Some(false),
+                    (Some(true), Some(true)) => Some(true),
+                    _ => None,
+                },
+                BinaryOp::Or => match (lhs(), rhs()) {
+                    (Some(true), _) | (_, Some(true)) => Some(true),
+                    (Some(false), Some(false)) => Some(false),
+                    _ => None,
+                },
+                BinaryOp::Eq => evaluate_os_comparison(&binary_expr.lhs, &binary_expr.rhs),
+                BinaryOp::Ne => {
+                    evaluate_os_comparison(&binary_expr.lhs, &binary_expr.rhs).map(|eq| !eq)
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Returns the spans of condition branches never taken on the platform the
+/// analysis runs on.
+pub fn collect_dead_branches<'i>(block: &Block<'i>) -> Vec<Span<'i>> {
+    let mut spans = Vec::new();
+    for node in block.walk() {
+        let Some(Statement::Condition(top_condition)) = node.as_statement() else {
+            continue;
+        };
+        let mut condition = top_condition;
+        loop {
+            match evaluate_condition(&condition.condition) {
+                Some(true) => {
+                    match &condition.else_block {
+                        Some(Either::Left(next_condition)) => spans.push(next_condition.span),
+                        Some(Either::Right(else_block)) => spans.push(else_block.span),
+                        None => {}
+                    }
+                    break;
+                }
+                Some(false) => spans.push(condition.then_block.span),
+                None => {}
+            }
+            match &condition.else_block {
+                Some(Either::Left(next_condition)) => condition = next_condition,
+                _ => break,
+            }
+        }
+    }
+    spans
+}
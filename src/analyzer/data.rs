@@ -13,24 +13,31 @@
 // limitations under the License.
 
 use std::{
+    borrow::Cow,
     collections::{BTreeSet, HashMap},
     path::{Path, PathBuf},
     pin::Pin,
     sync::Arc,
-    time::Instant,
 };
 
 use either::Either;
 use pest::Span;
-use tower_lsp::lsp_types::DocumentSymbol;
+use tower_lsp::lsp_types::{DocumentSymbol, Range};
 
 use crate::{
-    analyzer::{cache::CacheNode, toplevel::TopLevelStatementsExt, utils::resolve_path},
+    analyzer::{
+        cache::{CacheConfig, CacheNode, CachedFile},
+        toplevel::TopLevelStatementsExt,
+        utils::{normalize_path_case, resolve_path},
+    },
     common::{
+        builtins::TARGET_NAME,
         storage::{Document, DocumentVersion},
         utils::parse_simple_literal,
     },
-    parser::{parse, Assignment, Block, Call, Comments, Condition, Expr, Identifier},
+    parser::{
+        parse, AssignOp, Assignment, Block, Call, Comments, Condition, Expr, Identifier, LValue,
+    },
 };
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -43,12 +50,34 @@ pub struct PathSpan<'i> {
 pub struct WorkspaceContext {
     pub root: PathBuf,
     pub dot_gn_version: DocumentVersion,
-    pub build_config: PathBuf,
+    /// The BUILDCONFIG.gn file, absent for workspaces without `.gn`.
+    pub build_config: Option<PathBuf>,
+    /// Directories to look up BUILD.gn files in when a source-absolute label
+    /// does not exist under the root.
+    pub label_search_roots: Vec<PathBuf>,
+    /// Whether paths differing only in casing refer to the same file.
+    pub case_insensitive: bool,
 }
 
 impl WorkspaceContext {
     pub fn resolve_path(&self, name: &str, current_dir: &Path) -> PathBuf {
-        resolve_path(name, &self.root, current_dir)
+        self.normalize_case(&resolve_path(name, &self.root, current_dir))
+    }
+
+    /// Returns `path` with the casing on disk if the workspace is
+    /// case-insensitive, so that it can be used as a cache key.
+    pub fn normalize_case(&self, path: &Path) -> PathBuf {
+        if self.case_insensitive {
+            normalize_path_case(path, &self.root)
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    /// Returns the directory containing `path`, falling back to the workspace
+    /// root if `path` has no parent.
+    pub fn current_dir<'a>(&'a self, path: &'a Path) -> &'a Path {
+        path.parent().unwrap_or(&self.root)
     }
 }
 
@@ -139,6 +168,8 @@ pub struct ShallowAnalyzedFile {
     pub ast: Pin<Box<Block<'static>>>,
     pub environment: FileEnvironment<'static, 'static>,
     pub links: Vec<AnalyzedLink<'static>>,
+    /// Whether the file is in a case-insensitive workspace.
+    pub case_insensitive: bool,
     pub node: Arc<CacheNode>,
 }
 
@@ -148,20 +179,22 @@ impl ShallowAnalyzedFile {
         ast: Pin<Box<Block<'static>>>,
         environment: FileEnvironment<'static, 'static>,
         links: Vec<AnalyzedLink<'static>>,
+        case_insensitive: bool,
         deps: Vec<Arc<CacheNode>>,
-        request_time: Instant,
+        cache_config: CacheConfig,
     ) -> Pin<Arc<Self>> {
-        let node = CacheNode::new(document.path.clone(), document.version, deps, request_time);
+        let node = CacheNode::new(document.path.clone(), document.version, deps, cache_config);
         Arc::pin(Self {
             document,
             ast,
             environment,
             links,
+            case_insensitive,
             node,
         })
     }
 
-    pub fn error(path: &Path, request_time: Instant) -> Pin<Arc<Self>> {
+    pub fn error(path: &Path, cache_config: CacheConfig) -> Pin<Arc<Self>> {
         let document = Arc::pin(Document::analysis_error(path));
         let ast = Box::pin(parse(&document.data));
         let environment = FileEnvironment::new();
@@ -175,12 +208,19 @@ impl ShallowAnalyzedFile {
             ast,
             environment,
             Vec::new(),
+            false,
             Vec::new(),
-            request_time,
+            cache_config,
         )
     }
 }
 
+impl CachedFile for ShallowAnalyzedFile {
+    fn node(&self) -> &Arc<CacheNode> {
+        &self.node
+    }
+}
+
 #[derive(Default)]
 pub struct MutableFileEnvironment<'i, 'p> {
     pub variables: VariableScope<'i, 'p>,
@@ -201,6 +241,12 @@ impl<'i, 'p> MutableFileEnvironment<'i, 'p> {
         self.targets.import(&env.targets);
     }
 
+    pub fn import_args(&mut self, env: &FileEnvironment<'i, 'p>) {
+        self.variables.import_args(&env.variables);
+        self.templates.import(&env.templates);
+        self.targets.import(&env.targets);
+    }
+
     pub fn finalize(self) -> FileEnvironment<'i, 'p> {
         FileEnvironment {
             variables: Arc::new(self.variables),
@@ -243,9 +289,9 @@ impl AnalyzedFile {
         links: Vec<AnalyzedLink<'static>>,
         symbols: Vec<DocumentSymbol>,
         deps: Vec<Arc<CacheNode>>,
-        request_time: Instant,
+        cache_config: CacheConfig,
     ) -> Pin<Arc<Self>> {
-        let node = CacheNode::new(document.path.clone(), document.version, deps, request_time);
+        let node = CacheNode::new(document.path.clone(), document.version, deps, cache_config);
         Arc::pin(Self {
             document,
             workspace_root,
@@ -264,6 +310,26 @@ impl AnalyzedFile {
     pub fn templates_at(&self, pos: usize) -> TemplateScope {
         self.analyzed_root.templates_at(pos)
     }
+
+    /// Returns the BUILDCONFIG.gn file implicitly imported by the file, if
+    /// the workspace has one.
+    pub fn build_config(&self) -> Option<&Path> {
+        self.analyzed_root
+            .statements
+            .iter()
+            .find_map(|statement| match statement {
+                AnalyzedStatement::SyntheticImport(import) => {
+                    Some(import.file.document.path.as_path())
+                }
+                _ => None,
+            })
+    }
+}
+
+impl CachedFile for AnalyzedFile {
+    fn node(&self) -> &Arc<CacheNode> {
+        &self.node
+    }
 }
 
 #[derive(Clone)]
@@ -282,6 +348,20 @@ impl<'i, 'p> AnalyzedBlock<'i, 'p> {
         })
     }
 
+    /// Returns the paths of imported files along with the spans importing them.
+    pub fn imports<'a>(&'a self) -> impl Iterator<Item = (Span<'i>, &'a Path)> + 'a {
+        self.top_level_statements()
+            .filter_map(|statement| match statement {
+                AnalyzedStatement::Import(import) => {
+                    Some((import.call.span, import.file.document.path.as_path()))
+                }
+                AnalyzedStatement::SyntheticImport(import) => {
+                    Some((import.span, import.file.document.path.as_path()))
+                }
+                _ => None,
+            })
+    }
+
     pub fn variables_at(&self, pos: usize) -> VariableScope<'i, 'p> {
         let mut variables = VariableScope::new();
 
@@ -342,8 +422,11 @@ impl<'i, 'p> AnalyzedBlock<'i, 'p> {
                     }
                 }
                 AnalyzedStatement::Import(import) => {
-                    // TODO: Handle import() within declare_args.
-                    variables.import(&import.file.environment.variables);
+                    if declare_args_stack.is_empty() {
+                        variables.import(&import.file.environment.variables);
+                    } else {
+                        variables.import_args(&import.file.environment.variables);
+                    }
                 }
                 AnalyzedStatement::SyntheticImport(import) => {
                     variables.import(&import.file.environment.variables);
@@ -371,6 +454,19 @@ impl<'i, 'p> AnalyzedBlock<'i, 'p> {
         variables
     }
 
+    /// Returns the innermost subscope containing the position. Positions in
+    /// the same subscope see the same variables.
+    pub fn scope_at(&self, pos: usize) -> &AnalyzedBlock<'i, 'p> {
+        for statement in self.top_level_statements() {
+            for scope in statement.subscopes() {
+                if scope.span.start() < pos && pos < scope.span.end() {
+                    return scope.scope_at(pos);
+                }
+            }
+        }
+        self
+    }
+
     pub fn templates_at(&self, pos: usize) -> TemplateScope<'i, 'p> {
         let mut templates = TemplateScope::new();
 
@@ -579,15 +675,27 @@ pub struct Target<'i, 'p> {
     pub document: &'i Document,
     pub call: &'p Call<'i>,
     pub name: &'i str,
+    /// Whether the target is named `target_name` inside a template, whose
+    /// value is only known where the template is invoked.
+    pub implicit_name: bool,
 }
 
 impl<'i, 'p> AnalyzedTarget<'i, 'p> {
     pub fn as_target(&self, document: &'i Document) -> Option<Target<'i, 'p>> {
-        let name = self.name.as_simple_string()?;
-        Some(Target {
+        if let Some(name) = self.name.as_simple_string() {
+            return Some(Target {
+                document,
+                call: self.call,
+                name,
+                implicit_name: false,
+            });
+        }
+        let identifier = self.name.as_primary_identifier()?;
+        (identifier.name == TARGET_NAME).then_some(Target {
             document,
             call: self.call,
-            name,
+            name: identifier.name,
+            implicit_name: true,
         })
     }
 }
@@ -610,6 +718,11 @@ impl<'i, 'p> AnalyzedTemplate<'i, 'p> {
             comments: self.comments.clone(),
         })
     }
+
+    /// Returns targets defined at the top level of the template body.
+    pub fn targets<'a>(&'a self) -> impl Iterator<Item = Target<'i, 'p>> + 'a {
+        self.body_block.targets()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -618,13 +731,73 @@ pub struct Variable<'i, 'p> {
     pub is_args: bool,
 }
 
-impl Variable<'_, '_> {
+impl<'i, 'p> Variable<'i, 'p> {
     pub fn new(is_args: bool) -> Self {
         Self {
             assignments: HashMap::new(),
             is_args,
         }
     }
+
+    /// Returns assignments in a deterministic order, sorted by their locations.
+    pub fn sorted_assignments(&self) -> Vec<&VariableAssignment<'i, 'p>> {
+        let mut assignments: Vec<_> = self.assignments.values().collect();
+        assignments.sort_by_key(|assignment| assignment.sort_key());
+        assignments
+    }
+
+    /// Returns the first `limit` assignments of `sorted_assignments` without
+    /// sorting all of them, for variables assigned in many places.
+    pub fn first_assignments(&self, limit: usize) -> Vec<&VariableAssignment<'i, 'p>> {
+        let mut assignments: Vec<_> = self.assignments.values().collect();
+        if limit < assignments.len() {
+            assignments.select_nth_unstable_by_key(limit, |assignment| assignment.sort_key());
+            assignments.truncate(limit);
+        }
+        assignments.sort_by_key(|assignment| assignment.sort_key());
+        assignments
+    }
+
+    /// Returns the value of a variable assigned exactly once with a simple
+    /// string literal, e.g. `suffix = "foo"`.
+    pub fn constant_string(&self) -> Option<&'i str> {
+        if self.assignments.len() != 1 {
+            return None;
+        }
+        match self.assignments.values().next()?.assignment_or_call {
+            Either::Left(assignment)
+                if assignment.op == AssignOp::Assign
+                    && matches!(assignment.lvalue, LValue::Identifier(_)) =>
+            {
+                assignment.rvalue.as_simple_string()
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'i, 'p> VariableScope<'i, 'p> {
+    /// Imports variables as build arguments, as an import() within
+    /// declare_args() does.
+    pub fn import_args(&mut self, other: &VariableScope<'i, 'p>) {
+        for (name, variable) in other.all_items() {
+            let args = self.ensure(name, || Variable::new(true));
+            args.is_args = true;
+            args.assignments.extend(variable.assignments);
+        }
+    }
+}
+
+/// A build argument declared in `declare_args()`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgInfo {
+    pub name: String,
+    /// Source text of the default value, or `None` if the argument is defined
+    /// by a builtin call such as `forward_variables_from()`.
+    pub default_value: Option<String>,
+    pub path: PathBuf,
+    pub range: Range,
 }
 
 #[derive(Clone, Debug)]
@@ -635,6 +808,19 @@ pub struct VariableAssignment<'i, 'p> {
     pub comments: Comments<'i>,
 }
 
+impl<'i> VariableAssignment<'i, '_> {
+    pub fn span(&self) -> Span<'i> {
+        match &self.assignment_or_call {
+            Either::Left(assignment) => assignment.span,
+            Either::Right(call) => call.span,
+        }
+    }
+
+    fn sort_key(&self) -> (&'i Path, usize) {
+        (&self.document.path, self.span().start())
+    }
+}
+
 impl<'i, 'p> AnalyzedAssignment<'i, 'p> {
     pub fn as_variable_assignment(&self, document: &'i Document) -> VariableAssignment<'i, 'p> {
         VariableAssignment {
@@ -700,11 +886,18 @@ pub type VariableScope<'i, 'p> = Environment<'i, Variable<'i, 'p>>;
 #[derive(Clone, Eq, PartialEq)]
 pub enum AnalyzedLink<'i> {
     /// Link to a file. No range is specified.
-    File { path: PathBuf, span: Span<'i> },
+    File {
+        path: PathBuf,
+        span: Span<'i>,
+        /// Whether the file existed when the link was collected. Only imports
+        /// link to missing files.
+        exists: bool,
+    },
     /// Link to a target defined in a BUILD.gn file.
     Target {
         path: PathBuf,
-        name: &'i str,
+        /// Target name, owned when substituted from variables.
+        name: Cow<'i, str>,
         span: Span<'i>,
     },
 }
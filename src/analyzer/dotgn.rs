@@ -14,18 +14,45 @@
 
 use std::path::{Path, PathBuf};
 
+use tower_lsp::lsp_types::Range;
+
 use crate::{
     analyzer::utils::resolve_path,
-    common::{
-        error::{Error, Result},
-        utils::LineIndex,
-    },
+    common::utils::LineIndex,
     parser::{parse, AssignOp, LValue, Statement},
 };
 
-pub fn evaluate_dot_gn(workspace_root: &Path, input: &str) -> Result<PathBuf> {
+/// An error in a `.gn` file, located at the offending statement if any.
+#[derive(thiserror::Error, Clone, Debug, Eq, PartialEq)]
+#[error("{}: {message}", self.location())]
+pub struct DotGnError {
+    pub path: PathBuf,
+    pub range: Option<Range>,
+    pub message: String,
+}
+
+impl DotGnError {
+    fn location(&self) -> String {
+        match &self.range {
+            Some(range) => format!(
+                "{}:{}:{}",
+                self.path.to_string_lossy(),
+                range.start.line + 1,
+                range.start.character + 1
+            ),
+            None => self.path.to_string_lossy().to_string(),
+        }
+    }
+}
+
+pub fn evaluate_dot_gn(workspace_root: &Path, input: &str) -> Result<PathBuf, DotGnError> {
     let line_index = LineIndex::new(input);
     let ast = parse(input);
+    let error = |range: Option<Range>, message: &str| DotGnError {
+        path: workspace_root.join(".gn"),
+        range,
+        message: message.to_string(),
+    };
 
     let mut build_config_path: Option<PathBuf> = None;
 
@@ -38,43 +65,25 @@ pub fn evaluate_dot_gn(workspace_root: &Path, input: &str) -> Result<PathBuf> {
             continue;
         }
 
-        let position = line_index.position(assignment.span.start());
+        let range = Some(line_index.range(assignment.span));
 
         if assignment.op != AssignOp::Assign {
-            return Err(Error::General(format!(
-                "{}:{}:{}: buildconfig must be assigned exactly once",
-                workspace_root.join(".gn").to_string_lossy(),
-                position.line + 1,
-                position.character + 1
-            )));
+            return Err(error(range, "buildconfig must be assigned exactly once"));
         }
         let Some(name) = assignment.rvalue.as_simple_string() else {
-            return Err(Error::General(format!(
-                "{}:{}:{}: buildconfig is not a simple string",
-                workspace_root.join(".gn").to_string_lossy(),
-                position.line + 1,
-                position.character + 1
-            )));
+            return Err(error(range, "buildconfig is not a simple string"));
         };
 
         if build_config_path
             .replace(resolve_path(name, workspace_root, workspace_root))
             .is_some()
         {
-            return Err(Error::General(format!(
-                "{}:{}:{}: buildconfig is assigned multiple times",
-                workspace_root.join(".gn").to_string_lossy(),
-                position.line + 1,
-                position.character + 1
-            )));
+            return Err(error(range, "buildconfig is assigned multiple times"));
         }
     }
 
     let Some(build_config_path) = build_config_path else {
-        return Err(Error::General(format!(
-            "{}: buildconfig is not assigned directly",
-            workspace_root.join(".gn").to_string_lossy()
-        )));
+        return Err(error(None, "buildconfig is not assigned directly"));
     };
 
     Ok(build_config_path)
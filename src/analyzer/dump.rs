@@ -0,0 +1,233 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Stable textual snapshots of analysis results for golden tests.
+
+#![cfg(test)]
+
+use std::{fmt::Write, path::Path};
+
+use either::Either;
+use pest::Span;
+
+use crate::{
+    analyzer::data::{
+        AnalyzedBlock, AnalyzedCondition, AnalyzedFile, AnalyzedStatement, ShallowAnalyzedFile,
+    },
+    parser::Node,
+};
+
+fn format_pos(span: Span) -> String {
+    let (line, col) = span.start_pos().line_col();
+    format!("@{line}:{col}")
+}
+
+fn format_path(path: &Path, workspace_root: &Path) -> String {
+    match path.strip_prefix(workspace_root) {
+        Ok(relative) => format!("//{}", relative.to_string_lossy().replace('\\', "/")),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+struct Dumper<'a> {
+    workspace_root: &'a Path,
+    output: String,
+}
+
+impl Dumper<'_> {
+    fn line(&mut self, depth: usize, text: impl AsRef<str>) {
+        writeln!(self.output, "{}{}", "  ".repeat(depth), text.as_ref()).unwrap();
+    }
+
+    fn scopes<'a, 'i: 'a + 'p, 'p: 'a>(
+        &mut self,
+        depth: usize,
+        scopes: impl IntoIterator<Item = &'a AnalyzedBlock<'i, 'p>>,
+    ) {
+        let mut scopes: Vec<_> = scopes.into_iter().collect();
+        scopes.sort_by_key(|scope| scope.span.start());
+        for scope in scopes {
+            self.block(depth, scope);
+        }
+    }
+
+    fn block(&mut self, depth: usize, block: &AnalyzedBlock) {
+        self.line(depth, format!("scope {}", format_pos(block.span)));
+        for statement in &block.statements {
+            self.statement(depth + 1, statement);
+        }
+    }
+
+    fn condition(&mut self, depth: usize, condition: &AnalyzedCondition, keyword: &str) {
+        self.line(
+            depth,
+            format!(
+                "{keyword} {} {}",
+                condition.condition.condition.span().as_str(),
+                format_pos(condition.condition.span)
+            ),
+        );
+        self.scopes(
+            depth + 1,
+            condition.expr_scopes.iter().chain([&condition.then_block]),
+        );
+        match &condition.else_block {
+            Some(Either::Left(next_condition)) => self.condition(depth, next_condition, "else if"),
+            Some(Either::Right(last_block)) => {
+                self.line(depth, "else");
+                self.block(depth + 1, last_block);
+            }
+            None => {}
+        }
+    }
+
+    fn statement(&mut self, depth: usize, statement: &AnalyzedStatement) {
+        let pos = format_pos(statement.span());
+        match statement {
+            AnalyzedStatement::Assignment(assignment) => {
+                self.line(
+                    depth,
+                    format!("assignment {} {pos}", assignment.primary_variable.as_str()),
+                );
+                self.scopes(depth + 1, &assignment.expr_scopes);
+            }
+            AnalyzedStatement::Conditions(condition) => {
+                self.condition(depth, condition, "if");
+            }
+            AnalyzedStatement::DeclareArgs(declare_args) => {
+                self.line(depth, format!("declare_args {pos}"));
+                self.block(depth + 1, &declare_args.body_block);
+            }
+            AnalyzedStatement::Foreach(foreach) => {
+                self.line(
+                    depth,
+                    format!("foreach {} {pos}", foreach.loop_variable.name),
+                );
+                self.scopes(
+                    depth + 1,
+                    foreach.expr_scopes.iter().chain([&foreach.body_block]),
+                );
+            }
+            AnalyzedStatement::ForwardVariablesFrom(forward_variables_from) => {
+                self.line(depth, format!("forward_variables_from {pos}"));
+                self.scopes(depth + 1, &forward_variables_from.expr_scopes);
+            }
+            AnalyzedStatement::Import(import) => {
+                self.line(
+                    depth,
+                    format!(
+                        "import {} {pos}",
+                        format_path(&import.file.document.path, self.workspace_root)
+                    ),
+                );
+            }
+            AnalyzedStatement::Target(target) => {
+                self.line(
+                    depth,
+                    format!(
+                        "target {} {} {pos}",
+                        target.call.function.name,
+                        target.name.span().as_str()
+                    ),
+                );
+                self.scopes(depth + 1, statement.subscopes());
+            }
+            AnalyzedStatement::Template(template) => {
+                self.line(
+                    depth,
+                    format!("template {} {pos}", template.name.span().as_str()),
+                );
+                self.scopes(depth + 1, statement.subscopes());
+            }
+            AnalyzedStatement::BuiltinCall(builtin_call) => {
+                self.line(
+                    depth,
+                    format!("call {} {pos}", builtin_call.call.function.name),
+                );
+                self.scopes(depth + 1, statement.subscopes());
+            }
+            AnalyzedStatement::SyntheticImport(synthetic_import) => {
+                self.line(
+                    depth,
+                    format!(
+                        "synthetic_import {}",
+                        format_path(&synthetic_import.file.document.path, self.workspace_root)
+                    ),
+                );
+            }
+        }
+    }
+}
+
+impl AnalyzedFile {
+    /// Returns a stable textual snapshot of the analyzed statements and their
+    /// scopes. Positions are 1-based `line:column` pairs.
+    pub fn debug_dump(&self) -> String {
+        let mut dumper = Dumper {
+            workspace_root: &self.workspace_root,
+            output: String::new(),
+        };
+        for statement in &self.analyzed_root.statements {
+            dumper.statement(0, statement);
+        }
+        dumper.output
+    }
+}
+
+impl ShallowAnalyzedFile {
+    /// Returns a stable textual snapshot of the variables, templates and
+    /// targets defined in the file, sorted by name. Positions are 1-based
+    /// `line:column` pairs.
+    pub fn debug_dump(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("variables:\n");
+        let mut variables: Vec<_> = self.environment.variables.locals().iter().collect();
+        variables.sort_by_key(|(name, _)| **name);
+        for (name, variable) in variables {
+            let mut line = format!("  {name}");
+            if variable.is_args {
+                line.push_str(" (args)");
+            }
+            for assignment in variable.sorted_assignments() {
+                if assignment.document.path == self.document.path {
+                    write!(line, " {}", format_pos(assignment.primary_variable)).unwrap();
+                }
+            }
+            writeln!(output, "{line}").unwrap();
+        }
+
+        output.push_str("templates:\n");
+        let mut templates: Vec<_> = self.environment.templates.locals().values().collect();
+        templates.sort_by_key(|template| template.name);
+        for template in templates {
+            writeln!(
+                output,
+                "  {} {}",
+                template.name,
+                format_pos(template.call.span)
+            )
+            .unwrap();
+        }
+
+        output.push_str("targets:\n");
+        let mut targets: Vec<_> = self.environment.targets.locals().values().collect();
+        targets.sort_by_key(|target| target.name);
+        for target in targets {
+            writeln!(output, "  {} {}", target.name, format_pos(target.call.span)).unwrap();
+        }
+
+        output
+    }
+}
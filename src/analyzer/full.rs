@@ -13,11 +13,9 @@
 // limitations under the License.
 
 use std::{
-    collections::BTreeMap,
     path::{Path, PathBuf},
     pin::Pin,
     sync::{Arc, Mutex},
-    time::Instant,
 };
 
 use either::Either;
@@ -25,13 +23,14 @@ use pest::Span;
 
 use crate::{
     analyzer::{
-        cache::CacheNode,
+        cache::{CacheConfig, CacheNode, LruCache},
         data::{
             AnalyzedBuiltinCall, AnalyzedCondition, AnalyzedDeclareArgs, AnalyzedForeach,
             AnalyzedForwardVariablesFrom, AnalyzedStatement, SyntheticImport,
         },
         links::collect_links,
-        shallow::{ShallowAnalysisSnapshot, ShallowAnalyzer},
+        metrics::AnalysisMetrics,
+        shallow::{ShallowAnalysisSnapshot, ShallowAnalyzer, SharedShallowCache},
         symbols::collect_symbols,
         AnalyzedAssignment, AnalyzedBlock, AnalyzedFile, AnalyzedImport, AnalyzedLink,
         AnalyzedTarget, AnalyzedTemplate, WorkspaceContext,
@@ -47,17 +46,23 @@ pub struct FullAnalyzer {
     context: WorkspaceContext,
     shallow_analyzer: ShallowAnalyzer,
     storage: Arc<Mutex<DocumentStorage>>,
-    #[allow(clippy::type_complexity)]
-    cache: BTreeMap<PathBuf, Pin<Arc<AnalyzedFile>>>,
+    cache: LruCache<PathBuf, AnalyzedFile>,
+    metrics: Arc<AnalysisMetrics>,
 }
 
 impl FullAnalyzer {
-    pub fn new(context: &WorkspaceContext, storage: &Arc<Mutex<DocumentStorage>>) -> Self {
+    pub fn new(
+        context: &WorkspaceContext,
+        storage: &Arc<Mutex<DocumentStorage>>,
+        shared_cache: &SharedShallowCache,
+        metrics: &Arc<AnalysisMetrics>,
+    ) -> Self {
         Self {
             context: context.clone(),
             storage: storage.clone(),
-            shallow_analyzer: ShallowAnalyzer::new(context, storage),
+            shallow_analyzer: ShallowAnalyzer::new(context, storage, shared_cache, metrics),
             cache: Default::default(),
+            metrics: metrics.clone(),
         }
     }
 
@@ -65,48 +70,61 @@ impl FullAnalyzer {
         &mut self.shallow_analyzer
     }
 
-    pub fn analyze(&mut self, path: &Path, request_time: Instant) -> Pin<Arc<AnalyzedFile>> {
-        self.analyze_cached(path, request_time)
+    pub fn analyze(&mut self, path: &Path, cache_config: CacheConfig) -> Pin<Arc<AnalyzedFile>> {
+        self.analyze_cached(path, cache_config)
     }
 
-    fn analyze_cached(&mut self, path: &Path, request_time: Instant) -> Pin<Arc<AnalyzedFile>> {
+    fn analyze_cached(&mut self, path: &Path, cache_config: CacheConfig) -> Pin<Arc<AnalyzedFile>> {
         if let Some(cached_file) = self.cache.get(path) {
             if cached_file
                 .node
-                .verify(request_time, &self.storage.lock().unwrap())
+                .verify(cache_config, &self.storage.lock().unwrap())
             {
-                return cached_file.clone();
+                self.metrics.record_cache(true);
+                return cached_file;
             }
         }
 
-        let new_file = self.analyze_uncached(path, request_time);
-        self.cache.insert(path.to_path_buf(), new_file.clone());
+        self.metrics.record_cache(false);
+        let metrics = self.metrics.clone();
+        let new_file = metrics.time_full(|| self.analyze_uncached(path, cache_config));
+        self.cache
+            .insert(path.to_path_buf(), new_file.clone(), cache_config.capacity);
         new_file
     }
 
-    fn analyze_uncached(&mut self, path: &Path, request_time: Instant) -> Pin<Arc<AnalyzedFile>> {
+    fn analyze_uncached(
+        &mut self,
+        path: &Path,
+        cache_config: CacheConfig,
+    ) -> Pin<Arc<AnalyzedFile>> {
         let document = self.storage.lock().unwrap().read(path);
-        let ast = Box::pin(parse(&document.data));
+        let ast = Box::pin(self.metrics.time_parse(|| parse(&document.data)));
 
         let mut deps = Vec::new();
         let mut snapshot = ShallowAnalysisSnapshot::new();
         let mut analyzed_root =
-            self.analyze_block(&ast, &document, request_time, &mut snapshot, &mut deps);
+            self.analyze_block(&ast, &document, cache_config, &mut snapshot, &mut deps);
 
         // Insert a synthetic import of BUILDCONFIG.gn.
-        let dot_gn_file =
-            self.shallow_analyzer
-                .analyze(&self.context.build_config, request_time, &mut snapshot);
-        analyzed_root.statements.insert(
-            0,
-            AnalyzedStatement::SyntheticImport(Box::new(SyntheticImport {
-                file: dot_gn_file.clone(),
-                span: Span::new(&document.data, 0, 0).unwrap(),
-            })),
-        );
-        deps.push(dot_gn_file.node.clone());
+        if let Some(build_config) = &self.context.build_config {
+            let dot_gn_file =
+                self.shallow_analyzer
+                    .analyze(build_config, cache_config, &mut snapshot);
+            analyzed_root.statements.insert(
+                0,
+                AnalyzedStatement::SyntheticImport(Box::new(SyntheticImport {
+                    file: dot_gn_file.clone(),
+                    span: Span::new(&document.data, 0, 0).unwrap(),
+                })),
+            );
+            deps.push(dot_gn_file.node.clone());
+        }
 
-        let links = collect_links(&ast, path, &self.context);
+        let links = collect_links(&ast, path, &self.context, |name, offset| {
+            let variables = analyzed_root.variables_at(offset);
+            variables.get(name)?.constant_string().map(str::to_string)
+        });
         let symbols = collect_symbols(ast.as_node(), &document.line_index);
 
         // SAFETY: links' contents are backed by pinned document.
@@ -125,7 +143,7 @@ impl FullAnalyzer {
             links,
             symbols,
             deps,
-            request_time,
+            cache_config,
         )
     }
 
@@ -133,7 +151,7 @@ impl FullAnalyzer {
         &mut self,
         block: &'p Block<'i>,
         document: &'i Document,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         deps: &mut Vec<Arc<CacheNode>>,
     ) -> AnalyzedBlock<'i, 'p> {
@@ -149,7 +167,7 @@ impl FullAnalyzer {
                             self.analyze_expr(
                                 &array_access.index,
                                 document,
-                                request_time,
+                                cache_config,
                                 snapshot,
                                 deps,
                             ),
@@ -159,7 +177,7 @@ impl FullAnalyzer {
                     expr_scopes.extend(self.analyze_expr(
                         &assignment.rvalue,
                         document,
-                        request_time,
+                        cache_config,
                         snapshot,
                         deps,
                     ));
@@ -176,14 +194,14 @@ impl FullAnalyzer {
                     statements.push(self.analyze_call(
                         call,
                         document,
-                        request_time,
+                        cache_config,
                         snapshot,
                         deps,
                     ));
                 }
                 Statement::Condition(condition) => {
                     statements.push(AnalyzedStatement::Conditions(Box::new(
-                        self.analyze_condition(condition, document, request_time, snapshot, deps),
+                        self.analyze_condition(condition, document, cache_config, snapshot, deps),
                     )));
                 }
                 Statement::Error(_) => {}
@@ -202,14 +220,14 @@ impl FullAnalyzer {
         &mut self,
         call: &'p Call<'i>,
         document: &'i Document,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         deps: &mut Vec<Arc<CacheNode>>,
     ) -> AnalyzedStatement<'i, 'p> {
         let body_block = call
             .block
             .as_ref()
-            .map(|block| self.analyze_block(block, document, request_time, snapshot, deps));
+            .map(|block| self.analyze_block(block, document, cache_config, snapshot, deps));
 
         let body_block = match (call.function.name, body_block) {
             (DECLARE_ARGS, Some(body_block)) => {
@@ -223,7 +241,7 @@ impl FullAnalyzer {
                     if let Some(loop_variable) = call.args[0].as_primary_identifier() {
                         let loop_items = &call.args[1];
                         let expr_scopes =
-                            self.analyze_expr(loop_items, document, request_time, snapshot, deps);
+                            self.analyze_expr(loop_items, document, cache_config, snapshot, deps);
                         return AnalyzedStatement::Foreach(Box::new(AnalyzedForeach {
                             call,
                             loop_variable,
@@ -241,7 +259,7 @@ impl FullAnalyzer {
                         .args
                         .iter()
                         .flat_map(|expr| {
-                            self.analyze_expr(expr, document, request_time, snapshot, deps)
+                            self.analyze_expr(expr, document, cache_config, snapshot, deps)
                         })
                         .collect();
                     return AnalyzedStatement::ForwardVariablesFrom(Box::new(
@@ -258,8 +276,8 @@ impl FullAnalyzer {
                 if let Some(name) = call.only_arg().and_then(|expr| expr.as_simple_string()) {
                     let path = self
                         .context
-                        .resolve_path(name, document.path.parent().unwrap());
-                    let file = self.shallow_analyzer.analyze(&path, request_time, snapshot);
+                        .resolve_path(name, self.context.current_dir(&document.path));
+                    let file = self.shallow_analyzer.analyze(&path, cache_config, snapshot);
                     deps.push(file.node.clone());
                     return AnalyzedStatement::Import(Box::new(AnalyzedImport { call, file }));
                 }
@@ -271,7 +289,7 @@ impl FullAnalyzer {
                         .args
                         .iter()
                         .flat_map(|expr| {
-                            self.analyze_expr(expr, document, request_time, snapshot, deps)
+                            self.analyze_expr(expr, document, cache_config, snapshot, deps)
                         })
                         .collect();
                     return AnalyzedStatement::Template(Box::new(AnalyzedTemplate {
@@ -290,7 +308,7 @@ impl FullAnalyzer {
                         .args
                         .iter()
                         .flat_map(|expr| {
-                            self.analyze_expr(expr, document, request_time, snapshot, deps)
+                            self.analyze_expr(expr, document, cache_config, snapshot, deps)
                         })
                         .collect();
                     return AnalyzedStatement::Target(Box::new(AnalyzedTarget {
@@ -308,7 +326,7 @@ impl FullAnalyzer {
         let expr_scopes = call
             .args
             .iter()
-            .flat_map(|expr| self.analyze_expr(expr, document, request_time, snapshot, deps))
+            .flat_map(|expr| self.analyze_expr(expr, document, cache_config, snapshot, deps))
             .collect();
         AnalyzedStatement::BuiltinCall(Box::new(AnalyzedBuiltinCall {
             call,
@@ -321,16 +339,16 @@ impl FullAnalyzer {
         &mut self,
         condition: &'p Condition<'i>,
         document: &'i Document,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         deps: &mut Vec<Arc<CacheNode>>,
     ) -> AnalyzedCondition<'i, 'p> {
         let expr_scopes =
-            self.analyze_expr(&condition.condition, document, request_time, snapshot, deps);
+            self.analyze_expr(&condition.condition, document, cache_config, snapshot, deps);
         let then_block = self.analyze_block(
             &condition.then_block,
             document,
-            request_time,
+            cache_config,
             snapshot,
             deps,
         );
@@ -338,10 +356,10 @@ impl FullAnalyzer {
             match &condition.else_block {
                 None => None,
                 Some(Either::Left(next_condition)) => Some(Either::Left(Box::new(
-                    self.analyze_condition(next_condition, document, request_time, snapshot, deps),
+                    self.analyze_condition(next_condition, document, cache_config, snapshot, deps),
                 ))),
                 Some(Either::Right(last_block)) => Some(Either::Right(Box::new(
-                    self.analyze_block(last_block, document, request_time, snapshot, deps),
+                    self.analyze_block(last_block, document, cache_config, snapshot, deps),
                 ))),
             };
         AnalyzedCondition {
@@ -356,7 +374,7 @@ impl FullAnalyzer {
         &mut self,
         expr: &'p Expr<'i>,
         document: &'i Document,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         deps: &mut Vec<Arc<CacheNode>>,
     ) -> Vec<AnalyzedBlock<'i, 'p>> {
@@ -364,7 +382,7 @@ impl FullAnalyzer {
             Expr::Primary(primary_expr) => match primary_expr.as_ref() {
                 PrimaryExpr::Block(block) => {
                     let analyzed_block =
-                        self.analyze_block(block, document, request_time, snapshot, deps);
+                        self.analyze_block(block, document, cache_config, snapshot, deps);
                     vec![analyzed_block]
                 }
                 PrimaryExpr::Call(call) => {
@@ -372,24 +390,24 @@ impl FullAnalyzer {
                         .args
                         .iter()
                         .flat_map(|expr| {
-                            self.analyze_expr(expr, document, request_time, snapshot, deps)
+                            self.analyze_expr(expr, document, cache_config, snapshot, deps)
                         })
                         .collect();
                     if let Some(block) = &call.block {
                         let analyzed_block =
-                            self.analyze_block(block, document, request_time, snapshot, deps);
+                            self.analyze_block(block, document, cache_config, snapshot, deps);
                         analyzed_blocks.push(analyzed_block);
                     }
                     analyzed_blocks
                 }
                 PrimaryExpr::ParenExpr(paren_expr) => {
-                    self.analyze_expr(&paren_expr.expr, document, request_time, snapshot, deps)
+                    self.analyze_expr(&paren_expr.expr, document, cache_config, snapshot, deps)
                 }
                 PrimaryExpr::List(list_literal) => list_literal
                     .values
                     .iter()
                     .flat_map(|expr| {
-                        self.analyze_expr(expr, document, request_time, snapshot, deps)
+                        self.analyze_expr(expr, document, cache_config, snapshot, deps)
                     })
                     .collect(),
                 PrimaryExpr::Identifier(_)
@@ -400,15 +418,15 @@ impl FullAnalyzer {
                 | PrimaryExpr::Error(_) => Vec::new(),
             },
             Expr::Unary(unary_expr) => {
-                self.analyze_expr(&unary_expr.expr, document, request_time, snapshot, deps)
+                self.analyze_expr(&unary_expr.expr, document, cache_config, snapshot, deps)
             }
             Expr::Binary(binary_expr) => {
                 let mut analyzed_blocks =
-                    self.analyze_expr(&binary_expr.lhs, document, request_time, snapshot, deps);
+                    self.analyze_expr(&binary_expr.lhs, document, cache_config, snapshot, deps);
                 analyzed_blocks.extend(self.analyze_expr(
                     &binary_expr.rhs,
                     document,
-                    request_time,
+                    cache_config,
                     snapshot,
                     deps,
                 ));
@@ -12,14 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::{Path, PathBuf};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
 
 use crate::{
     analyzer::{data::AnalyzedLink, WorkspaceContext},
-    common::utils::parse_simple_literal,
-    parser::{Block, Node},
+    common::{builtins::IMPORT, utils::parse_simple_literal},
+    parser::{Block, Node, StringLiteral},
 };
 
+/// A link resolved to its destination, as reported to link-checking tools.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResolvedTarget {
+    /// Absolute path of the linked file, or the BUILD.gn file for a target.
+    pub path: PathBuf,
+    /// Target name. `None` for links to files.
+    pub name: Option<String>,
+    /// Whether the linked file or target exists.
+    pub exists: bool,
+    /// Whether the link is in a condition branch never taken on the platform
+    /// the analysis runs on, e.g. under `if (false)`.
+    pub unreachable: bool,
+}
+
+/// Returns the BUILD.gn file of a source-absolute directory, trying label
+/// search roots when it does not exist under the workspace root.
+fn resolve_build_file(rel_dir: &str, workspace: &WorkspaceContext) -> PathBuf {
+    let build_path = workspace.normalize_case(&workspace.root.join(rel_dir).join("BUILD.gn"));
+    if workspace.label_search_roots.is_empty() || build_path.exists() {
+        return build_path;
+    }
+    workspace
+        .label_search_roots
+        .iter()
+        .map(|root| workspace.normalize_case(&root.join(rel_dir).join("BUILD.gn")))
+        .find(|path| path.exists())
+        .unwrap_or(build_path)
+}
+
 #[allow(clippy::manual_map)]
 fn resolve_target<'s>(
     label: &'s str,
@@ -28,15 +62,20 @@ fn resolve_target<'s>(
 ) -> Option<(PathBuf, &'s str)> {
     if let Some((prefix, name)) = label.split_once(':') {
         if let Some(rel_dir) = prefix.strip_prefix("//") {
-            Some((workspace.root.join(rel_dir).join("BUILD.gn"), name))
+            Some((resolve_build_file(rel_dir, workspace), name))
         } else {
-            let build_path = current_path.parent().unwrap().join(prefix).join("BUILD.gn");
+            let build_path = workspace.normalize_case(
+                &workspace
+                    .current_dir(current_path)
+                    .join(prefix)
+                    .join("BUILD.gn"),
+            );
             build_path.exists().then_some((build_path, name))
         }
     } else if let Some(rel_dir) = label.strip_prefix("//") {
         if !rel_dir.is_empty() {
             Some((
-                workspace.root.join(rel_dir).join("BUILD.gn"),
+                resolve_build_file(rel_dir, workspace),
                 rel_dir.split('/').next_back().unwrap(),
             ))
         } else {
@@ -47,30 +86,186 @@ fn resolve_target<'s>(
     }
 }
 
+/// Returns the literal directory prefix of a string whose only interpolation
+/// is at the end, e.g. `//foo/bar/` of `"//foo/bar/$target_name"`.
+fn get_interpolated_dir_prefix<'i>(string: &StringLiteral<'i>) -> Option<&'i str> {
+    if string.embedded_exprs.len() != 1 {
+        return None;
+    }
+    let (prefix, interpolation) = string.raw_value.split_at(string.raw_value.find('$')?);
+    let is_identifier = |name: &str| {
+        name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+            && name
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    };
+    let is_trailing = if let Some(expr) = interpolation.strip_prefix("${") {
+        expr.strip_suffix('}')
+            .is_some_and(|expr| !expr.contains('}'))
+    } else {
+        is_identifier(&interpolation[1..])
+    };
+    (is_trailing && prefix.ends_with('/') && !prefix.contains('\\')).then_some(prefix)
+}
+
+/// Evaluates a string whose only interpolations are identifiers with constant
+/// values, e.g. `"foo_$suffix"` where `suffix = "bar"` is in scope.
+pub fn interpolate_constants(
+    string: &StringLiteral,
+    lookup_constant: &impl Fn(&str, usize) -> Option<String>,
+) -> Option<String> {
+    let raw_value = string.raw_value;
+    // The raw value starts after the opening quote.
+    let raw_start = string.span.start() + 1;
+    let mut value = String::new();
+    let mut pos = 0;
+    for expr in &string.embedded_exprs {
+        let identifier = expr.as_primary_identifier()?;
+        let start = identifier.span.start() - raw_start;
+        let end = identifier.span.end() - raw_start;
+        let before = &raw_value[pos..start];
+        let (literal, next) = if let Some(literal) = before.strip_suffix("${") {
+            raw_value[end..]
+                .starts_with('}')
+                .then_some((literal, end + 1))?
+        } else {
+            (before.strip_suffix('$')?, end)
+        };
+        value.push_str(parse_simple_literal(literal)?);
+        value.push_str(&lookup_constant(identifier.name, identifier.span.start())?);
+        pos = next;
+    }
+    value.push_str(parse_simple_literal(&raw_value[pos..])?);
+    Some(value)
+}
+
+/// Materializes a label whose only interpolations are identifiers with
+/// constant values, e.g. `//foo:$suffix` where `suffix = "bar"` is in scope.
+fn substitute_constants(
+    string: &StringLiteral,
+    lookup_constant: &impl Fn(&str, usize) -> Option<String>,
+) -> Option<String> {
+    let raw_value = string.raw_value;
+    if !raw_value.starts_with("//") && !raw_value.starts_with(':') {
+        return None;
+    }
+    interpolate_constants(string, lookup_constant)
+}
+
+/// Minimum number of linked files in a directory to check their existence by
+/// listing the directory once instead of checking each file.
+const BATCH_THRESHOLD: usize = 16;
+
+/// Checks existence of files, listing directories containing many of them
+/// (e.g. huge `sources` lists) once instead of checking files one by one.
+fn check_files_exist(paths: &[&Path]) -> Vec<bool> {
+    let mut counts: HashMap<&Path, usize> = HashMap::new();
+    for path in paths {
+        if let Some(dir) = path.parent() {
+            *counts.entry(dir).or_default() += 1;
+        }
+    }
+
+    let listings: HashMap<&Path, HashSet<OsString>> = counts
+        .into_iter()
+        .filter(|(_, count)| *count >= BATCH_THRESHOLD)
+        .filter_map(|(dir, _)| {
+            let entries = std::fs::read_dir(dir).ok()?;
+            Some((
+                dir,
+                entries
+                    .filter_map(|entry| Some(entry.ok()?.file_name()))
+                    .collect(),
+            ))
+        })
+        .collect();
+
+    paths
+        .iter()
+        .map(|path| {
+            let listed = path
+                .parent()
+                .and_then(|dir| listings.get(dir))
+                .zip(path.file_name())
+                .is_some_and(|(listing, name)| listing.contains(name));
+            // Fall back to checking the file itself, e.g. for case-insensitive
+            // file systems.
+            listed || matches!(path.try_exists(), Ok(true))
+        })
+        .collect()
+}
+
+/// Collects links in a file. `lookup_constant` returns the constant string
+/// value of a variable at an offset, used to resolve interpolated labels.
 pub fn collect_links<'i>(
     ast: &Block<'i>,
     path: &Path,
     workspace: &WorkspaceContext,
+    lookup_constant: impl Fn(&str, usize) -> Option<String>,
 ) -> Vec<AnalyzedLink<'i>> {
-    ast.strings()
-        .filter_map(|string| {
-            let content = parse_simple_literal(string.raw_value)?;
-            if !content.contains(":") && content.contains(".") {
-                let path = workspace.resolve_path(content, path.parent().unwrap());
-                if let Ok(true) = path.try_exists() {
-                    return Some(AnalyzedLink::File {
-                        path: path.to_path_buf(),
+    // Imported files are linked even if missing, so that a typo is easy to
+    // spot and the file can be created from the link.
+    let import_starts: HashSet<usize> = ast
+        .walk()
+        .filter_map(|node| node.as_call())
+        .filter(|call| call.function.name == IMPORT)
+        .filter_map(|call| Some(call.only_arg()?.as_primary_string()?.span.start()))
+        .collect();
+
+    let mut links = Vec::new();
+    let mut file_candidates = Vec::new();
+    for string in ast.strings() {
+        let Some(content) = parse_simple_literal(string.raw_value) else {
+            if let Some(label) = substitute_constants(string, &lookup_constant) {
+                if let Some((build_gn_path, name)) = resolve_target(&label, path, workspace) {
+                    links.push(AnalyzedLink::Target {
+                        path: build_gn_path,
+                        name: Cow::Owned(name.to_string()),
                         span: string.span,
                     });
+                    continue;
                 }
-            } else if let Some((build_gn_path, name)) = resolve_target(content, path, workspace) {
-                return Some(AnalyzedLink::Target {
-                    path: build_gn_path,
-                    name,
-                    span: string.span,
-                });
             }
-            None
-        })
-        .collect()
+            // Interpolated strings may name nonexistent files, so link only a
+            // literal directory prefix that exists.
+            if let Some(prefix) = get_interpolated_dir_prefix(string) {
+                let path = workspace.resolve_path(prefix, workspace.current_dir(path));
+                if path.is_dir() {
+                    links.push(AnalyzedLink::File {
+                        path,
+                        span: string.span,
+                        exists: true,
+                    });
+                }
+            }
+            continue;
+        };
+        if !content.contains(":") && content.contains(".") {
+            let path = workspace.resolve_path(content, workspace.current_dir(path));
+            let imported = import_starts.contains(&string.span.start());
+            file_candidates.push((path, string.span, imported));
+        } else if let Some((build_gn_path, name)) = resolve_target(content, path, workspace) {
+            links.push(AnalyzedLink::Target {
+                path: build_gn_path,
+                name: Cow::Borrowed(name),
+                span: string.span,
+            });
+        }
+    }
+
+    let paths: Vec<&Path> = file_candidates
+        .iter()
+        .map(|(path, _, _)| path.as_path())
+        .collect();
+    let exists = check_files_exist(&paths);
+    links.extend(
+        file_candidates
+            .into_iter()
+            .zip(exists)
+            .filter(|((_, _, imported), exists)| *exists || *imported)
+            .map(|((path, span, _), exists)| AnalyzedLink::File { path, span, exists }),
+    );
+
+    links.sort_by_key(|link| link.span().start());
+    links
 }
@@ -0,0 +1,85 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Counters of where analysis time goes in a workspace. Updated with relaxed
+/// atomics, so that they are cheap enough to stay enabled.
+#[derive(Default)]
+pub struct AnalysisMetrics {
+    parse_nanos: AtomicU64,
+    shallow_nanos: AtomicU64,
+    full_nanos: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+}
+
+impl AnalysisMetrics {
+    /// Runs `f` and adds its duration to `counter`.
+    fn time<T>(counter: &AtomicU64, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        counter.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+
+    pub fn time_parse<T>(&self, f: impl FnOnce() -> T) -> T {
+        Self::time(&self.parse_nanos, f)
+    }
+
+    pub fn time_shallow<T>(&self, f: impl FnOnce() -> T) -> T {
+        Self::time(&self.shallow_nanos, f)
+    }
+
+    pub fn time_full<T>(&self, f: impl FnOnce() -> T) -> T {
+        Self::time(&self.full_nanos, f)
+    }
+
+    pub fn record_cache(&self, hit: bool) {
+        let counter = if hit {
+            &self.cache_hits
+        } else {
+            &self.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> AnalysisStats {
+        let millis = |counter: &AtomicU64| {
+            Duration::from_nanos(counter.load(Ordering::Relaxed)).as_secs_f64() * 1000.0
+        };
+        AnalysisStats {
+            parse_ms: millis(&self.parse_nanos),
+            shallow_analysis_ms: millis(&self.shallow_nanos),
+            full_analysis_ms: millis(&self.full_nanos),
+            cache_hits: self.cache_hits.load(Ordering::Relaxed),
+            cache_misses: self.cache_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A snapshot of `AnalysisMetrics`. Analysis times include parsing and
+/// analyzing imported files.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisStats {
+    pub parse_ms: f64,
+    pub shallow_analysis_ms: f64,
+    pub full_analysis_ms: f64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
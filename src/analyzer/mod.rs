@@ -17,43 +17,72 @@ use std::{
     path::{Path, PathBuf},
     pin::Pin,
     sync::{Arc, Mutex, RwLock},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 pub use data::{
     AnalyzedAssignment, AnalyzedBlock, AnalyzedFile, AnalyzedImport, AnalyzedLink,
-    AnalyzedStatement, AnalyzedTarget, AnalyzedTemplate, ShallowAnalyzedFile, Target, Template,
-    Variable, VariableScope,
+    AnalyzedStatement, AnalyzedTarget, AnalyzedTemplate, ArgInfo, ShallowAnalyzedFile, Target,
+    Template, Variable, VariableScope,
 };
 
+pub use cache::CacheNode;
+pub use dotgn::{evaluate_dot_gn, DotGnError};
+pub use links::{interpolate_constants, ResolvedTarget};
+pub use metrics::AnalysisStats;
 pub use toplevel::TopLevelStatementsExt;
+pub use types::{common_scope_members, infer_element_type, ValueType};
+pub use utils::resolve_path;
+
+use tower_lsp::lsp_types::Range;
 
 use crate::{
     analyzer::{
-        data::WorkspaceContext, dotgn::evaluate_dot_gn, full::FullAnalyzer,
-        shallow::ShallowAnalysisSnapshot,
+        cache::{CacheConfig, DEFAULT_VERIFY_INTERVAL},
+        conditions::collect_dead_branches,
+        data::WorkspaceContext,
+        full::FullAnalyzer,
+        metrics::AnalysisMetrics,
+        shallow::{ShallowAnalysisSnapshot, SharedShallowCache},
+        utils::detect_case_insensitive,
     },
     common::{
+        config::WorkspaceConfigurations,
         error::{Error, Result},
         storage::DocumentStorage,
+        utils::is_args_file,
         workspace::WorkspaceFinder,
     },
+    parser::Node,
 };
 
 mod cache;
+mod conditions;
 mod data;
 mod dotgn;
+mod dump;
 mod full;
 mod links;
+mod metrics;
 mod shallow;
 mod symbols;
 mod tests;
 mod toplevel;
+mod types;
 mod utils;
 
 pub struct Analyzer {
     storage: Arc<Mutex<DocumentStorage>>,
     workspaces: RwLock<BTreeMap<PathBuf, Arc<Mutex<WorkspaceAnalyzer>>>>,
+    verify_interval: RwLock<Duration>,
+    frozen: RwLock<bool>,
+    cache_capacity: RwLock<usize>,
+    manual_root: RwLock<Option<PathBuf>>,
+    args_file_patterns: RwLock<Vec<String>>,
+    label_search_roots: RwLock<Vec<String>>,
+    case_insensitive_paths: RwLock<Option<bool>>,
+    shallow_cache: SharedShallowCache,
+    metrics: Mutex<BTreeMap<PathBuf, Arc<AnalysisMetrics>>>,
 }
 
 impl Analyzer {
@@ -61,6 +90,72 @@ impl Analyzer {
         Self {
             storage: storage.clone(),
             workspaces: Default::default(),
+            verify_interval: RwLock::new(DEFAULT_VERIFY_INTERVAL),
+            frozen: Default::default(),
+            cache_capacity: Default::default(),
+            manual_root: Default::default(),
+            args_file_patterns: RwLock::new(WorkspaceConfigurations::default().args_file_patterns),
+            label_search_roots: Default::default(),
+            case_insensitive_paths: Default::default(),
+            shallow_cache: Default::default(),
+            metrics: Default::default(),
+        }
+    }
+
+    pub fn set_verify_interval(&self, verify_interval: Duration) {
+        *self.verify_interval.write().unwrap() = verify_interval;
+    }
+
+    /// Stops checking whether analyzed files changed on disk, so that
+    /// requests see a consistent snapshot until `thaw` is called. In-memory
+    /// documents are still updated.
+    pub fn freeze(&self) {
+        *self.frozen.write().unwrap() = true;
+    }
+
+    /// Resumes checking whether analyzed files changed on disk.
+    pub fn thaw(&self) {
+        *self.frozen.write().unwrap() = false;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        *self.frozen.read().unwrap()
+    }
+
+    /// Sets the maximum number of analyzed files cached for each workspace.
+    /// Zero means unlimited.
+    pub fn set_cache_capacity(&self, cache_capacity: usize) {
+        *self.cache_capacity.write().unwrap() = cache_capacity;
+    }
+
+    /// Sets the directory to treat as `//` for files not in any workspace.
+    pub fn set_manual_root(&self, manual_root: Option<PathBuf>) {
+        *self.manual_root.write().unwrap() = manual_root;
+    }
+
+    /// Sets the file name patterns of build argument files, which are
+    /// analyzed on their own when they are not in any workspace.
+    pub fn set_args_file_patterns(&self, args_file_patterns: Vec<String>) {
+        *self.args_file_patterns.write().unwrap() = args_file_patterns;
+    }
+
+    /// Sets additional directories to look up labels in, e.g. generated
+    /// output trees. Relative paths are resolved against workspace roots.
+    pub fn set_label_search_roots(&self, label_search_roots: Vec<String>) {
+        *self.label_search_roots.write().unwrap() = label_search_roots;
+    }
+
+    /// Sets whether paths differing only in casing refer to the same file.
+    /// `None` detects it from the filesystem of each workspace.
+    pub fn set_case_insensitive_paths(&self, case_insensitive_paths: Option<bool>) {
+        *self.case_insensitive_paths.write().unwrap() = case_insensitive_paths;
+    }
+
+    fn cache_config(&self, request_time: Instant) -> CacheConfig {
+        CacheConfig {
+            frozen: self.is_frozen(),
+            capacity: *self.cache_capacity.read().unwrap(),
+            ..CacheConfig::new(request_time, *self.verify_interval.read().unwrap())
         }
     }
 
@@ -71,13 +166,16 @@ impl Analyzer {
         request_time: Instant,
     ) -> Result<Pin<Arc<AnalyzedFile>>> {
         if !path.is_absolute() {
-            return Err(Error::General("Path must be absolute".to_string()));
+            return Err(Error::InvalidParams(format!(
+                "path must be absolute: {}",
+                path.display()
+            )));
         }
         Ok(self
             .workspace_for(path, finder)?
             .lock()
             .unwrap()
-            .analyze(path, request_time))
+            .analyze(path, self.cache_config(request_time)))
     }
 
     pub fn analyze_shallow(
@@ -87,13 +185,65 @@ impl Analyzer {
         request_time: Instant,
     ) -> Result<Pin<Arc<ShallowAnalyzedFile>>> {
         if !path.is_absolute() {
-            return Err(Error::General("Path must be absolute".to_string()));
+            return Err(Error::InvalidParams(format!(
+                "path must be absolute: {}",
+                path.display()
+            )));
         }
         Ok(self
             .workspace_for(path, finder)?
             .lock()
             .unwrap()
-            .analyze_shallow(path, request_time))
+            .analyze_shallow(path, self.cache_config(request_time)))
+    }
+
+    /// Returns the links in a file with their destinations resolved.
+    pub fn links(
+        &self,
+        path: &Path,
+        finder: &WorkspaceFinder,
+        request_time: Instant,
+    ) -> Result<Vec<(Range, ResolvedTarget)>> {
+        let file = self.analyze(path, finder, request_time)?;
+        let dead_branches = collect_dead_branches(&file.ast);
+        file.links
+            .iter()
+            .map(|link| {
+                let range = file.document.line_index.range(link.span());
+                let unreachable = dead_branches.iter().any(|branch| {
+                    branch.start() <= link.span().start() && link.span().end() <= branch.end()
+                });
+                let resolved = match link {
+                    AnalyzedLink::File { path, .. } => ResolvedTarget {
+                        path: path.clone(),
+                        name: None,
+                        exists: path.exists(),
+                        unreachable,
+                    },
+                    AnalyzedLink::Target { path, name, .. } => {
+                        // Target names are case-sensitive even on
+                        // case-insensitive file systems.
+                        let (target_name, _toolchain) = name.split_once('(').unwrap_or((name, ""));
+                        let exists = path.exists() && {
+                            let target_file = self.analyze_shallow(path, finder, request_time)?;
+                            target_file
+                                .environment
+                                .targets
+                                .locals()
+                                .values()
+                                .any(|target| target.name == target_name)
+                        };
+                        ResolvedTarget {
+                            path: path.clone(),
+                            name: Some(name.to_string()),
+                            exists,
+                            unreachable,
+                        }
+                    }
+                };
+                Ok((range, resolved))
+            })
+            .collect()
     }
 
     pub fn cached_files(&self, workspace_root: &Path) -> Vec<Pin<Arc<ShallowAnalyzedFile>>> {
@@ -109,52 +259,170 @@ impl Analyzer {
         cached_files
     }
 
+    /// Returns analysis metrics accumulated for each workspace root.
+    pub fn stats(&self) -> BTreeMap<PathBuf, AnalysisStats> {
+        self.metrics
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(root, metrics)| (root.clone(), metrics.stats()))
+            .collect()
+    }
+
+    /// Returns build arguments declared with `declare_args()` in the cached
+    /// files of a workspace, sorted by name and location.
+    pub fn build_args(&self, workspace_root: &Path) -> Vec<ArgInfo> {
+        let mut args = BTreeMap::new();
+        for file in self.cached_files(workspace_root) {
+            for (name, variable) in file.environment.variables.locals() {
+                if !variable.is_args {
+                    continue;
+                }
+                // Files importing an args file share its assignments, so
+                // deduplicate them by their locations.
+                for assignment in variable.assignments.values() {
+                    let path = &assignment.document.path;
+                    let offset = assignment.primary_variable.start();
+                    args.entry((name.to_string(), path.clone(), offset))
+                        .or_insert_with(|| ArgInfo {
+                            name: name.to_string(),
+                            default_value: assignment
+                                .assignment_or_call
+                                .left()
+                                .map(|assignment| assignment.rvalue.span().as_str().to_string()),
+                            path: path.clone(),
+                            range: assignment
+                                .document
+                                .line_index
+                                .range(assignment.primary_variable),
+                        });
+                }
+            }
+        }
+        args.into_values().collect()
+    }
+
     pub fn workspace_roots(&self) -> Vec<PathBuf> {
         self.workspaces.read().unwrap().keys().cloned().collect()
     }
 
+    /// Returns the root of the workspace containing a file, and whether the
+    /// root was chosen without a `.gn` file. Every lookup of a workspace root
+    /// goes through this so that manual roots are honored consistently.
+    pub fn resolve_workspace_root(
+        &self,
+        path: &Path,
+        finder: &WorkspaceFinder,
+    ) -> Result<(PathBuf, bool)> {
+        if let Some(workspace_root) = finder.find_for(path) {
+            return Ok((workspace_root.to_path_buf(), false));
+        }
+        let manual_root = self
+            .manual_root
+            .read()
+            .unwrap()
+            .clone()
+            .filter(|manual_root| path.starts_with(manual_root));
+        match manual_root {
+            Some(manual_root) => Ok((manual_root, true)),
+            // Build argument files usually live in output directories
+            // outside the source tree. Analyze them on their own.
+            None if is_args_file(&self.args_file_patterns.read().unwrap(), path) => {
+                let parent = path
+                    .parent()
+                    .ok_or_else(|| Error::WorkspaceNotFound(path.to_path_buf()))?;
+                Ok((parent.to_path_buf(), true))
+            }
+            None => Err(Error::WorkspaceNotFound(path.to_path_buf())),
+        }
+    }
+
+    /// Returns the root of the workspace containing a file.
+    pub fn workspace_root(&self, path: &Path, finder: &WorkspaceFinder) -> Result<PathBuf> {
+        Ok(self.resolve_workspace_root(path, finder)?.0)
+    }
+
     fn workspace_for(
         &self,
         path: &Path,
         finder: &WorkspaceFinder,
     ) -> Result<Arc<Mutex<WorkspaceAnalyzer>>> {
-        let workspace_root = finder
-            .find_for(path)
-            .ok_or(Error::General("Workspace not found".to_string()))?;
+        let (workspace_root, is_manual) = self.resolve_workspace_root(path, finder)?;
         let dot_gn_path = workspace_root.join(".gn");
         let dot_gn_version = {
             let storage = self.storage.lock().unwrap();
             storage.read_version(&dot_gn_path)
         };
 
+        let label_search_roots: Vec<PathBuf> = self
+            .label_search_roots
+            .read()
+            .unwrap()
+            .iter()
+            .map(|root| resolve_path(root, &workspace_root, &workspace_root))
+            .collect();
+
+        let case_insensitive = self
+            .case_insensitive_paths
+            .read()
+            .unwrap()
+            .unwrap_or_else(|| detect_case_insensitive(&workspace_root));
+
         {
             let read_lock = self.workspaces.read().unwrap();
-            if let Some(workspace) = read_lock.get(workspace_root) {
-                if workspace.lock().unwrap().context.dot_gn_version == dot_gn_version {
+            if let Some(workspace) = read_lock.get(&workspace_root) {
+                let workspace_analyzer = workspace.lock().unwrap();
+                if workspace_analyzer.context.dot_gn_version == dot_gn_version
+                    && workspace_analyzer.context.label_search_roots == label_search_roots
+                    && workspace_analyzer.context.case_insensitive == case_insensitive
+                {
                     return Ok(workspace.clone());
                 }
             }
         }
 
-        let build_config = {
+        // Workspaces without .gn have no build config.
+        let build_config = if is_manual {
+            None
+        } else {
             let storage = self.storage.lock().unwrap();
             let document = storage.read(&dot_gn_path);
-            evaluate_dot_gn(workspace_root, &document.data)?
+            Some(evaluate_dot_gn(&workspace_root, &document.data)?)
         };
 
         let context = WorkspaceContext {
-            root: workspace_root.to_path_buf(),
+            root: workspace_root.clone(),
             dot_gn_version,
             build_config,
+            label_search_roots,
+            case_insensitive,
         };
 
-        let workspace = Arc::new(Mutex::new(WorkspaceAnalyzer::new(&context, &self.storage)));
+        // Metrics outlive workspaces replaced for outdated contexts.
+        let metrics = self
+            .metrics
+            .lock()
+            .unwrap()
+            .entry(workspace_root.clone())
+            .or_default()
+            .clone();
+        let workspace = Arc::new(Mutex::new(WorkspaceAnalyzer::new(
+            &context,
+            &self.storage,
+            &self.shallow_cache,
+            &metrics,
+        )));
 
+        // Keep a workspace created concurrently for the same context, but
+        // replace one with an outdated context.
         let mut write_lock = self.workspaces.write().unwrap();
-        Ok(write_lock
-            .entry(workspace_root.to_path_buf())
-            .or_insert(workspace)
-            .clone())
+        if let Some(existing) = write_lock.get(&workspace_root) {
+            if existing.lock().unwrap().context == context {
+                return Ok(existing.clone());
+            }
+        }
+        write_lock.insert(workspace_root, workspace.clone());
+        Ok(workspace)
     }
 }
 
@@ -164,24 +432,29 @@ struct WorkspaceAnalyzer {
 }
 
 impl WorkspaceAnalyzer {
-    pub fn new(context: &WorkspaceContext, storage: &Arc<Mutex<DocumentStorage>>) -> Self {
+    pub fn new(
+        context: &WorkspaceContext,
+        storage: &Arc<Mutex<DocumentStorage>>,
+        shared_cache: &SharedShallowCache,
+        metrics: &Arc<AnalysisMetrics>,
+    ) -> Self {
         Self {
             context: context.clone(),
-            analyzer: FullAnalyzer::new(context, storage),
+            analyzer: FullAnalyzer::new(context, storage, shared_cache, metrics),
         }
     }
 
-    pub fn analyze(&mut self, path: &Path, request_time: Instant) -> Pin<Arc<AnalyzedFile>> {
-        self.analyzer.analyze(path, request_time)
+    pub fn analyze(&mut self, path: &Path, cache_config: CacheConfig) -> Pin<Arc<AnalyzedFile>> {
+        self.analyzer.analyze(path, cache_config)
     }
 
     pub fn analyze_shallow(
         &mut self,
         path: &Path,
-        request_time: Instant,
+        cache_config: CacheConfig,
     ) -> Pin<Arc<ShallowAnalyzedFile>> {
         self.analyzer
             .get_shallow()
-            .analyze(path, request_time, &mut ShallowAnalysisSnapshot::new())
+            .analyze(path, cache_config, &mut ShallowAnalysisSnapshot::new())
     }
 }
@@ -16,20 +16,20 @@ use std::{
     collections::{BTreeMap, HashMap},
     path::{Path, PathBuf},
     pin::Pin,
-    sync::{Arc, Mutex},
-    time::Instant,
+    sync::{Arc, Mutex, Weak},
 };
 
 use either::Either;
 
 use crate::{
     analyzer::{
-        cache::CacheNode,
+        cache::{CacheConfig, CacheNode, LruCache},
         data::{
             FileEnvironment, MutableFileEnvironment, PathSpan, ShallowAnalyzedFile, Target,
             Template, Variable, VariableAssignment, WorkspaceContext,
         },
         links::collect_links,
+        metrics::AnalysisMetrics,
         toplevel::TopLevelStatementsExt,
         AnalyzedLink,
     },
@@ -41,35 +41,109 @@ use crate::{
     parser::{parse, Block, Call, Comments, LValue, Node, Statement},
 };
 
+/// Returns whether a symbol is visible to importers. Private symbols prefixed
+/// with `_` are still available in their own file through the full analysis.
 fn is_exported(name: &str) -> bool {
     !name.starts_with("_")
 }
 
 pub type ShallowAnalysisSnapshot = HashMap<PathBuf, Pin<Arc<ShallowAnalyzedFile>>>;
 
+/// Shallow analysis results shared among workspace analyzers, e.g. the ones
+/// created again after `.gn` changes.
+///
+/// A file analyzed under two workspace contexts yields the same result as long
+/// as the contexts resolve paths and labels identically. Entries are keyed on
+/// every context input but the `.gn` version, and verified with their cache
+/// nodes, which cover the file version and its imports.
+///
+/// Entries are weak so that the per-workspace caches alone decide when files
+/// are evicted.
+#[derive(Clone, Default)]
+pub struct SharedShallowCache {
+    files: Arc<Mutex<BTreeMap<SharedCacheKey, Weak<ShallowAnalyzedFile>>>>,
+}
+
+#[derive(Clone, Eq, Ord, PartialEq, PartialOrd)]
+struct SharedCacheKey {
+    root: PathBuf,
+    build_config: Option<PathBuf>,
+    label_search_roots: Vec<PathBuf>,
+    case_insensitive: bool,
+    path: PathBuf,
+}
+
+fn shared_cache_key(context: &WorkspaceContext, path: &Path) -> SharedCacheKey {
+    SharedCacheKey {
+        root: context.root.clone(),
+        build_config: context.build_config.clone(),
+        label_search_roots: context.label_search_roots.clone(),
+        case_insensitive: context.case_insensitive,
+        path: path.to_path_buf(),
+    }
+}
+
+impl SharedShallowCache {
+    fn get(
+        &self,
+        context: &WorkspaceContext,
+        path: &Path,
+    ) -> Option<Pin<Arc<ShallowAnalyzedFile>>> {
+        let files = self.files.lock().unwrap();
+        let file = files.get(&shared_cache_key(context, path))?.upgrade()?;
+        Some(Pin::new(file))
+    }
+
+    fn insert(
+        &self,
+        context: &WorkspaceContext,
+        path: &Path,
+        file: &Pin<Arc<ShallowAnalyzedFile>>,
+    ) {
+        let mut files = self.files.lock().unwrap();
+        // Drop entries of released files whenever the map doubles in size.
+        if files.len().is_power_of_two() {
+            files.retain(|_, file| file.strong_count() > 0);
+        }
+        files.insert(
+            shared_cache_key(context, path),
+            Arc::downgrade(&Pin::into_inner(file.clone())),
+        );
+    }
+}
+
 pub struct ShallowAnalyzer {
     context: WorkspaceContext,
     storage: Arc<Mutex<DocumentStorage>>,
-    cache: BTreeMap<PathBuf, Pin<Arc<ShallowAnalyzedFile>>>,
+    cache: LruCache<PathBuf, ShallowAnalyzedFile>,
+    shared_cache: SharedShallowCache,
+    metrics: Arc<AnalysisMetrics>,
 }
 
 impl ShallowAnalyzer {
-    pub fn new(context: &WorkspaceContext, storage: &Arc<Mutex<DocumentStorage>>) -> Self {
+    pub fn new(
+        context: &WorkspaceContext,
+        storage: &Arc<Mutex<DocumentStorage>>,
+        shared_cache: &SharedShallowCache,
+        metrics: &Arc<AnalysisMetrics>,
+    ) -> Self {
         Self {
             context: context.clone(),
             storage: storage.clone(),
             cache: Default::default(),
+            shared_cache: shared_cache.clone(),
+            metrics: metrics.clone(),
         }
     }
 
     pub fn cached_files(&self) -> Vec<Pin<Arc<ShallowAnalyzedFile>>> {
-        self.cache.values().cloned().collect()
+        self.cache.files().cloned().collect()
     }
 
     pub fn analyze(
         &mut self,
         path: &Path,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
     ) -> Pin<Arc<ShallowAnalyzedFile>> {
         if snapshot.contains_key(path) {
@@ -79,30 +153,31 @@ impl ShallowAnalyzer {
         if let Some(cached_file) = self.cache.get(path) {
             if cached_file
                 .node
-                .verify(request_time, &self.storage.lock().unwrap())
+                .verify(cache_config, &self.storage.lock().unwrap())
             {
-                return cached_file.clone();
+                self.metrics.record_cache(true);
+                return cached_file;
             }
         }
 
-        self.analyze_cached(path, request_time, snapshot, &mut Vec::new())
+        self.analyze_cached(path, cache_config, snapshot, &mut Vec::new())
     }
 
     fn analyze_cached(
         &mut self,
         path: &Path,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         visiting: &mut Vec<PathBuf>,
     ) -> Pin<Arc<ShallowAnalyzedFile>> {
         if visiting.iter().any(|p| p == path) {
-            return ShallowAnalyzedFile::error(path, request_time);
+            return ShallowAnalyzedFile::error(path, cache_config);
         }
 
         if snapshot.contains_key(path) {
             return snapshot.get(path).unwrap().clone();
         }
-        let file = self.analyze_cached_inner(path, request_time, snapshot, visiting);
+        let file = self.analyze_cached_inner(path, cache_config, snapshot, visiting);
         assert!(!snapshot.contains_key(path));
         snapshot.insert(path.to_path_buf(), file.clone());
         file
@@ -111,33 +186,61 @@ impl ShallowAnalyzer {
     fn analyze_cached_inner(
         &mut self,
         path: &Path,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         visiting: &mut Vec<PathBuf>,
     ) -> Pin<Arc<ShallowAnalyzedFile>> {
         if let Some(cached_file) = self.cache.get(path) {
             if cached_file
                 .node
-                .verify(request_time, &self.storage.lock().unwrap())
+                .verify(cache_config, &self.storage.lock().unwrap())
+            {
+                self.metrics.record_cache(true);
+                return cached_file;
+            }
+        }
+
+        if let Some(shared_file) = self.shared_cache.get(&self.context, path) {
+            if shared_file
+                .node
+                .verify(cache_config, &self.storage.lock().unwrap())
             {
-                return cached_file.clone();
+                self.cache.insert(
+                    path.to_path_buf(),
+                    shared_file.clone(),
+                    cache_config.capacity,
+                );
+                self.metrics.record_cache(true);
+                return shared_file;
             }
         }
 
-        let new_file = self.analyze_uncached(path, request_time, snapshot, visiting);
-        self.cache.insert(path.to_path_buf(), new_file.clone());
+        self.metrics.record_cache(false);
+        let new_file = self.analyze_uncached(path, cache_config, snapshot, visiting);
+        self.cache
+            .insert(path.to_path_buf(), new_file.clone(), cache_config.capacity);
+        self.shared_cache.insert(&self.context, path, &new_file);
         new_file
     }
 
     fn analyze_uncached(
         &mut self,
         path: &Path,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         visiting: &mut Vec<PathBuf>,
     ) -> Pin<Arc<ShallowAnalyzedFile>> {
+        // Time only the outermost file, which includes its imports.
+        let outermost = visiting.is_empty();
         visiting.push(path.to_path_buf());
-        let new_file = self.analyze_uncached_inner(path, request_time, snapshot, visiting);
+        let new_file = if outermost {
+            let metrics = self.metrics.clone();
+            metrics.time_shallow(|| {
+                self.analyze_uncached_inner(path, cache_config, snapshot, visiting)
+            })
+        } else {
+            self.analyze_uncached_inner(path, cache_config, snapshot, visiting)
+        };
         visiting.pop();
         new_file
     }
@@ -145,17 +248,21 @@ impl ShallowAnalyzer {
     fn analyze_uncached_inner(
         &mut self,
         path: &Path,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         visiting: &mut Vec<PathBuf>,
     ) -> Pin<Arc<ShallowAnalyzedFile>> {
         let document = self.storage.lock().unwrap().read(path);
-        let ast = Box::pin(parse(&document.data));
+        let ast = Box::pin(self.metrics.time_parse(|| parse(&document.data)));
         let mut deps = Vec::new();
         let environment =
-            self.analyze_block(&ast, &document, request_time, snapshot, &mut deps, visiting);
+            self.analyze_block(&ast, &document, cache_config, snapshot, &mut deps, visiting);
 
-        let links = collect_links(&ast, path, &self.context);
+        // Without positions, only file-level constants are known.
+        let links = collect_links(&ast, path, &self.context, |name, _| {
+            let variable = environment.variables.get(name)?;
+            variable.constant_string().map(str::to_string)
+        });
 
         // SAFETY: links' contents are backed by pinned document.
         let links = unsafe { std::mem::transmute::<Vec<AnalyzedLink>, Vec<AnalyzedLink>>(links) };
@@ -165,7 +272,15 @@ impl ShallowAnalyzer {
         // SAFETY: ast's contents are backed by pinned document.
         let ast = unsafe { std::mem::transmute::<Pin<Box<Block>>, Pin<Box<Block>>>(ast) };
 
-        ShallowAnalyzedFile::new(document, ast, environment, links, deps, request_time)
+        ShallowAnalyzedFile::new(
+            document,
+            ast,
+            environment,
+            links,
+            self.context.case_insensitive,
+            deps,
+            cache_config,
+        )
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -173,7 +288,7 @@ impl ShallowAnalyzer {
         &mut self,
         block: &'p Block<'i>,
         document: &'i Document,
-        request_time: Instant,
+        cache_config: CacheConfig,
         snapshot: &mut ShallowAnalysisSnapshot,
         deps: &mut Vec<Arc<CacheNode>>,
         visiting: &mut Vec<PathBuf>,
@@ -222,9 +337,13 @@ impl ShallowAnalyzer {
                         {
                             let path = self
                                 .context
-                                .resolve_path(name, document.path.parent().unwrap());
-                            let file = self.analyze_cached(&path, request_time, snapshot, visiting);
-                            environment.import(&file.environment);
+                                .resolve_path(name, self.context.current_dir(&document.path));
+                            let file = self.analyze_cached(&path, cache_config, snapshot, visiting);
+                            if declare_args_stack.is_empty() {
+                                environment.import(&file.environment);
+                            } else {
+                                environment.import_args(&file.environment);
+                            }
                             deps.push(file.node.clone());
                         }
                     }
@@ -295,6 +414,7 @@ impl ShallowAnalyzer {
                                     document,
                                     call,
                                     name,
+                                    implicit_name: false,
                                 },
                             );
                         }
@@ -13,10 +13,13 @@
 // limitations under the License.
 
 use either::Either;
-use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind, SymbolTag};
 
 use crate::{
-    common::utils::LineIndex,
+    common::{
+        builtins::{TARGET_NAME, TEMPLATE},
+        utils::LineIndex,
+    },
     parser::{Node, Statement},
 };
 
@@ -49,14 +52,22 @@ pub fn collect_symbols(node: &dyn Node, line_index: &LineIndex) -> Vec<DocumentS
                         call.only_arg().and_then(|arg| arg.as_primary_string())
                     {
                         format!("{}(\"{}\")", call.function.name, string.raw_value)
+                    } else if call
+                        .only_arg()
+                        .and_then(|arg| arg.as_primary_identifier())
+                        .is_some_and(|identifier| identifier.name == TARGET_NAME)
+                    {
+                        format!("{}({})", call.function.name, TARGET_NAME)
                     } else {
                         format!("{}(...)", call.function.name)
                     };
+                    let is_deprecated =
+                        call.function.name == TEMPLATE && call.comments.is_deprecated();
                     symbols.push(DocumentSymbol {
                         name,
                         detail: None,
                         kind: SymbolKind::FUNCTION,
-                        tags: None,
+                        tags: is_deprecated.then(|| vec![SymbolTag::DEPRECATED]),
                         deprecated: None,
                         range: line_index.range(call.span()),
                         selection_range: line_index.range(call.function.span()),
@@ -108,14 +119,17 @@ pub fn collect_symbols(node: &dyn Node, line_index: &LineIndex) -> Vec<DocumentS
                             current_condition = next_condition;
                         }
                         Some(Either::Right(else_block)) => {
+                            let else_keyword = current_condition.else_keyword.unwrap();
                             current_children.push(DocumentSymbol {
                                 name: "else".to_string(),
                                 detail: None,
                                 kind: SymbolKind::NAMESPACE,
                                 tags: None,
                                 deprecated: None,
-                                range: line_index.range(else_block.span()),
-                                selection_range: line_index.range(else_block.span()),
+                                range: line_index.range(
+                                    else_keyword.start_pos().span(&else_block.span().end_pos()),
+                                ),
+                                selection_range: line_index.range(else_keyword),
                                 children: Some(collect_symbols(else_block.as_node(), line_index)),
                             });
                             break;
@@ -134,3 +148,82 @@ pub fn collect_symbols(node: &dyn Node, line_index: &LineIndex) -> Vec<DocumentS
     }
     symbols
 }
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::parser::parse;
+
+    use super::*;
+
+    fn selection_ranges(symbols: &[DocumentSymbol]) -> Vec<(String, Range)> {
+        let mut ranges = Vec::new();
+        for symbol in symbols {
+            ranges.push((symbol.name.clone(), symbol.selection_range));
+            ranges.extend(selection_ranges(
+                symbol.children.as_deref().unwrap_or_default(),
+            ));
+        }
+        ranges
+    }
+
+    #[test]
+    fn test_selection_ranges() {
+        let input = r#"a = 1
+foo("bar") {
+  b = [ 1 ]
+}
+if (x) {
+} else if (y) {
+} # else
+else {
+}
+"#;
+        let line_index = LineIndex::new(input);
+        let ast = parse(input);
+        let symbols = collect_symbols(ast.as_node(), &line_index);
+
+        let range = |start: (u32, u32), end: (u32, u32)| {
+            Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+        };
+        assert_eq!(
+            selection_ranges(&symbols),
+            [
+                ("a = ...".to_string(), range((0, 0), (0, 1))),
+                ("foo(\"bar\")".to_string(), range((1, 0), (1, 3))),
+                ("b = ...".to_string(), range((2, 2), (2, 3))),
+                ("if (x)".to_string(), range((4, 4), (4, 5))),
+                ("else if (y)".to_string(), range((5, 11), (5, 12))),
+                ("else".to_string(), range((7, 0), (7, 4))),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deprecated_template() {
+        let input = r#"# DEPRECATED: Use new_foo instead.
+template("foo") {
+}
+
+# Not deprecated.
+template("new_foo") {
+}
+"#;
+        let line_index = LineIndex::new(input);
+        let ast = parse(input);
+        let symbols = collect_symbols(ast.as_node(), &line_index);
+
+        let tags: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.tags.clone()))
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                ("template(\"foo\")", Some(vec![SymbolTag::DEPRECATED])),
+                ("template(\"new_foo\")", None),
+            ]
+        );
+    }
+}
@@ -15,13 +15,26 @@
 #![cfg(test)]
 
 use std::{
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Duration, Instant, SystemTime},
 };
 
+use tower_lsp::lsp_types::{Position, Range};
+
 use crate::{
-    analyzer::Analyzer,
-    common::{storage::DocumentStorage, testutils::testdata, workspace::WorkspaceFinder},
+    analyzer::{
+        cache::{CacheConfig, DEFAULT_VERIFY_INTERVAL},
+        data::WorkspaceContext,
+        full::FullAnalyzer,
+        shallow::{ShallowAnalysisSnapshot, ShallowAnalyzer, SharedShallowCache},
+        AnalyzedLink, Analyzer,
+    },
+    common::{
+        storage::{DocumentStorage, DocumentVersion},
+        testutils::{testdata, TestWorkspace},
+        workspace::WorkspaceFinder,
+    },
     parser::Statement,
 };
 
@@ -75,3 +88,671 @@ fn test_analyze_cycles() {
         )
         .is_ok());
 }
+
+#[test]
+fn test_analyze_without_parent() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let context = WorkspaceContext {
+        root: testdata("workspaces/smoke"),
+        dot_gn_version: DocumentVersion::IoError,
+        build_config: Some(testdata("workspaces/smoke/BUILDCONFIG.gn")),
+        label_search_roots: Vec::new(),
+        case_insensitive: false,
+    };
+
+    // The filesystem root has no parent directory.
+    let path = Path::new("/");
+    storage.lock().unwrap().load_to_memory(
+        path,
+        "import(\"foo.gni\")\nsources = [ \"BUILD.gn\", \"bar:baz\" ]\n",
+        1,
+    );
+
+    let mut analyzer = FullAnalyzer::new(
+        &context,
+        &storage,
+        &SharedShallowCache::default(),
+        &Default::default(),
+    );
+    let file = analyzer.analyze(
+        path,
+        CacheConfig::new(Instant::now(), DEFAULT_VERIFY_INTERVAL),
+    );
+
+    // Relative paths are resolved against the workspace root. The missing
+    // import is linked as well.
+    let links: Vec<_> = file
+        .links
+        .iter()
+        .map(|link| match link {
+            AnalyzedLink::File { path, span, exists } => (span.as_str(), path.clone(), *exists),
+            AnalyzedLink::Target { .. } => panic!("unexpected target link"),
+        })
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            ("\"foo.gni\"", testdata("workspaces/smoke/foo.gni"), false),
+            ("\"BUILD.gn\"", testdata("workspaces/smoke/BUILD.gn"), true),
+        ]
+    );
+}
+
+#[test]
+fn test_verify_interval_zero() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    let path = root.join("BUILD.gn");
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+
+    let write_file = |data: &str, modified: SystemTime| {
+        std::fs::write(&path, data).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    };
+    let base_time = SystemTime::now();
+    let request_time = Instant::now();
+
+    // With the default interval, recent verification results are reused.
+    write_file("a = 1\n", base_time);
+    let file = analyzer.analyze(&path, &finder, request_time).unwrap();
+    assert!(file.variables_at(0).get("a").is_some());
+    write_file("b = 1\n", base_time + Duration::from_secs(1));
+    let file = analyzer
+        .analyze(&path, &finder, request_time + Duration::from_millis(1))
+        .unwrap();
+    assert!(file.variables_at(0).get("a").is_some());
+
+    // With zero interval, files are verified on every request.
+    analyzer.set_verify_interval(Duration::ZERO);
+    write_file("c = 1\n", base_time + Duration::from_secs(2));
+    let file = analyzer
+        .analyze(&path, &finder, request_time + Duration::from_millis(2))
+        .unwrap();
+    assert!(file.variables_at(0).get("c").is_some());
+    write_file("d = 1\n", base_time + Duration::from_secs(3));
+    let file = analyzer
+        .analyze(&path, &finder, request_time + Duration::from_millis(3))
+        .unwrap();
+    assert!(file.variables_at(0).get("d").is_some());
+}
+
+#[test]
+fn test_freeze() {
+    let workspace = TestWorkspace::new([("BUILD.gn", "")]);
+    let path = workspace.path("BUILD.gn");
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    analyzer.set_verify_interval(Duration::ZERO);
+
+    let write_file = |data: &str, modified: SystemTime| {
+        std::fs::write(&path, data).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    };
+    let base_time = SystemTime::now();
+    let request_time = Instant::now();
+
+    write_file("a = 1\n", base_time);
+    let file = analyzer.analyze(&path, &finder, request_time).unwrap();
+    assert!(file.variables_at(0).get("a").is_some());
+
+    // Frozen analysis ignores changes on disk, even with zero interval.
+    analyzer.freeze();
+    write_file("b = 1\n", base_time + Duration::from_secs(1));
+    let file = analyzer
+        .analyze(&path, &finder, request_time + Duration::from_secs(1))
+        .unwrap();
+    assert!(file.variables_at(0).get("a").is_some());
+
+    // Thawing picks up the changes.
+    analyzer.thaw();
+    let file = analyzer
+        .analyze(&path, &finder, request_time + Duration::from_secs(2))
+        .unwrap();
+    assert!(file.variables_at(0).get("b").is_some());
+}
+
+#[test]
+fn test_analyze_import_in_declare_args() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let path = testdata("workspaces/declare_args/BUILD.gn");
+
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+    let variables = file.variables_at(file.document.data.len());
+    assert!(variables.get("enable_foo").unwrap().is_args);
+    assert!(!variables.get("foo_flags").unwrap().is_args);
+
+    let file = analyzer
+        .analyze_shallow(&path, &finder, Instant::now())
+        .unwrap();
+    assert!(
+        file.environment
+            .variables
+            .get("enable_foo")
+            .unwrap()
+            .is_args
+    );
+}
+
+#[test]
+fn test_links() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let path = testdata("workspaces/links/BUILD.gn");
+
+    let links = analyzer.links(&path, &finder, Instant::now()).unwrap();
+    let links: Vec<_> = links
+        .into_iter()
+        .map(|(range, target)| (range.start.line, target.name, target.exists))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (19, Some("valid".to_string()), true),
+            (20, Some("broken".to_string()), false),
+            (21, Some("valid_extra".to_string()), false),
+            (22, Some("valid(//build:gcc)".to_string()), true),
+        ]
+    );
+}
+
+#[test]
+fn test_shared_shallow_cache() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let shared_cache = SharedShallowCache::default();
+    let context = WorkspaceContext {
+        root: testdata("workspaces/smoke"),
+        dot_gn_version: DocumentVersion::IoError,
+        build_config: Some(testdata("workspaces/smoke/BUILDCONFIG.gn")),
+        label_search_roots: Vec::new(),
+        case_insensitive: false,
+    };
+    // A workspace created again after `.gn` changes.
+    let new_context = WorkspaceContext {
+        dot_gn_version: DocumentVersion::OnDisk {
+            modified: std::time::SystemTime::UNIX_EPOCH,
+        },
+        ..context.clone()
+    };
+    let other_root_context = WorkspaceContext {
+        root: testdata("workspaces"),
+        ..context.clone()
+    };
+    let other_build_config_context = WorkspaceContext {
+        build_config: Some(testdata("workspaces/smoke/other/BUILDCONFIG.gn")),
+        ..context.clone()
+    };
+    let path = testdata("workspaces/smoke/BUILD.gn");
+    let cache_config = CacheConfig::new(Instant::now(), DEFAULT_VERIFY_INTERVAL);
+
+    let analyze = |context: &WorkspaceContext| {
+        ShallowAnalyzer::new(context, &storage, &shared_cache, &Default::default()).analyze(
+            &path,
+            cache_config,
+            &mut ShallowAnalysisSnapshot::new(),
+        )
+    };
+    let file = analyze(&context);
+
+    // Workspaces with matching contexts share the analysis.
+    assert!(std::ptr::eq(&*file, &*analyze(&context)));
+    assert!(std::ptr::eq(&*file, &*analyze(&new_context)));
+    // A different root resolves paths differently.
+    assert!(!std::ptr::eq(&*file, &*analyze(&other_root_context)));
+    // Nor is the analysis shared across build configs.
+    assert!(!std::ptr::eq(
+        &*file,
+        &*analyze(&other_build_config_context)
+    ));
+}
+
+#[test]
+fn test_shallow_cache_eviction() {
+    let workspace = TestWorkspace::new([
+        ("x.gni", "import(\"//y.gni\")\nx = 1\n"),
+        ("y.gni", "import(\"//w.gni\")\ny = 1\n"),
+        ("w.gni", "w = 1\n"),
+        ("z.gni", "z = 1\n"),
+    ]);
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let context = WorkspaceContext {
+        root: workspace.root().to_path_buf(),
+        dot_gn_version: DocumentVersion::IoError,
+        build_config: Some(workspace.path("BUILDCONFIG.gn")),
+        label_search_roots: Vec::new(),
+        case_insensitive: false,
+    };
+    let cache_config = CacheConfig {
+        capacity: 1,
+        ..CacheConfig::new(Instant::now(), DEFAULT_VERIFY_INTERVAL)
+    };
+    let mut analyzer = ShallowAnalyzer::new(
+        &context,
+        &storage,
+        &SharedShallowCache::default(),
+        &Default::default(),
+    );
+    let cached_paths = |analyzer: &ShallowAnalyzer| -> Vec<_> {
+        analyzer
+            .cached_files()
+            .iter()
+            .map(|file| file.document.path.clone())
+            .collect()
+    };
+
+    // y.gni and w.gni are kept beyond the capacity while x.gni depends on
+    // them.
+    let x = analyzer.analyze(
+        &workspace.path("x.gni"),
+        cache_config,
+        &mut ShallowAnalysisSnapshot::new(),
+    );
+    assert_eq!(
+        cached_paths(&analyzer),
+        vec![
+            workspace.path("w.gni"),
+            workspace.path("x.gni"),
+            workspace.path("y.gni")
+        ]
+    );
+
+    // Once x.gni is evicted, files it depended on are re-checked and evicted
+    // in the same run.
+    drop(x);
+    analyzer.analyze(
+        &workspace.path("z.gni"),
+        cache_config,
+        &mut ShallowAnalysisSnapshot::new(),
+    );
+    assert_eq!(cached_paths(&analyzer), vec![workspace.path("z.gni")]);
+}
+
+#[test]
+fn test_links_interpolated() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let path = testdata("workspaces/links/interpolated.gn");
+    storage.lock().unwrap().load_to_memory(
+        &path,
+        r#"outputs = [
+  "$root_out_dir/foo",
+  "//sub/$target_name.txt",
+  "//sub/${target_name}_${current_cpu}",
+  "//missing/$target_name",
+  "//sub/${target_name}",
+  "sub/$target_name",
+]
+"#,
+        1,
+    );
+
+    // Only trailing interpolations after an existing directory are linked.
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+    let links: Vec<_> = file
+        .links
+        .iter()
+        .map(|link| match link {
+            AnalyzedLink::File { path, span, .. } => (span.as_str(), path.clone()),
+            AnalyzedLink::Target { .. } => panic!("unexpected target link"),
+        })
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            ("\"//sub/${target_name}\"", testdata("workspaces/links/sub")),
+            ("\"sub/$target_name\"", testdata("workspaces/links/sub")),
+        ]
+    );
+}
+
+/// Writes a workspace whose `BUILD.gn` lists 200 existing sources and a
+/// missing one, and returns the path to `BUILD.gn`.
+fn write_many_sources(root: &Path) -> PathBuf {
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    std::fs::create_dir(root.join("src")).unwrap();
+    let mut data = String::from("source_set(\"many\") {\n  sources = [\n");
+    for i in 0..200 {
+        std::fs::write(root.join(format!("src/file{i}.cc")), "").unwrap();
+        data.push_str(&format!("    \"src/file{i}.cc\",\n"));
+    }
+    data.push_str("    \"src/missing.cc\",\n  ]\n}\n");
+    let path = root.join("BUILD.gn");
+    std::fs::write(&path, data).unwrap();
+    path
+}
+
+#[test]
+fn test_links_many_sources() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = write_many_sources(temp_dir.path());
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+
+    // Existing files are linked in source order; missing ones are not.
+    let spans: Vec<_> = file.links.iter().map(|link| link.span().as_str()).collect();
+    let expected: Vec<_> = (0..200).map(|i| format!("\"src/file{i}.cc\"")).collect();
+    assert_eq!(spans, expected);
+}
+
+/// Run with `cargo test -- --ignored --nocapture` to see timings.
+#[test]
+#[ignore]
+fn bench_links_many_sources() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = write_many_sources(temp_dir.path());
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+
+    let start_time = Instant::now();
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+    eprintln!(
+        "Collected {} links in {:?}",
+        file.links.len(),
+        start_time.elapsed()
+    );
+}
+
+#[test]
+fn test_manual_root() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    std::fs::create_dir(root.join("foo")).unwrap();
+    std::fs::write(root.join("foo/BUILD.gn"), "group(\"foo\") {}\n").unwrap();
+    let path = root.join("fragment.gn");
+    std::fs::write(&path, "deps = [ \"//foo\" ]\n").unwrap();
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+
+    // Without a manual root, files outside workspaces can not be analyzed.
+    assert!(analyzer.analyze(&path, &finder, Instant::now()).is_err());
+
+    analyzer.set_manual_root(Some(root.to_path_buf()));
+    assert_eq!(analyzer.workspace_root(&path, &finder).unwrap(), root);
+    // Manual roots are reported as such so that they are not indexed.
+    assert_eq!(
+        analyzer.resolve_workspace_root(&path, &finder).unwrap(),
+        (root.to_path_buf(), true)
+    );
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+    // Without .gn, no build config is imported.
+    assert_eq!(file.build_config(), None);
+    assert_eq!(file.links.len(), 1);
+    let AnalyzedLink::Target { path, name, .. } = &file.links[0] else {
+        panic!("unexpected file link");
+    };
+    assert_eq!(path, &root.join("foo/BUILD.gn"));
+    assert_eq!(*name, "foo");
+}
+
+#[test]
+fn test_missing_import_links() {
+    let workspace = TestWorkspace::new([
+        ("present.gni", ""),
+        (
+            "BUILD.gn",
+            "import(\"//present.gni\")\nimport(\"//missing.gni\")\nsources = [ \"missing.cc\" ]\n",
+        ),
+    ]);
+    let root = workspace.root();
+    let path = workspace.path("BUILD.gn");
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+
+    // Missing imports are linked and reported as broken, but other missing
+    // files are not linked.
+    let links: Vec<_> = analyzer
+        .links(&path, &finder, Instant::now())
+        .unwrap()
+        .into_iter()
+        .map(|(_, target)| (target.path, target.exists))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            (root.join("present.gni"), true),
+            (root.join("missing.gni"), false),
+        ]
+    );
+}
+
+#[test]
+fn test_label_search_roots() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    std::fs::create_dir_all(root.join("out/gen/generated")).unwrap();
+    std::fs::write(
+        root.join("out/gen/generated/BUILD.gn"),
+        "group(\"generated\") {}\n",
+    )
+    .unwrap();
+    let path = root.join("BUILD.gn");
+    std::fs::write(&path, "deps = [ \"//generated\" ]\n").unwrap();
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+
+    let resolve = || {
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        let [AnalyzedLink::Target { path, .. }] = file.links.as_slice() else {
+            panic!("unexpected links");
+        };
+        path.clone()
+    };
+
+    // Labels missing in the source tree resolve under the source root.
+    assert_eq!(resolve(), root.join("generated/BUILD.gn"));
+
+    analyzer.set_label_search_roots(vec!["//out/gen".to_string()]);
+    assert_eq!(resolve(), root.join("out/gen/generated/BUILD.gn"));
+}
+
+#[test]
+fn test_constant_label_interpolation() {
+    let workspace = TestWorkspace::new([
+        ("foo/BUILD.gn", "group(\"bar\") {}\n"),
+        (
+            "BUILD.gn",
+            r#"suffix = "bar"
+deps = [
+  "//foo:$suffix",
+  "//foo:${suffix}",
+  "//foo:$unknown",
+]
+"#,
+        ),
+    ]);
+    let root = workspace.root();
+    let path = workspace.path("BUILD.gn");
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+
+    // Labels interpolating constants resolve, while unknown values get no
+    // link.
+    let links: Vec<_> = file
+        .links
+        .iter()
+        .map(|link| {
+            let AnalyzedLink::Target { path, name, span } = link else {
+                panic!("unexpected file link");
+            };
+            (path.clone(), name.to_string(), span.as_str())
+        })
+        .collect();
+    assert_eq!(
+        links,
+        [
+            (
+                root.join("foo/BUILD.gn"),
+                "bar".to_string(),
+                "\"//foo:$suffix\""
+            ),
+            (
+                root.join("foo/BUILD.gn"),
+                "bar".to_string(),
+                "\"//foo:${suffix}\""
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_bom() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let root = temp_dir.path();
+    std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+    std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+    let path = root.join("BUILD.gn");
+    std::fs::write(&path, "\u{feff}foo = 1\nbar = foo\n").unwrap();
+
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+
+    // The BOM is not part of the content, so spans match client positions.
+    assert!(file.document.has_bom);
+    assert!(file.document.data.starts_with("foo"));
+    let variables = file.variables_at(file.document.data.len());
+    let range = |name: &str| {
+        let assignment = variables.get(name).unwrap().sorted_assignments()[0];
+        file.document.line_index.range(assignment.primary_variable)
+    };
+    assert_eq!(
+        range("foo"),
+        Range::new(Position::new(0, 0), Position::new(0, 3))
+    );
+    assert_eq!(
+        range("bar"),
+        Range::new(Position::new(1, 0), Position::new(1, 3))
+    );
+}
+
+#[test]
+fn test_links_in_dead_branches() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let path = testdata("workspaces/links/dead.gn");
+    storage.lock().unwrap().load_to_memory(
+        &path,
+        r#"if (false) {
+  deps = [ ":broken_false" ]
+}
+if (true) {
+} else if (!false) {
+  deps = [ ":broken_else" ]
+}
+if (enable_foo) {
+  deps = [ ":broken_unknown" ]
+}
+"#,
+        1,
+    );
+
+    let links = analyzer.links(&path, &finder, Instant::now()).unwrap();
+    let links: Vec<_> = links
+        .into_iter()
+        .map(|(_, target)| (target.name.unwrap(), target.exists, target.unreachable))
+        .collect();
+    assert_eq!(
+        links,
+        vec![
+            ("broken_false".to_string(), false, true),
+            ("broken_else".to_string(), false, true),
+            ("broken_unknown".to_string(), false, false),
+        ]
+    );
+}
+
+#[test]
+fn test_debug_dump_golden() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+
+    for name in ["smoke", "hover"] {
+        let path = testdata(format!("workspaces/{name}/BUILD.gn"));
+
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        let expected = std::fs::read_to_string(testdata(format!("golden/{name}.txt"))).unwrap();
+        assert_eq!(file.debug_dump(), expected, "{name}");
+
+        let file = analyzer
+            .analyze_shallow(&path, &finder, Instant::now())
+            .unwrap();
+        let expected =
+            std::fs::read_to_string(testdata(format!("golden/{name}.shallow.txt"))).unwrap();
+        assert_eq!(file.debug_dump(), expected, "{name}");
+    }
+}
+
+#[test]
+fn test_private_templates() {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let private_path = testdata("workspaces/smoke/private.gni");
+    let importer_path = testdata("workspaces/smoke/importer.gn");
+    storage.lock().unwrap().load_to_memory(
+        &private_path,
+        "template(\"_private\") {\n}\n_private(\"x\") {\n}\n",
+        1,
+    );
+    storage.lock().unwrap().load_to_memory(
+        &importer_path,
+        "import(\"//private.gni\")\n_private(\"y\") {\n}\n",
+        1,
+    );
+
+    // Private templates are available in the defining file...
+    let file = analyzer
+        .analyze(&private_path, &finder, Instant::now())
+        .unwrap();
+    assert!(file
+        .templates_at(file.document.data.len())
+        .contains("_private"));
+
+    // ...but not exported to importers.
+    let file = analyzer
+        .analyze(&importer_path, &finder, Instant::now())
+        .unwrap();
+    assert!(!file
+        .templates_at(file.document.data.len())
+        .contains("_private"));
+}
@@ -0,0 +1,138 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use either::Either;
+use itertools::Itertools;
+
+use crate::{
+    analyzer::data::Variable,
+    parser::{BinaryOp, Expr, LValue, PrimaryExpr, Statement},
+};
+
+/// Type of a GN value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValueType {
+    Boolean,
+    Integer,
+    String,
+    List,
+    Scope,
+}
+
+impl ValueType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ValueType::Boolean => "bool",
+            ValueType::Integer => "int",
+            ValueType::String => "string",
+            ValueType::List => "list",
+            ValueType::Scope => "scope",
+        }
+    }
+}
+
+/// Infers the type of an expression without evaluating identifiers.
+pub fn infer_expr_type(expr: &Expr) -> Option<ValueType> {
+    match expr {
+        Expr::Primary(primary) => match primary.as_ref() {
+            PrimaryExpr::Identifier(identifier) => {
+                matches!(identifier.name, "true" | "false").then_some(ValueType::Boolean)
+            }
+            PrimaryExpr::Integer(_) => Some(ValueType::Integer),
+            PrimaryExpr::String(_) => Some(ValueType::String),
+            PrimaryExpr::List(_) => Some(ValueType::List),
+            PrimaryExpr::Block(_) => Some(ValueType::Scope),
+            PrimaryExpr::ParenExpr(paren) => infer_expr_type(&paren.expr),
+            PrimaryExpr::Call(_)
+            | PrimaryExpr::ArrayAccess(_)
+            | PrimaryExpr::ScopeAccess(_)
+            | PrimaryExpr::Error(_) => None,
+        },
+        Expr::Unary(_) => Some(ValueType::Boolean),
+        Expr::Binary(binary) => match binary.op {
+            BinaryOp::Add | BinaryOp::Sub => {
+                // Either operand determines the type, e.g. `"lib" + name`.
+                infer_expr_type(&binary.lhs).or_else(|| infer_expr_type(&binary.rhs))
+            }
+            BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge
+            | BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::And
+            | BinaryOp::Or => Some(ValueType::Boolean),
+        },
+    }
+}
+
+/// Infers the type of elements of a list literal. Returns `None` if it is
+/// unknown or elements disagree.
+pub fn infer_element_type(expr: &Expr) -> Option<ValueType> {
+    let mut types = expr.as_primary_list()?.values.iter().map(infer_expr_type);
+    let first = types.next()??;
+    types.all(|ty| ty == Some(first)).then_some(first)
+}
+
+/// Returns the names of members assigned in every scope literal of a list
+/// literal, in the order of the first scope.
+pub fn common_scope_members<'i>(expr: &Expr<'i>) -> Vec<&'i str> {
+    let Some(list) = expr.as_primary_list() else {
+        return Vec::new();
+    };
+    let scope_members: Vec<Vec<&'i str>> = list
+        .values
+        .iter()
+        .map(|value| match value.as_primary() {
+            Some(PrimaryExpr::Block(block)) => block
+                .statements
+                .iter()
+                .filter_map(|statement| match statement {
+                    Statement::Assignment(assignment) => match &assignment.lvalue {
+                        LValue::Identifier(identifier) => Some(identifier.name),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect();
+    let Some((first, rest)) = scope_members.split_first() else {
+        return Vec::new();
+    };
+    first
+        .iter()
+        .copied()
+        .unique()
+        .filter(|name| rest.iter().all(|members| members.contains(name)))
+        .collect()
+}
+
+impl Variable<'_, '_> {
+    /// Infers the type of the variable from its assignments. Returns `None` if
+    /// it is unknown or assignments disagree.
+    pub fn inferred_type(&self) -> Option<ValueType> {
+        let mut types = self.assignments.values().filter_map(|assignment| {
+            match assignment.assignment_or_call {
+                Either::Left(assignment) if matches!(assignment.lvalue, LValue::Identifier(_)) => {
+                    infer_expr_type(&assignment.rvalue)
+                }
+                _ => None,
+            }
+        });
+        let first = types.next()?;
+        types.all(|ty| ty == first).then_some(first)
+    }
+}
@@ -24,3 +24,46 @@ pub fn resolve_path(name: &str, root_dir: &Path, current_dir: &Path) -> PathBuf
     }
     .normalize()
 }
+
+/// Returns `path` with the casing of each component below `root` replaced by
+/// the casing on disk, so that paths spelled differently on a
+/// case-insensitive filesystem map to one file. Components that do not exist
+/// are kept as they are.
+pub fn normalize_path_case(path: &Path, root: &Path) -> PathBuf {
+    let Ok(relative_path) = path.strip_prefix(root) else {
+        return path.to_path_buf();
+    };
+    let mut normalized = root.to_path_buf();
+    let mut on_disk = true;
+    for component in relative_path.components() {
+        let name = component.as_os_str();
+        let actual_name = on_disk
+            .then(|| {
+                let entry_names: Vec<_> = std::fs::read_dir(&normalized)
+                    .ok()?
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name())
+                    .collect();
+                // Prefer an exact match in case both casings exist.
+                entry_names
+                    .iter()
+                    .find(|entry_name| *entry_name == name)
+                    .or_else(|| {
+                        entry_names
+                            .iter()
+                            .find(|entry_name| entry_name.eq_ignore_ascii_case(name))
+                    })
+                    .cloned()
+            })
+            .flatten();
+        on_disk = actual_name.is_some();
+        normalized.push(actual_name.as_deref().unwrap_or(name));
+    }
+    normalized
+}
+
+/// Guesses whether the filesystem of a workspace is case-insensitive by
+/// looking up its `.gn` file with a different casing.
+pub fn detect_case_insensitive(root: &Path) -> bool {
+    root.join(".gn").exists() && root.join(".GN").exists()
+}
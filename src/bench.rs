@@ -15,22 +15,68 @@
 use std::{
     path::Path,
     sync::{Arc, Mutex},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use futures::future::join_all;
+use tower_lsp::lsp_types::{
+    CompletionParams, HoverParams, Position, TextDocumentIdentifier, TextDocumentPositionParams,
+    Url,
+};
 
 use crate::{
     analyzer::Analyzer,
     common::{storage::DocumentStorage, utils::find_gn_files, workspace::WorkspaceFinder},
+    parser::{parse, Node},
+    server::{
+        providers::{completion::completion, hover::hover},
+        RequestContext,
+    },
 };
 
-pub async fn run_bench(workspace_root: &Path) {
+/// Number of statements in the generated file parsed by the expressions
+/// benchmark.
+const GENERATED_STATEMENTS: usize = 2000;
+
+/// Number of times the expressions benchmark parses the generated file.
+const GENERATED_ITERATIONS: usize = 10;
+
+/// Maximum number of identifiers per file to request hover and completion
+/// at.
+const LATENCY_SAMPLES_PER_FILE: usize = 8;
+
+/// What a benchmark measures.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BenchMode {
+    /// Parses and analyzes files.
+    Analyze,
+    /// Only parses files, to separate parsing cost from analysis cost.
+    Parse,
+    /// Parses a large generated file dense with expressions, ignoring the
+    /// workspace, to measure expression parsing in isolation.
+    Expressions,
+    /// Requests hover and completion at identifiers, to measure latency as
+    /// seen by editors.
+    Latency,
+}
+
+impl BenchMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "analyze" => Some(BenchMode::Analyze),
+            "parse" => Some(BenchMode::Parse),
+            "expressions" => Some(BenchMode::Expressions),
+            "latency" => Some(BenchMode::Latency),
+            _ => None,
+        }
+    }
+}
+
+async fn bench_analyze(workspace_root: &Path, start_time: Instant) -> usize {
     let storage = Arc::new(Mutex::new(DocumentStorage::new()));
     let analyzer = Arc::new(Analyzer::new(&storage));
     let finder = WorkspaceFinder::new(Some(workspace_root));
 
-    let start_time = Instant::now();
     let mut count = 0;
 
     let mut tasks = Vec::new();
@@ -45,8 +91,185 @@ pub async fn run_bench(workspace_root: &Path) {
     }
     join_all(tasks).await;
 
+    eprintln!();
+    for (root, stats) in analyzer.stats() {
+        eprintln!(
+            "{}: parse {:.1}ms, shallow analysis {:.1}ms, full analysis {:.1}ms, \
+             cache hits {}, cache misses {}",
+            root.display(),
+            stats.parse_ms,
+            stats.shallow_analysis_ms,
+            stats.full_analysis_ms,
+            stats.cache_hits,
+            stats.cache_misses
+        );
+    }
+    count
+}
+
+fn bench_parse(workspace_root: &Path) -> usize {
+    let mut count = 0;
+    for path in find_gn_files(workspace_root) {
+        let Ok(data) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        std::hint::black_box(parse(&data));
+        eprint!(".");
+        count += 1;
+    }
+    count
+}
+
+/// Generates a BUILD.gn file with many operators and nested expressions.
+fn generate_build_file() -> String {
+    let mut data = String::new();
+    for i in 0..GENERATED_STATEMENTS {
+        data.push_str(&format!(
+            "if (!is_win && (count_{i} + 1 >= {i} || enable_{i} == false)) {{\n  \
+             sources = [ \"a_{i}.cc\" ] + extra_sources - [ \"b_{i}.cc\" ]\n}}\n"
+        ));
+    }
+    data
+}
+
+fn bench_expressions() -> usize {
+    let data = generate_build_file();
+    for _ in 0..GENERATED_ITERATIONS {
+        std::hint::black_box(parse(&data));
+        eprint!(".");
+    }
+    GENERATED_ITERATIONS
+}
+
+/// Returns the value at a percentile of sorted latencies.
+fn percentile(sorted: &[Duration], percent: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    sorted[(sorted.len() - 1) * percent / 100]
+}
+
+async fn bench_latency(workspace_root: &Path) -> usize {
+    let context = RequestContext::new_for_testing();
+    let mut hover_latencies = Vec::new();
+    let mut completion_latencies = Vec::new();
+
+    let mut count = 0;
+    for path in find_gn_files(workspace_root) {
+        let Ok(uri) = Url::from_file_path(&path) else {
+            continue;
+        };
+        let Ok(file) = context
+            .analyzer
+            .analyze(&path, &context.finder, context.request_time)
+        else {
+            continue;
+        };
+        let positions: Vec<Position> = file
+            .ast
+            .identifiers()
+            .take(LATENCY_SAMPLES_PER_FILE)
+            .map(|identifier| file.document.line_index.position(identifier.span.end()))
+            .collect();
+
+        for position in positions {
+            let text_document_position = TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position,
+            };
+
+            let start_time = Instant::now();
+            hover(
+                &context,
+                HoverParams {
+                    text_document_position_params: text_document_position.clone(),
+                    work_done_progress_params: Default::default(),
+                },
+            )
+            .await
+            .ok();
+            hover_latencies.push(start_time.elapsed());
+
+            let start_time = Instant::now();
+            completion(
+                &context,
+                CompletionParams {
+                    text_document_position,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                    context: None,
+                },
+            )
+            .await
+            .ok();
+            completion_latencies.push(start_time.elapsed());
+        }
+        eprint!(".");
+        count += 1;
+    }
+
+    eprintln!();
+    for (name, latencies) in [
+        ("hover", &mut hover_latencies),
+        ("completion", &mut completion_latencies),
+    ] {
+        latencies.sort();
+        eprintln!(
+            "{}: {} requests, p50 {:.2}ms, p95 {:.2}ms",
+            name,
+            latencies.len(),
+            percentile(latencies, 50).as_secs_f64() * 1000.0,
+            percentile(latencies, 95).as_secs_f64() * 1000.0
+        );
+    }
+    count
+}
+
+/// Runs a benchmark over the workspace. Returns the number of processed files
+/// and the elapsed time.
+pub async fn run_bench(workspace_root: &Path, mode: BenchMode) -> (usize, Duration) {
+    let start_time = Instant::now();
+
+    let count = match mode {
+        BenchMode::Analyze => bench_analyze(workspace_root, start_time).await,
+        BenchMode::Parse => bench_parse(workspace_root),
+        BenchMode::Expressions => bench_expressions(),
+        BenchMode::Latency => bench_latency(workspace_root).await,
+    };
+
     let elapsed = start_time.elapsed();
 
     eprintln!();
-    eprintln!("Processed {} files in {:.1}s", count, elapsed.as_secs_f64());
+    eprintln!(
+        "Processed {} files in {:.1}s ({:.0} files/s)",
+        count,
+        elapsed.as_secs_f64(),
+        count as f64 / elapsed.as_secs_f64()
+    );
+    (count, elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_parse_only_bench() {
+        let (count, _) = run_bench(&testdata("workspaces/smoke"), BenchMode::Parse).await;
+        assert!(count > 0);
+    }
+
+    #[tokio::test]
+    async fn test_expressions_bench() {
+        let (count, _) = run_bench(&testdata("workspaces/smoke"), BenchMode::Expressions).await;
+        assert_eq!(count, GENERATED_ITERATIONS);
+    }
+
+    #[tokio::test]
+    async fn test_latency_bench() {
+        let (count, _) = run_bench(&testdata("workspaces/smoke"), BenchMode::Latency).await;
+        assert!(count > 0);
+    }
 }
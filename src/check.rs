@@ -0,0 +1,129 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
+use crate::{
+    analyzer::Analyzer,
+    common::{storage::DocumentStorage, utils::find_gn_files, workspace::WorkspaceFinder},
+};
+
+/// Returns whether a label pointing to `path` should be checked.
+/// `label_check_roots` are source-absolute directories, e.g. `//src`, and
+/// empty `label_check_roots` check every label.
+fn is_label_checked(path: &Path, workspace_root: &Path, label_check_roots: &[String]) -> bool {
+    label_check_roots.is_empty()
+        || label_check_roots
+            .iter()
+            .any(|root| path.starts_with(workspace_root.join(root.trim_start_matches('/'))))
+}
+
+/// Prints unresolved links in the given files or directories. Labels outside
+/// `label_check_roots` are not checked unless it is empty. Returns the number
+/// of unresolved links found.
+pub fn run_check_links(paths: &[PathBuf], label_check_roots: &[String]) -> usize {
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(None);
+    let request_time = Instant::now();
+
+    let mut count = 0;
+    for path in paths {
+        let Ok(path) = std::path::absolute(path) else {
+            eprintln!("{}: invalid path", path.display());
+            continue;
+        };
+        let files: Vec<PathBuf> = if path.is_dir() {
+            find_gn_files(&path).collect()
+        } else {
+            vec![path]
+        };
+        for file in files {
+            count += check_file_links(&analyzer, &file, &finder, request_time, label_check_roots);
+        }
+    }
+    count
+}
+
+fn check_file_links(
+    analyzer: &Analyzer,
+    path: &Path,
+    finder: &WorkspaceFinder,
+    request_time: Instant,
+    label_check_roots: &[String],
+) -> usize {
+    let links = match analyzer.links(path, finder, request_time) {
+        Ok(links) => links,
+        Err(err) => {
+            eprintln!("{}: {}", path.display(), err);
+            return 0;
+        }
+    };
+    let workspace_root = finder.find_for(path);
+    let mut count = 0;
+    // Skip links in dead branches to avoid cross-platform false positives.
+    for (range, target) in links.iter().filter(|(_, target)| {
+        !target.exists
+            && !target.unreachable
+            && match (&target.name, workspace_root) {
+                (Some(_), Some(workspace_root)) => {
+                    is_label_checked(&target.path, workspace_root, label_check_roots)
+                }
+                _ => true,
+            }
+    }) {
+        let destination = match &target.name {
+            Some(name) => format!("{}:{}", target.path.display(), name),
+            None => target.path.display().to_string(),
+        };
+        println!(
+            "{}:{}:{}: unresolved link to {}",
+            path.display(),
+            range.start.line + 1,
+            range.start.character + 1,
+            destination
+        );
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::testutils::TestWorkspace;
+
+    use super::*;
+
+    #[test]
+    fn test_label_check_roots() {
+        let workspace = TestWorkspace::new([
+            (
+                "src/BUILD.gn",
+                "group(\"main\") {\n  deps = [\n    \"//src/missing\",\n    \"//third_party/missing\",\n  ]\n}\n",
+            ),
+            ("src/missing/BUILD.gn", ""),
+            ("third_party/missing/BUILD.gn", ""),
+        ]);
+        let paths = [workspace.path("src/BUILD.gn")];
+
+        assert_eq!(run_check_links(&paths, &[]), 2);
+        // Missing labels outside the roots are not reported.
+        assert_eq!(run_check_links(&paths, &["//src".to_string()]), 1);
+        assert_eq!(run_check_links(&paths, &["//build".to_string()]), 0);
+    }
+}
@@ -18,6 +18,19 @@ pub const DECLARE_ARGS: &str = "declare_args";
 pub const FOREACH: &str = "foreach";
 pub const SET_DEFAULTS: &str = "set_defaults";
 pub const FORWARD_VARIABLES_FROM: &str = "forward_variables_from";
+pub const TARGET_NAME: &str = "target_name";
+
+/// Target types that compile code, which are pointless without sources or
+/// dependencies.
+pub const SOURCE_TARGETS: &[&str] = &[
+    "executable",
+    "loadable_module",
+    "rust_library",
+    "rust_proc_macro",
+    "shared_library",
+    "source_set",
+    "static_library",
+];
 
 pub struct BuiltinSymbol {
     pub name: &'static str,
@@ -13,30 +13,111 @@
 // limitations under the License.
 
 use std::fmt::Display;
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
 
 use itertools::Itertools;
 use tower_lsp::lsp_types::{ConfigurationItem, Diagnostic, MessageType, Url};
 
 use crate::common::config::Configurations;
 
+/// Returns the verbosity of a message type. Higher values are more verbose.
+fn verbosity(typ: MessageType) -> u8 {
+    if typ == MessageType::ERROR {
+        0
+    } else if typ == MessageType::WARNING {
+        1
+    } else if typ == MessageType::INFO {
+        2
+    } else {
+        3
+    }
+}
+
+/// Parses a log level name as accepted by `--log-level`.
+pub fn parse_log_level(name: &str) -> Option<MessageType> {
+    match name {
+        "error" => Some(MessageType::ERROR),
+        "warning" => Some(MessageType::WARNING),
+        "info" => Some(MessageType::INFO),
+        "log" => Some(MessageType::LOG),
+        _ => None,
+    }
+}
+
+/// Diagnostics published to each URI, in order.
+#[cfg(test)]
+type PublishedDiagnostics = Vec<(Url, Vec<Diagnostic>)>;
+
 #[derive(Clone)]
 pub struct TestableClient {
     client: Option<tower_lsp::Client>,
+    log_level: MessageType,
+    #[cfg(test)]
+    published_diagnostics: Arc<Mutex<PublishedDiagnostics>>,
+    #[cfg(test)]
+    configurations: Arc<Mutex<Configurations>>,
+    #[cfg(test)]
+    logged_messages: Arc<Mutex<Vec<(MessageType, String)>>>,
 }
 
 impl TestableClient {
     pub fn new(client: tower_lsp::Client) -> Self {
         Self {
             client: Some(client),
+            log_level: MessageType::LOG,
+            #[cfg(test)]
+            published_diagnostics: Default::default(),
+            #[cfg(test)]
+            configurations: Default::default(),
+            #[cfg(test)]
+            logged_messages: Default::default(),
         }
     }
 
-    #[cfg(test)]
+    /// Drops log messages less severe than `log_level`.
+    pub fn with_log_level(self, log_level: MessageType) -> Self {
+        Self { log_level, ..self }
+    }
+
+    /// Creates a client that sends nothing, for tests and benchmarks.
     pub fn new_for_testing() -> Self {
-        Self { client: None }
+        Self {
+            client: None,
+            log_level: MessageType::LOG,
+            #[cfg(test)]
+            published_diagnostics: Default::default(),
+            #[cfg(test)]
+            configurations: Default::default(),
+            #[cfg(test)]
+            logged_messages: Default::default(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn logged_messages(&self) -> Vec<(MessageType, String)> {
+        self.logged_messages.lock().unwrap().clone()
+    }
+
+    #[cfg(test)]
+    pub fn set_configurations(&self, configurations: Configurations) {
+        *self.configurations.lock().unwrap() = configurations;
+    }
+
+    #[cfg(test)]
+    pub fn published_diagnostics(&self) -> PublishedDiagnostics {
+        self.published_diagnostics.lock().unwrap().clone()
     }
 
     pub async fn log_message<M: Display>(&self, typ: MessageType, message: M) {
+        if verbosity(typ) > verbosity(self.log_level) {
+            return;
+        }
+        #[cfg(test)]
+        self.logged_messages
+            .lock()
+            .unwrap()
+            .push((typ, message.to_string()));
         if let Some(client) = &self.client {
             client.log_message(typ, message).await;
         }
@@ -44,6 +125,9 @@ impl TestableClient {
 
     pub async fn configurations(&self) -> Configurations {
         let Some(client) = &self.client else {
+            #[cfg(test)]
+            return self.configurations.lock().unwrap().clone();
+            #[cfg(not(test))]
             return Configurations::default();
         };
 
@@ -70,6 +154,11 @@ impl TestableClient {
         diags: Vec<Diagnostic>,
         version: Option<i32>,
     ) {
+        #[cfg(test)]
+        self.published_diagnostics
+            .lock()
+            .unwrap()
+            .push((uri.clone(), diags.clone()));
         if let Some(client) = &self.client {
             client.publish_diagnostics(uri, diags, version).await;
         };
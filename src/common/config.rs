@@ -18,14 +18,30 @@ fn default_true() -> bool {
     true
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Configurations {
     pub binary_path: Option<PathBuf>,
+    /// Output directory for `gn gen`, relative to the workspace root.
+    pub out_dir: Option<PathBuf>,
     #[serde(default = "default_true")]
     pub background_indexing: bool,
     #[serde(default = "default_true")]
     pub error_reporting: bool,
+    #[serde(default)]
+    pub cache: CacheConfigurations,
+    #[serde(default)]
+    pub lints: LintConfigurations,
+    #[serde(default)]
+    pub diagnostic_profiles: Vec<DiagnosticProfile>,
+    #[serde(default)]
+    pub workspace: WorkspaceConfigurations,
+    #[serde(default)]
+    pub display: DisplayConfigurations,
+    #[serde(default)]
+    pub formatting: FormattingConfigurations,
+    #[serde(default)]
+    pub import: ImportConfigurations,
     pub experimental: ExperimentalConfigurations,
 }
 
@@ -33,16 +49,205 @@ impl Default for Configurations {
     fn default() -> Self {
         Self {
             binary_path: Default::default(),
+            out_dir: Default::default(),
             background_indexing: true,
             error_reporting: true,
+            cache: Default::default(),
+            lints: Default::default(),
+            diagnostic_profiles: Default::default(),
+            workspace: Default::default(),
+            display: Default::default(),
+            formatting: Default::default(),
+            import: Default::default(),
             experimental: Default::default(),
         }
     }
 }
 
-#[derive(Default, serde::Serialize, serde::Deserialize)]
+fn default_check_interval_ms() -> u64 {
+    5000
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheConfigurations {
+    /// Interval to check freshness of files on disk. 0 means always check.
+    #[serde(default = "default_check_interval_ms")]
+    pub check_interval_ms: u64,
+    /// Stops checking freshness of files on disk, so that analysis sees a
+    /// consistent snapshot.
+    #[serde(default)]
+    pub freeze: bool,
+    /// Maximum number of analyzed files kept in memory for each workspace,
+    /// evicting the least recently analyzed ones. 0 means unlimited.
+    #[serde(default)]
+    pub max_files: usize,
+}
+
+impl Default for CacheConfigurations {
+    fn default() -> Self {
+        Self {
+            check_interval_ms: default_check_interval_ms(),
+            freeze: false,
+            max_files: 0,
+        }
+    }
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintConfigurations {
+    /// Warns about lines indented with tabs.
+    #[serde(default)]
+    pub no_tabs: bool,
+    /// Marks instantiations of templates commented as `DEPRECATED`.
+    #[serde(default)]
+    pub deprecated_templates: bool,
+    /// Warns about targets compiling code that declare neither sources nor
+    /// dependencies.
+    #[serde(default)]
+    pub require_sources: bool,
+    /// Reports assignments to reserved words such as `true` or `else`.
+    #[serde(default)]
+    pub reserved_words: bool,
+    /// Marks imports of files none of whose symbols are referenced.
+    #[serde(default)]
+    pub unused_imports: bool,
+}
+
+fn default_args_file_patterns() -> Vec<String> {
+    vec!["args.gn".to_string()]
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceConfigurations {
+    /// Directory to treat as `//` for files outside any GN workspace.
+    pub manual_root: Option<PathBuf>,
+    /// File name patterns of build argument files, such as `args.gn` in
+    /// output directories, where `*` matches any characters.
+    #[serde(default = "default_args_file_patterns")]
+    pub args_file_patterns: Vec<String>,
+    /// Additional directories, such as `//out/Default/gen`, to look up
+    /// BUILD.gn files for source-absolute labels missing in the source tree.
+    #[serde(default)]
+    pub label_search_roots: Vec<String>,
+    /// Whether paths differing only in casing refer to the same file, as on
+    /// macOS and Windows. Detected from the filesystem if unset.
+    #[serde(default)]
+    pub case_insensitive_paths: Option<bool>,
+}
+
+impl Default for WorkspaceConfigurations {
+    fn default() -> Self {
+        Self {
+            manual_root: Default::default(),
+            args_file_patterns: default_args_file_patterns(),
+            label_search_roots: Default::default(),
+            case_insensitive_paths: Default::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LabelStyle {
+    /// Labels like `//foo/bar:baz`.
+    #[default]
+    Absolute,
+    /// Labels relative to the current file's directory, like `bar:baz`.
+    Relative,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisplayConfigurations {
+    #[serde(default)]
+    pub label_style: LabelStyle,
+}
+
+fn default_line_width() -> usize {
+    80
+}
+
+fn default_inline_list_limit() -> usize {
+    1
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattingConfigurations {
+    /// Maximum line width for lists kept on a single line.
+    #[serde(default = "default_line_width")]
+    pub line_width: usize,
+    /// Maximum number of elements in lists kept on a single line.
+    #[serde(default = "default_inline_list_limit")]
+    pub inline_list_limit: usize,
+    /// Sorts assignments in `declare_args` blocks by variable name.
+    #[serde(default)]
+    pub sort_declare_args: bool,
+}
+
+impl Default for FormattingConfigurations {
+    fn default() -> Self {
+        Self {
+            line_width: default_line_width(),
+            inline_list_limit: default_inline_list_limit(),
+            sort_declare_args: false,
+        }
+    }
+}
+
+fn default_common_dirs() -> Vec<String> {
+    vec!["//build/config".to_string()]
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConfigurations {
+    /// Directories of commonly imported files, which are suggested first
+    /// when completing imports.
+    #[serde(default = "default_common_dirs")]
+    pub common_dirs: Vec<String>,
+}
+
+impl Default for ImportConfigurations {
+    fn default() -> Self {
+        Self {
+            common_dirs: default_common_dirs(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+/// Diagnostic settings applied to files whose names match a pattern, in
+/// place of the global settings.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticProfile {
+    /// File name pattern, where `*` matches any characters, e.g. `*.gni`.
+    pub pattern: String,
+    #[serde(default)]
+    pub lints: LintConfigurations,
+    #[serde(default)]
+    pub undefined_variable_analysis: bool,
+    /// Overrides the severity of lint diagnostics.
+    pub lint_severity: Option<LintSeverity>,
+}
+
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExperimentalConfigurations {
     pub undefined_variable_analysis: bool,
     pub workspace_symbols: bool,
+    pub template_type_hints: bool,
+    pub label_inlay_hints: bool,
 }
@@ -12,18 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{borrow::Cow, io::ErrorKind};
+use std::{borrow::Cow, io::ErrorKind, path::PathBuf};
+
+use tower_lsp::jsonrpc::ErrorCode;
+
+use crate::analyzer::DotGnError;
 
 pub type RpcError = tower_lsp::jsonrpc::Error;
 pub type RpcResult<T> = tower_lsp::jsonrpc::Result<T>;
 
-fn new_rpc_error(message: String) -> RpcError {
-    tower_lsp::jsonrpc::Error {
-        code: tower_lsp::jsonrpc::ErrorCode::ServerError(1),
-        message: Cow::from(message),
-        data: None,
-    }
-}
+// Server-defined JSON-RPC error codes.
+pub const GENERAL_ERROR_CODE: i64 = 1;
+pub const NOT_FOUND_ERROR_CODE: i64 = 2;
+pub const WORKSPACE_NOT_FOUND_ERROR_CODE: i64 = 3;
+pub const DOT_GN_ERROR_CODE: i64 = 4;
 
 #[derive(thiserror::Error, Clone, Debug)]
 pub enum Error {
@@ -31,17 +33,38 @@ pub enum Error {
     General(String),
     #[error("File not found")]
     NotFound,
+    #[error("Invalid parameters: {0}")]
+    InvalidParams(String),
+    #[error("Workspace not found for {}", .0.display())]
+    WorkspaceNotFound(PathBuf),
+    #[error(transparent)]
+    DotGn(#[from] DotGnError),
 }
 
 impl Error {
     pub fn is_not_found(&self) -> bool {
         matches!(self, Error::NotFound)
     }
+
+    /// Returns the JSON-RPC error code reported to the client.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::General(_) => ErrorCode::ServerError(GENERAL_ERROR_CODE),
+            Error::NotFound => ErrorCode::ServerError(NOT_FOUND_ERROR_CODE),
+            Error::InvalidParams(_) => ErrorCode::InvalidParams,
+            Error::WorkspaceNotFound(_) => ErrorCode::ServerError(WORKSPACE_NOT_FOUND_ERROR_CODE),
+            Error::DotGn(_) => ErrorCode::ServerError(DOT_GN_ERROR_CODE),
+        }
+    }
 }
 
 impl From<Error> for RpcError {
     fn from(error: Error) -> Self {
-        new_rpc_error(error.to_string())
+        RpcError {
+            code: error.code(),
+            message: Cow::from(error.to_string()),
+            data: None,
+        }
     }
 }
 
@@ -36,10 +36,20 @@ pub struct Document {
     pub data: Pin<String>,
     pub version: DocumentVersion,
     pub line_index: LineIndex<'static>,
+    /// Whether a leading UTF-8 byte order mark was stripped from the data.
+    /// Clients don't count it in positions, so offsets exclude it too.
+    pub has_bom: bool,
+    /// Whether the file was not valid UTF-8. Invalid sequences in the data
+    /// are replaced with U+FFFD.
+    pub invalid_utf8: bool,
 }
 
 impl Document {
-    pub fn new(path: &Path, data: String, version: DocumentVersion) -> Self {
+    pub fn new(path: &Path, mut data: String, version: DocumentVersion) -> Self {
+        let has_bom = data.starts_with('\u{feff}');
+        if has_bom {
+            data.replace_range(..'\u{feff}'.len_utf8(), "");
+        }
         let data = Pin::new(data);
         let line_index = LineIndex::new(&data);
         // SAFETY: line_index is backed by pinned data.
@@ -49,6 +59,21 @@ impl Document {
             data,
             version,
             line_index,
+            has_bom,
+            invalid_utf8: false,
+        }
+    }
+
+    pub fn from_bytes(path: &Path, data: Vec<u8>, version: DocumentVersion) -> Self {
+        match String::from_utf8(data) {
+            Ok(data) => Self::new(path, data, version),
+            Err(err) => {
+                let data = String::from_utf8_lossy(err.as_bytes()).into_owned();
+                Self {
+                    invalid_utf8: true,
+                    ..Self::new(path, data, version)
+                }
+            }
         }
     }
 
@@ -61,6 +86,7 @@ impl std::hash::Hash for Document {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.path.hash(state);
         self.data.hash(state);
+        self.has_bom.hash(state);
         // Skip LineIndex as it's derived from data.
         self.version.hash(state);
     }
@@ -68,7 +94,10 @@ impl std::hash::Hash for Document {
 
 impl PartialEq for Document {
     fn eq(&self, other: &Self) -> bool {
-        self.path == other.path && self.data == other.data && self.version == other.version
+        self.path == other.path
+            && self.data == other.data
+            && self.has_bom == other.has_bom
+            && self.version == other.version
     }
 }
 
@@ -102,8 +131,8 @@ impl DocumentStorage {
         }
         // Read the version first to be pesimistic about file changes.
         let version = self.read_version(path);
-        let data = std::fs::read_to_string(path).unwrap_or_default();
-        Arc::pin(Document::new(path, data, version))
+        let data = std::fs::read(path).unwrap_or_default();
+        Arc::pin(Document::from_bytes(path, data, version))
     }
 
     pub fn load_to_memory(&mut self, path: &Path, data: &str, revision: i32) {
@@ -121,6 +150,10 @@ impl DocumentStorage {
         self.memory_docs.remove(path);
     }
 
+    pub fn is_in_memory(&self, path: &Path) -> bool {
+        self.memory_docs.contains_key(path)
+    }
+
     pub fn memory_docs(&self) -> Vec<Pin<Arc<Document>>> {
         self.memory_docs.values().cloned().collect()
     }
@@ -22,3 +22,40 @@ pub fn testdata(name: impl AsRef<Path>) -> PathBuf {
     path.push(name.as_ref());
     path
 }
+
+/// A workspace written to a temporary directory, which is removed on drop.
+/// Useful for cross-file tests that would otherwise need many fixture files.
+pub struct TestWorkspace {
+    dir: tempfile::TempDir,
+}
+
+impl TestWorkspace {
+    /// Creates a workspace with a `.gn` file, an empty `BUILDCONFIG.gn`, and
+    /// the given files keyed by their paths relative to the root. Files may
+    /// override `.gn` and `BUILDCONFIG.gn`.
+    pub fn new<'a>(files: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = Self { dir };
+        workspace.write(".gn", "buildconfig = \"//BUILDCONFIG.gn\"\n");
+        workspace.write("BUILDCONFIG.gn", "");
+        for (name, data) in files {
+            workspace.write(name, data);
+        }
+        workspace
+    }
+
+    pub fn root(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn path(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.root().join(name)
+    }
+
+    /// Writes a file, creating its parent directories as needed.
+    pub fn write(&self, name: impl AsRef<Path>, data: &str) {
+        let path = self.path(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, data).unwrap();
+    }
+}
@@ -39,40 +39,83 @@ pub fn find_gn_files(root: &Path) -> impl Iterator<Item = PathBuf> {
     })
 }
 
+/// Matches a file name against a pattern, where `*` matches any characters.
+pub fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| matches_pattern(rest, &name[i..]))
+        }
+    }
+}
+
+/// Returns whether the file name matches any of the build argument file
+/// patterns, such as `args.gn`.
+pub fn is_args_file(patterns: &[String], path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    patterns
+        .iter()
+        .any(|pattern| matches_pattern(pattern, name))
+}
+
 #[derive(Clone, Debug)]
 pub struct LineIndex<'i> {
     input: &'i str,
-    lines: Vec<&'i str>,
+    /// Byte offsets where each line starts.
+    line_starts: Vec<usize>,
+    /// Whether each line is ASCII-only, so that UTF-16 columns equal byte
+    /// columns and conversions within the line take constant time.
+    ascii_lines: Vec<bool>,
 }
 
 impl<'i> LineIndex<'i> {
     pub fn new(input: &'i str) -> Self {
-        let mut lines: Vec<&str> = input.split_inclusive('\n').collect();
-        if input.is_empty() {
-            lines.push(input);
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(input.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let ascii_lines = line_starts
+            .iter()
+            .zip(line_starts.iter().skip(1).chain([&input.len()]))
+            .map(|(&start, &end)| input[start..end].is_ascii())
+            .collect();
+        Self {
+            input,
+            line_starts,
+            ascii_lines,
         }
-        if input.ends_with('\n') {
-            lines.push(&input[input.len()..]);
-        }
-        Self { input, lines }
     }
 
-    fn str_offset(&self, s: &str) -> usize {
-        // SAFETY: s must be in the same string as input.
-        unsafe { s.as_ptr().offset_from(self.input.as_ptr()) as usize }
+    fn line(&self, index: usize) -> &'i str {
+        let start = self.line_starts[index];
+        let end = self
+            .line_starts
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.input.len());
+        &self.input[start..end]
     }
 
     pub fn position(&self, offset: usize) -> Position {
         let index = self
-            .lines
-            .binary_search_by_key(&offset, |line| self.str_offset(line))
-            .unwrap_or_else(|index| index - 1);
-        let line = self.lines[index];
-        let bytes = offset - self.str_offset(line);
-        let character = line
-            .get(..bytes)
-            .map(|s| s.encode_utf16().count())
-            .unwrap_or(0);
+            .line_starts
+            .partition_point(|&start| start <= offset)
+            .saturating_sub(1);
+        let bytes = offset - self.line_starts[index];
+        let character = if self.ascii_lines[index] {
+            bytes.min(self.line(index).len())
+        } else {
+            self.line(index)
+                .get(..bytes)
+                .map(|s| s.encode_utf16().count())
+                .unwrap_or(0)
+        };
         Position {
             line: index as u32,
             character: character as u32,
@@ -86,18 +129,32 @@ impl<'i> LineIndex<'i> {
         }
     }
 
+    /// Returns the offset of a position like `offset`, but clamps characters
+    /// past the end of the line to the end of the line.
+    pub fn clamped_offset(&self, position: Position) -> Option<usize> {
+        let index = position.line as usize;
+        let start = *self.line_starts.get(index)?;
+        let end = start + self.line(index).trim_end_matches(['\n', '\r']).len();
+        Some(self.offset(position).map_or(end, |offset| offset.min(end)))
+    }
+
     pub fn offset(&self, position: Position) -> Option<usize> {
-        let line = self.lines.get(position.line as usize)?;
+        let index = position.line as usize;
+        let start = *self.line_starts.get(index)?;
+        let line = self.line(index);
+        let target = position.character as usize;
+        if self.ascii_lines[index] {
+            return (target <= line.len()).then_some(start + target);
+        }
         let mut character = 0;
         for (i, ch) in line.char_indices() {
-            if character >= position.character as usize {
-                return Some(self.str_offset(line) + i);
+            if character >= target {
+                return Some(start + i);
             }
-            let mut buf = [0; 2];
-            character += ch.encode_utf16(&mut buf).len();
+            character += ch.len_utf16();
         }
-        if character >= position.character as usize {
-            Some(self.str_offset(line) + line.len())
+        if character >= target {
+            Some(start + line.len())
         } else {
             None
         }
@@ -135,6 +192,15 @@ impl AsyncSignal {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*.gni", "foo.gni"));
+        assert!(matches_pattern("BUILD.gn", "BUILD.gn"));
+        assert!(matches_pattern("*_test*.gn", "foo_test_bar.gn"));
+        assert!(!matches_pattern("*.gni", "BUILD.gn"));
+        assert!(!matches_pattern("BUILD.gn", "xBUILD.gn"));
+    }
+
     #[test]
     fn line_index() {
         let input = "\n\nfoo\n\n";
@@ -159,6 +225,12 @@ mod tests {
         assert_eq!(index.offset(Position::new(4, 0)), Some(7));
         assert_eq!(index.offset(Position::new(4, 1)), None);
         assert_eq!(index.offset(Position::new(5, 0)), None);
+
+        assert_eq!(index.clamped_offset(Position::new(2, 2)), Some(4));
+        assert_eq!(index.clamped_offset(Position::new(2, 4)), Some(5));
+        assert_eq!(index.clamped_offset(Position::new(2, 9)), Some(5));
+        assert_eq!(index.clamped_offset(Position::new(4, 1)), Some(7));
+        assert_eq!(index.clamped_offset(Position::new(5, 0)), None);
     }
 
     #[test]
@@ -182,6 +254,57 @@ mod tests {
         assert_eq!(index.offset(Position::new(3, 0)), None);
     }
 
+    #[test]
+    fn line_index_many_lines() {
+        let input: String = (0..5000).map(|i| format!("line{i}\n")).collect();
+        let index = LineIndex::new(&input);
+
+        let mut offset = 0;
+        for i in 0..5000 {
+            assert_eq!(index.position(offset), Position::new(i, 0));
+            assert_eq!(index.offset(Position::new(i, 0)), Some(offset));
+            offset += format!("line{i}\n").len();
+        }
+        assert_eq!(index.position(offset), Position::new(5000, 0));
+        assert_eq!(index.offset(Position::new(5001, 0)), None);
+    }
+
+    #[test]
+    fn line_index_non_ascii() {
+        let input = "a\u{e9}\u{1f600}b\nc";
+        let index = LineIndex::new(input);
+
+        assert_eq!(index.position(1), Position::new(0, 1));
+        assert_eq!(index.position(3), Position::new(0, 2));
+        assert_eq!(index.position(7), Position::new(0, 4));
+        assert_eq!(index.position(9), Position::new(1, 0));
+
+        assert_eq!(index.offset(Position::new(0, 2)), Some(3));
+        assert_eq!(index.offset(Position::new(0, 4)), Some(7));
+        assert_eq!(index.offset(Position::new(0, 6)), Some(9));
+        assert_eq!(index.offset(Position::new(0, 7)), None);
+        assert_eq!(index.offset(Position::new(1, 1)), Some(10));
+    }
+
+    /// Run with `cargo test -- --ignored --nocapture` to see timings.
+    #[test]
+    #[ignore]
+    fn bench_line_index_long_line() {
+        let input = "x".repeat(1 << 20);
+        let index = LineIndex::new(&input);
+
+        let start_time = std::time::Instant::now();
+        for offset in (0..input.len()).step_by(16) {
+            let position = index.position(offset);
+            assert_eq!(index.offset(position), Some(offset));
+        }
+        eprintln!(
+            "{} conversions in {:?}",
+            input.len() / 16 * 2,
+            start_time.elapsed()
+        );
+    }
+
     #[test]
     fn line_index_empty() {
         let input = "";
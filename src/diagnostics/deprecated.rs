@@ -0,0 +1,100 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString};
+
+use crate::analyzer::{AnalyzedBlock, AnalyzedStatement, TopLevelStatementsExt};
+
+/// Diagnostic code of deprecated template instantiations.
+pub const DEPRECATED_TEMPLATE: &str = "deprecated-template";
+
+pub fn collect_deprecated_templates(
+    analyzed_root: &AnalyzedBlock,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for statement in analyzed_root.top_level_statements() {
+        let AnalyzedStatement::Target(target) = statement else {
+            continue;
+        };
+        let function = &target.call.function;
+        let templates = analyzed_root.templates_at(target.call.span.start());
+        let Some(template) = templates.get(function.name) else {
+            continue;
+        };
+        if !template.comments.is_deprecated() {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            range: analyzed_root.document.line_index.range(function.span),
+            severity: Some(DiagnosticSeverity::HINT),
+            code: Some(NumberOrString::String(DEPRECATED_TEMPLATE.to_string())),
+            message: format!("Template {} is deprecated", function.name),
+            tags: Some(vec![DiagnosticTag::DEPRECATED]),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::{
+        analyzer::Analyzer,
+        common::{storage::DocumentStorage, testutils::testdata, workspace::WorkspaceFinder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_deprecated_templates() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let path = testdata("workspaces/smoke/deprecated.gn");
+        storage.lock().unwrap().load_to_memory(
+            &path,
+            r#"# DEPRECATED: Use new_foo instead.
+template("foo") {
+}
+
+template("new_foo") {
+}
+
+foo("a") {
+}
+
+new_foo("b") {
+}
+"#,
+            1,
+        );
+
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        let mut diagnostics = Vec::new();
+        collect_deprecated_templates(&file.analyzed_root, &mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(7, 0), Position::new(7, 3))
+        );
+        assert_eq!(diagnostics[0].tags, Some(vec![DiagnosticTag::DEPRECATED]));
+    }
+}
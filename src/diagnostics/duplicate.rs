@@ -0,0 +1,206 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use pest::Span;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Url,
+};
+
+use crate::{
+    analyzer::{AnalyzedBlock, AnalyzedStatement, Template},
+    common::storage::Document,
+    parser::{Call, Node},
+};
+
+/// Diagnostic code of templates defined more than once.
+pub const DUPLICATE_TEMPLATE: &str = "duplicate-template";
+
+/// Returns the span of the first argument of a call, or the call itself.
+fn name_span<'i>(call: &Call<'i>) -> Span<'i> {
+    call.args.first().map_or(call.span, |arg| arg.span())
+}
+
+/// Returns whether two templates are the same definition, e.g. a file
+/// imported more than once.
+fn is_same_definition(a: &Template, b: &Template) -> bool {
+    a.document.path == b.document.path && a.call.span.start() == b.call.span.start()
+}
+
+fn duplicate_diagnostic(
+    document: &Document,
+    span: Span,
+    message: String,
+    previous: &Template,
+) -> Diagnostic {
+    let related_information = Url::from_file_path(&previous.document.path)
+        .ok()
+        .map(|uri| {
+            vec![DiagnosticRelatedInformation {
+                location: Location {
+                    uri,
+                    range: previous.document.line_index.range(name_span(previous.call)),
+                },
+                message: format!("Template {} is defined here", previous.name),
+            }]
+        });
+    Diagnostic {
+        range: document.line_index.range(span),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(DUPLICATE_TEMPLATE.to_string())),
+        message,
+        related_information,
+        ..Default::default()
+    }
+}
+
+pub fn collect_duplicate_templates(
+    analyzed_root: &AnalyzedBlock,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let document = analyzed_root.document;
+    let mut defined: HashMap<&str, Template> = HashMap::new();
+    // Only statements directly in the file are checked, since exclusive
+    // branches of conditions may define templates of the same name.
+    for statement in &analyzed_root.statements {
+        match statement {
+            AnalyzedStatement::Template(template) => {
+                let Some(template) = template.as_template(document) else {
+                    continue;
+                };
+                if let Some(previous) = defined.get(template.name) {
+                    diagnostics.push(duplicate_diagnostic(
+                        document,
+                        name_span(template.call),
+                        format!("Template {} is already defined", template.name),
+                        previous,
+                    ));
+                } else {
+                    defined.insert(template.name, template);
+                }
+            }
+            AnalyzedStatement::Import(import) => {
+                let mut imported: Vec<_> = import
+                    .file
+                    .environment
+                    .templates
+                    .all_items()
+                    .into_values()
+                    .collect();
+                imported.sort_by_key(|template| template.name);
+                for template in imported {
+                    match defined.get(template.name) {
+                        Some(previous) if !is_same_definition(previous, &template) => {
+                            diagnostics.push(duplicate_diagnostic(
+                                document,
+                                name_span(import.call),
+                                format!("Import redefines template {}", template.name),
+                                previous,
+                            ));
+                        }
+                        Some(_) => {}
+                        None => {
+                            defined.insert(template.name, template);
+                        }
+                    }
+                }
+            }
+            AnalyzedStatement::Assignment(_)
+            | AnalyzedStatement::Conditions(_)
+            | AnalyzedStatement::DeclareArgs(_)
+            | AnalyzedStatement::Foreach(_)
+            | AnalyzedStatement::ForwardVariablesFrom(_)
+            | AnalyzedStatement::SyntheticImport(_)
+            | AnalyzedStatement::Target(_)
+            | AnalyzedStatement::BuiltinCall(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::{
+        analyzer::Analyzer,
+        common::{storage::DocumentStorage, testutils::TestWorkspace, workspace::WorkspaceFinder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_duplicate_templates() {
+        let workspace = TestWorkspace::new([
+            ("foo.gni", "template(\"foo\") {\n}\n"),
+            ("bar.gni", "template(\"bar\") {\n}\n"),
+            ("other/bar.gni", "template(\"bar\") {\n}\n"),
+            (
+                "BUILD.gn",
+                r#"template("foo") {
+}
+
+template("foo") {
+}
+
+import("//bar.gni")
+import("//bar.gni")
+import("//other/bar.gni")
+"#,
+            ),
+        ]);
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let file = analyzer
+            .analyze(&workspace.path("BUILD.gn"), &finder, Instant::now())
+            .unwrap();
+
+        let mut diagnostics = Vec::new();
+        collect_duplicate_templates(&file.analyzed_root, &mut diagnostics);
+
+        // Importing the same file twice is fine.
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "Template foo is already defined");
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(3, 9), Position::new(3, 14))
+        );
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(
+            related[0].location.uri,
+            Url::from_file_path(workspace.path("BUILD.gn")).unwrap()
+        );
+        assert_eq!(
+            related[0].location.range,
+            Range::new(Position::new(0, 9), Position::new(0, 14))
+        );
+
+        assert_eq!(diagnostics[1].message, "Import redefines template bar");
+        assert_eq!(
+            diagnostics[1].range,
+            Range::new(Position::new(8, 7), Position::new(8, 24))
+        );
+        let related = diagnostics[1].related_information.as_ref().unwrap();
+        assert_eq!(
+            related[0].location.uri,
+            Url::from_file_path(workspace.path("bar.gni")).unwrap()
+        );
+    }
+}
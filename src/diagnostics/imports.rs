@@ -0,0 +1,134 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString};
+
+use crate::{
+    analyzer::{AnalyzedBlock, AnalyzedStatement, TopLevelStatementsExt},
+    parser::Node,
+};
+
+/// Diagnostic code of imports whose symbols are never referenced.
+pub const UNUSED_IMPORT: &str = "unused-import";
+
+pub fn collect_unused_imports(analyzed_root: &AnalyzedBlock, diagnostics: &mut Vec<Diagnostic>) {
+    // Symbols imported to a .gni file are visible to its importers too.
+    let document = analyzed_root.document;
+    if document
+        .path
+        .extension()
+        .is_some_and(|extension| extension == "gni")
+    {
+        return;
+    }
+
+    // Synthetic imports of BUILDCONFIG.gn are a separate statement kind, so
+    // they are never reported.
+    for statement in analyzed_root.top_level_statements() {
+        let AnalyzedStatement::Import(import) = statement else {
+            continue;
+        };
+        let environment = &import.file.environment;
+        // Importing a file defining targets instantiates them.
+        if !environment.targets.all_items().is_empty() {
+            continue;
+        }
+        let used = analyzed_root
+            .block
+            .identifiers()
+            .filter(|identifier| identifier.span.start() >= import.call.span.end())
+            .any(|identifier| {
+                environment.variables.contains(identifier.name)
+                    || environment.templates.contains(identifier.name)
+            });
+        if used {
+            continue;
+        }
+        let path = import
+            .call
+            .only_arg()
+            .map_or(import.call.function.name, |arg| arg.span().as_str());
+        diagnostics.push(Diagnostic {
+            range: document.line_index.range(import.call.span),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(UNUSED_IMPORT.to_string())),
+            message: format!("Nothing imported from {path} is used"),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::{
+        analyzer::Analyzer,
+        common::{storage::DocumentStorage, testutils::TestWorkspace, workspace::WorkspaceFinder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_unused_imports() {
+        let workspace = TestWorkspace::new([
+            ("BUILDCONFIG.gn", "is_linux = true\n"),
+            ("template.gni", "template(\"foo\") {\n}\n"),
+            ("variable.gni", "bar_enabled = true\n"),
+            ("unused.gni", "baz = 1\n"),
+            ("indirect.gni", "import(\"//template.gni\")\n"),
+            (
+                "BUILD.gn",
+                r#"import("//template.gni")
+import("//variable.gni")
+import("//unused.gni")
+import("//indirect.gni")
+
+foo("a") {
+  enabled = "$bar_enabled"
+}
+
+if (is_linux) {
+}
+"#,
+            ),
+        ]);
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let file = analyzer
+            .analyze(&workspace.path("BUILD.gn"), &finder, Instant::now())
+            .unwrap();
+
+        let mut diagnostics = Vec::new();
+        collect_unused_imports(&file.analyzed_root, &mut diagnostics);
+
+        // Templates imported transitively count as used.
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(2, 0), Position::new(2, 22))
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Nothing imported from \"//unused.gni\" is used"
+        );
+    }
+}
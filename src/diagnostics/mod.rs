@@ -12,29 +12,374 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tower_lsp::lsp_types::Diagnostic;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity};
 
 use crate::{
-    analyzer::AnalyzedBlock,
-    common::config::Configurations,
-    diagnostics::{syntax::collect_syntax_errors, undefined::collect_undefined_identifiers},
+    analyzer::{AnalyzedBlock, AnalyzedFile},
+    common::{
+        config::{Configurations, DiagnosticProfile, LintSeverity},
+        utils::{is_args_file, matches_pattern},
+    },
+    diagnostics::{
+        deprecated::collect_deprecated_templates,
+        duplicate::collect_duplicate_templates,
+        imports::collect_unused_imports,
+        reserved::collect_reserved_words,
+        sources::collect_missing_sources,
+        syntax::{collect_encoding_errors, collect_syntax_errors},
+        tabs::collect_tab_indentations,
+        undefined::collect_undefined_identifiers,
+    },
 };
 
+pub use tabs::{untabify_edits, NO_TABS};
+
+mod deprecated;
+mod duplicate;
+mod imports;
+mod reserved;
+mod sources;
 mod syntax;
+mod tabs;
 mod undefined;
 
+fn find_profile<'c>(config: &'c Configurations, path: &Path) -> Option<&'c DiagnosticProfile> {
+    let name = path.file_name()?.to_str()?;
+    config
+        .diagnostic_profiles
+        .iter()
+        .find(|profile| matches_pattern(&profile.pattern, name))
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum DiagnosticPass {
+    Syntax,
+    NoTabs,
+    DeprecatedTemplates,
+    DuplicateTemplates,
+    RequireSources,
+    ReservedWords,
+    UndefinedVariables,
+    UnusedImports,
+}
+
+impl DiagnosticPass {
+    /// Returns whether the pass depends only on the file content, not on
+    /// imported files.
+    fn is_local(self) -> bool {
+        match self {
+            DiagnosticPass::Syntax
+            | DiagnosticPass::NoTabs
+            | DiagnosticPass::RequireSources
+            | DiagnosticPass::ReservedWords => true,
+            DiagnosticPass::DeprecatedTemplates
+            | DiagnosticPass::DuplicateTemplates
+            | DiagnosticPass::UndefinedVariables
+            | DiagnosticPass::UnusedImports => false,
+        }
+    }
+
+    fn run(self, analyzed_root: &AnalyzedBlock) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        match self {
+            DiagnosticPass::Syntax => {
+                collect_encoding_errors(analyzed_root.document, &mut diagnostics);
+                collect_syntax_errors(
+                    analyzed_root.block,
+                    analyzed_root.document,
+                    &mut diagnostics,
+                );
+            }
+            DiagnosticPass::NoTabs => {
+                collect_tab_indentations(analyzed_root.document, &mut diagnostics)
+            }
+            DiagnosticPass::DeprecatedTemplates => {
+                collect_deprecated_templates(analyzed_root, &mut diagnostics)
+            }
+            DiagnosticPass::DuplicateTemplates => {
+                collect_duplicate_templates(analyzed_root, &mut diagnostics)
+            }
+            DiagnosticPass::RequireSources => collect_missing_sources(
+                analyzed_root.block,
+                analyzed_root.document,
+                &mut diagnostics,
+            ),
+            DiagnosticPass::ReservedWords => collect_reserved_words(
+                analyzed_root.block,
+                analyzed_root.document,
+                &mut diagnostics,
+            ),
+            DiagnosticPass::UndefinedVariables => {
+                collect_undefined_identifiers(analyzed_root, &mut diagnostics)
+            }
+            DiagnosticPass::UnusedImports => {
+                collect_unused_imports(analyzed_root, &mut diagnostics)
+            }
+        }
+        diagnostics
+    }
+}
+
+struct CachedPass {
+    file: Pin<Arc<AnalyzedFile>>,
+    diagnostics: Arc<Vec<Diagnostic>>,
+}
+
+/// Caches results of diagnostic passes per file, so that changing the
+/// configuration of one pass does not recompute the others.
+#[derive(Default)]
+pub struct DiagnosticsCache {
+    passes: HashMap<(PathBuf, DiagnosticPass), CachedPass>,
+}
+
+impl DiagnosticsCache {
+    /// Forgets the results for a file, e.g. when it is closed.
+    pub fn remove(&mut self, path: &Path) {
+        self.passes
+            .retain(|(cached_path, _), _| cached_path != path);
+    }
+
+    fn run(&mut self, file: &Pin<Arc<AnalyzedFile>>, pass: DiagnosticPass) -> Arc<Vec<Diagnostic>> {
+        let key = (file.document.path.clone(), pass);
+        if let Some(cached) = self.passes.get(&key) {
+            // Passes reading imported files are valid only for the same
+            // analysis, while others are valid for the same document version.
+            let fresh = if pass.is_local() {
+                cached.file.document.version == file.document.version
+            } else {
+                std::ptr::eq(&*cached.file, &**file)
+            };
+            if fresh {
+                return cached.diagnostics.clone();
+            }
+        }
+        let diagnostics = Arc::new(pass.run(&file.analyzed_root));
+        self.passes.insert(
+            key,
+            CachedPass {
+                file: file.clone(),
+                diagnostics: diagnostics.clone(),
+            },
+        );
+        diagnostics
+    }
+}
+
 pub fn compute_diagnostics(
-    analyzed_root: &AnalyzedBlock,
+    file: &Pin<Arc<AnalyzedFile>>,
     config: &Configurations,
+    cache: &mut DiagnosticsCache,
 ) -> Vec<Diagnostic> {
     let mut diagnostics = Vec::new();
-    collect_syntax_errors(
-        analyzed_root.block,
-        analyzed_root.document,
-        &mut diagnostics,
-    );
-    if config.experimental.undefined_variable_analysis {
-        collect_undefined_identifiers(analyzed_root, &mut diagnostics);
+    diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::Syntax));
+    // Redefined templates are likely errors, so they are reported regardless
+    // of the lint settings.
+    diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::DuplicateTemplates));
+
+    // A matching profile replaces the global lint settings.
+    let profile = find_profile(config, &file.document.path);
+    let (lints, undefined_variable_analysis, lint_severity) = match profile {
+        Some(profile) => (
+            &profile.lints,
+            profile.undefined_variable_analysis,
+            profile.lint_severity,
+        ),
+        None => (
+            &config.lints,
+            config.experimental.undefined_variable_analysis,
+            None,
+        ),
+    };
+
+    let mut lint_diagnostics = Vec::new();
+    if lints.no_tabs {
+        lint_diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::NoTabs));
+    }
+    if lints.deprecated_templates {
+        lint_diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::DeprecatedTemplates));
+    }
+    if lints.require_sources {
+        lint_diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::RequireSources));
+    }
+    if lints.reserved_words {
+        lint_diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::ReservedWords));
     }
+    if lints.unused_imports {
+        lint_diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::UnusedImports));
+    }
+    // Build argument files may refer to arguments declared anywhere in the
+    // build, so unknown identifiers are not undefined there.
+    let args_file = is_args_file(&config.workspace.args_file_patterns, &file.document.path);
+    if undefined_variable_analysis && !args_file {
+        lint_diagnostics.extend_from_slice(&cache.run(file, DiagnosticPass::UndefinedVariables));
+    }
+    if let Some(severity) = lint_severity {
+        let severity = match severity {
+            LintSeverity::Error => DiagnosticSeverity::ERROR,
+            LintSeverity::Warning => DiagnosticSeverity::WARNING,
+            LintSeverity::Information => DiagnosticSeverity::INFORMATION,
+            LintSeverity::Hint => DiagnosticSeverity::HINT,
+        };
+        for diagnostic in &mut lint_diagnostics {
+            diagnostic.severity = Some(severity);
+        }
+    }
+    diagnostics.extend(lint_diagnostics);
     diagnostics
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use crate::{
+        analyzer::Analyzer,
+        common::{
+            config::{ExperimentalConfigurations, LintConfigurations},
+            storage::DocumentStorage,
+            testutils::testdata,
+            workspace::WorkspaceFinder,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_diagnostic_profiles() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let config = Configurations {
+            diagnostic_profiles: vec![DiagnosticProfile {
+                pattern: "*.gni".to_string(),
+                lints: LintConfigurations {
+                    no_tabs: true,
+                    ..Default::default()
+                },
+                undefined_variable_analysis: false,
+                lint_severity: Some(LintSeverity::Error),
+            }],
+            ..Default::default()
+        };
+
+        let compute = |name: &str| {
+            let path = testdata("workspaces/smoke").join(name);
+            storage
+                .lock()
+                .unwrap()
+                .load_to_memory(&path, "if (true) {\n\ta = 1\n}\n", 1);
+            let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+            compute_diagnostics(&file, &config, &mut DiagnosticsCache::default())
+                .into_iter()
+                .map(|diagnostic| diagnostic.severity)
+                .collect::<Vec<_>>()
+        };
+
+        // The stricter profile applies only to .gni files.
+        assert_eq!(compute("profile.gni"), [Some(DiagnosticSeverity::ERROR)]);
+        assert!(compute("BUILD.gn").is_empty());
+    }
+
+    #[test]
+    fn test_args_file() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let config = Configurations {
+            experimental: ExperimentalConfigurations {
+                undefined_variable_analysis: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let data = "is_debug = false\nuse_foo = enable_bar\n";
+
+        // args.gn outside any workspace is analyzed on its own.
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("out/Default/args.gn");
+        storage.lock().unwrap().load_to_memory(&path, data, 1);
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        assert!(compute_diagnostics(&file, &config, &mut DiagnosticsCache::default()).is_empty());
+
+        // The same references are undefined in ordinary files.
+        let path = testdata("workspaces/smoke/overrides.gn");
+        storage.lock().unwrap().load_to_memory(&path, data, 1);
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        assert!(!compute_diagnostics(&file, &config, &mut DiagnosticsCache::default()).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+        std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+        let path = root.join("BUILD.gn");
+        std::fs::write(&path, b"a = \"\xff\"\n").unwrap();
+
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+
+        // The file is still analyzed, with the encoding error reported.
+        assert!(file
+            .variables_at(file.document.data.len())
+            .get("a")
+            .is_some());
+        let diagnostics =
+            compute_diagnostics(&file, &Configurations::default(), &mut Default::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].message.contains("UTF-8"));
+    }
+
+    #[test]
+    fn test_diagnostics_cache() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let path = testdata("workspaces/smoke/cache.gn");
+        storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "a = [1, 2 3]\nb = c\n", 1);
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+
+        let mut cache = DiagnosticsCache::default();
+        let mut config = Configurations::default();
+        let syntax_diagnostics = |cache: &DiagnosticsCache| {
+            cache.passes[&(path.clone(), DiagnosticPass::Syntax)]
+                .diagnostics
+                .clone()
+        };
+
+        let count = compute_diagnostics(&file, &config, &mut cache).len();
+        let cached = syntax_diagnostics(&cache);
+        assert!(count > 0);
+
+        // Toggling undefined variable analysis adds its results, reusing the
+        // syntax errors computed before.
+        config.experimental.undefined_variable_analysis = true;
+        assert!(compute_diagnostics(&file, &config, &mut cache).len() > count);
+        assert!(Arc::ptr_eq(&syntax_diagnostics(&cache), &cached));
+
+        // A new version invalidates the results.
+        storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "a = [1, 2 3]\nb = c\n", 2);
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        compute_diagnostics(&file, &config, &mut cache);
+        assert!(!Arc::ptr_eq(&syntax_diagnostics(&cache), &cached));
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::{
+    common::storage::Document,
+    parser::{Block, LValue, Node, Statement},
+};
+
+/// Diagnostic code of assignments to reserved words.
+pub const RESERVED_WORDS: &str = "reserved-words";
+
+/// Words GN tokenizes as keywords, which the parser here accepts as
+/// identifiers.
+const KEYWORDS: &[&str] = &["if", "else", "true", "false"];
+
+pub fn collect_reserved_words(ast: &Block, document: &Document, diagnostics: &mut Vec<Diagnostic>) {
+    for statement in ast.walk().filter_map(|node| node.as_statement()) {
+        let Statement::Assignment(assignment) = statement else {
+            continue;
+        };
+        let identifier = match &assignment.lvalue {
+            LValue::Identifier(identifier) => identifier.as_ref(),
+            LValue::ArrayAccess(array_access) => &array_access.array,
+            LValue::ScopeAccess(scope_access) => &scope_access.scope,
+        };
+        if !KEYWORDS.contains(&identifier.name) {
+            continue;
+        }
+        diagnostics.push(Diagnostic {
+            range: document.line_index.range(identifier.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(RESERVED_WORDS.to_string())),
+            message: format!(
+                "`{}` is a reserved word and cannot be used as a variable name",
+                identifier.name
+            ),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::{common::storage::DocumentVersion, parser::parse};
+
+    use super::*;
+
+    #[test]
+    fn test_reserved_words() {
+        let document = Document::new(
+            Path::new("/BUILD.gn"),
+            r#"true = 1
+truthy = true
+group("foo") {
+  else = [ "bar" ]
+}
+"#
+            .to_string(),
+            DocumentVersion::InMemory { revision: 1 },
+        );
+        let ast = parse(&document.data);
+        let mut diagnostics = Vec::new();
+        collect_reserved_words(&ast, &document, &mut diagnostics);
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.range, diagnostic.message.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Range::new(Position::new(0, 0), Position::new(0, 4)),
+                    "`true` is a reserved word and cannot be used as a variable name"
+                ),
+                (
+                    Range::new(Position::new(3, 2), Position::new(3, 6)),
+                    "`else` is a reserved word and cannot be used as a variable name"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pest::Span;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+
+use crate::{
+    common::{
+        builtins::{FORWARD_VARIABLES_FROM, SOURCE_TARGETS},
+        storage::Document,
+    },
+    parser::{Block, LValue, Node, Statement},
+};
+
+/// Diagnostic code of targets without sources or dependencies.
+pub const REQUIRE_SOURCES: &str = "require-sources";
+
+/// Variables any of which gives a target something to build.
+const INPUT_VARIABLES: &[&str] = &["sources", "deps", "public_deps"];
+
+/// Returns whether a target body assigns or appends to an input variable
+/// anywhere, including in conditions, or may forward one from the invoker.
+fn declares_inputs(block: &Block) -> bool {
+    block
+        .walk()
+        .filter_map(|node| node.as_statement())
+        .any(|statement| match statement {
+            Statement::Assignment(assignment) => matches!(
+                &assignment.lvalue,
+                LValue::Identifier(identifier) if INPUT_VARIABLES.contains(&identifier.name)
+            ),
+            Statement::Call(call) => call.function.name == FORWARD_VARIABLES_FROM,
+            Statement::Condition(_) | Statement::Error(_) => false,
+        })
+}
+
+pub fn collect_missing_sources(
+    ast: &Block,
+    document: &Document,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let data: &str = &document.data;
+    for call in ast.walk().filter_map(|node| node.as_call()) {
+        if !SOURCE_TARGETS.contains(&call.function.name) {
+            continue;
+        }
+        let Some(block) = &call.block else {
+            continue;
+        };
+        if declares_inputs(block) {
+            continue;
+        }
+        // Report on the header, e.g. `executable("foo")`.
+        let header_end = data[..block.span.start()].trim_end().len();
+        let header = Span::new(data, call.span.start(), header_end).unwrap();
+        diagnostics.push(Diagnostic {
+            range: document.line_index.range(header),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(REQUIRE_SOURCES.to_string())),
+            message: format!("{} has no sources or deps", call.function.name),
+            ..Default::default()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use crate::{common::storage::DocumentVersion, parser::parse};
+
+    use super::*;
+
+    #[test]
+    fn test_missing_sources() {
+        let document = Document::new(
+            Path::new("/BUILD.gn"),
+            r#"executable("empty") {
+  output_name = "foo"
+}
+
+executable("conditional") {
+  if (is_win) {
+    sources += [ "win.cc" ]
+  }
+}
+
+executable("deps_only") {
+  deps = [ ":empty" ]
+}
+
+executable("forwarded") {
+  forward_variables_from(invoker, "*")
+}
+
+group("group") {
+}
+"#
+            .to_string(),
+            DocumentVersion::InMemory { revision: 1 },
+        );
+        let ast = parse(&document.data);
+        let mut diagnostics = Vec::new();
+        collect_missing_sources(&ast, &document, &mut diagnostics);
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 0), Position::new(0, 19))
+        );
+        assert_eq!(diagnostics[0].message, "executable has no sources or deps");
+    }
+}
@@ -27,3 +27,14 @@ pub fn collect_syntax_errors(ast: &Block, document: &Document, diagnostics: &mut
         ..Default::default()
     }));
 }
+
+pub fn collect_encoding_errors(document: &Document, diagnostics: &mut Vec<Diagnostic>) {
+    if document.invalid_utf8 {
+        diagnostics.push(Diagnostic {
+            range: Default::default(),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: "File is not valid UTF-8; invalid bytes are replaced with U+FFFD".to_string(),
+            ..Default::default()
+        });
+    }
+}
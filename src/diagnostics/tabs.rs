@@ -0,0 +1,95 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pest::Span;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range, TextEdit};
+
+use crate::common::storage::Document;
+
+/// Diagnostic code of tab indentations.
+pub const NO_TABS: &str = "no-tabs";
+
+/// Number of spaces replacing a tab, matching `gn format` indentation.
+const TAB_WIDTH: usize = 2;
+
+/// Returns the leading whitespace of lines indented with tabs.
+fn tab_indentations(data: &str) -> impl Iterator<Item = Span<'_>> {
+    let mut offset = 0;
+    data.split_inclusive('\n').filter_map(move |line| {
+        let start = offset;
+        offset += line.len();
+        let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+        line[..indent]
+            .contains('\t')
+            .then(|| Span::new(data, start, start + indent).unwrap())
+    })
+}
+
+pub fn collect_tab_indentations(document: &Document, diagnostics: &mut Vec<Diagnostic>) {
+    diagnostics.extend(tab_indentations(&document.data).map(|span| Diagnostic {
+        range: document.line_index.range(span),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(NO_TABS.to_string())),
+        message: "Indentation contains tabs; use spaces instead".to_string(),
+        ..Default::default()
+    }));
+}
+
+/// Returns edits converting tab indentations overlapping `range` to spaces.
+pub fn untabify_edits(document: &Document, range: Range) -> Vec<TextEdit> {
+    tab_indentations(&document.data)
+        .map(|span| TextEdit {
+            range: document.line_index.range(span),
+            new_text: span.as_str().replace('\t', &" ".repeat(TAB_WIDTH)),
+        })
+        .filter(|edit| {
+            range.start.line <= edit.range.start.line && edit.range.start.line <= range.end.line
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tower_lsp::lsp_types::Position;
+
+    use crate::common::storage::DocumentVersion;
+
+    use super::*;
+
+    #[test]
+    fn test_tab_indentations() {
+        let document = Document::new(
+            Path::new("/BUILD.gn"),
+            "group(\"foo\") {\n\t deps = []\n  data = [ \"\t\" ]\n}\n".to_string(),
+            DocumentVersion::InMemory { revision: 1 },
+        );
+
+        let mut diagnostics = Vec::new();
+        collect_tab_indentations(&document, &mut diagnostics);
+        let tab_range = Range::new(Position::new(1, 0), Position::new(1, 2));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, tab_range);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+
+        assert_eq!(
+            untabify_edits(&document, tab_range),
+            vec![TextEdit {
+                range: tab_range,
+                new_text: "   ".to_string(),
+            }]
+        );
+    }
+}
@@ -23,6 +23,9 @@ use crate::{
     parser::{Expr, Identifier, LValue, PrimaryExpr},
 };
 
+const TARGET_NAME: &str = "target_name";
+const INVOKER: &str = "invoker";
+
 fn builtin_scope() -> &'static Arc<VariableScope<'static, 'static>> {
     static SCOPE: OnceLock<Arc<VariableScope<'static, 'static>>> = OnceLock::new();
     SCOPE.get_or_init(|| {
@@ -123,6 +126,8 @@ impl<'i> PrimaryExpr<'i> {
                     .collect_undefined_identifiers(document, tracker, diagnostics);
             }
             PrimaryExpr::ScopeAccess(scope_access) => {
+                // The member is a field of the scope, not a free variable.
+                // Scope members are not tracked, so it is never reported.
                 scope_access
                     .scope
                     .collect_undefined_identifiers(document, tracker, diagnostics);
@@ -250,9 +255,24 @@ impl<'i, 'p> AnalyzedBlock<'i, 'p> {
             }
 
             // Collect undefined identifiers in subscopes.
-            for subscope in statement.subscopes() {
+            for subscope in statement.expr_scopes() {
                 subscope.collect_undefined_identifiers(&mut tracker.clone(), diagnostics);
             }
+            if let Some(body_scope) = statement.body_scope() {
+                // Target and template bodies define implicit variables.
+                let mut body_tracker = tracker.clone();
+                match statement {
+                    AnalyzedStatement::Target(_) => {
+                        body_tracker.insert(TARGET_NAME);
+                    }
+                    AnalyzedStatement::Template(_) => {
+                        body_tracker.insert(TARGET_NAME);
+                        body_tracker.insert(INVOKER);
+                    }
+                    _ => {}
+                }
+                body_scope.collect_undefined_identifiers(&mut body_tracker, diagnostics);
+            }
 
             // Update variables.
             match statement {
@@ -296,3 +316,72 @@ pub fn collect_undefined_identifiers<'i, 'p>(
 ) {
     block.collect_undefined_identifiers(&mut VariablesTracker::new(), diagnostics);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Instant,
+    };
+
+    use crate::{
+        analyzer::Analyzer,
+        common::{storage::DocumentStorage, testutils::testdata, workspace::WorkspaceFinder},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_implicit_body_variables() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let path = testdata("workspaces/smoke/implicit.gn");
+        storage.lock().unwrap().load_to_memory(
+            &path,
+            r#"template("foo") {
+  print(target_name)
+  print(invoker.sources)
+}
+
+group("bar") {
+  print(target_name)
+}
+"#,
+            1,
+        );
+
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        let mut diagnostics = Vec::new();
+        collect_undefined_identifiers(&file.analyzed_root, &mut diagnostics);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_scope_access_member() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Analyzer::new(&storage);
+        let finder = WorkspaceFinder::new(None);
+        let path = testdata("workspaces/smoke/scope_access.gn");
+        storage.lock().unwrap().load_to_memory(
+            &path,
+            r#"a = {
+  c = 1
+}
+print(a.b)
+print(undefined_scope.b)
+"#,
+            1,
+        );
+
+        let file = analyzer.analyze(&path, &finder, Instant::now()).unwrap();
+        let mut diagnostics = Vec::new();
+        collect_undefined_identifiers(&file.analyzed_root, &mut diagnostics);
+        // Only the undefined scope is reported, never its members.
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(messages, ["undefined_scope not defined"]);
+    }
+}
@@ -12,12 +12,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::bench::run_bench;
+use tower_lsp::lsp_types::MessageType;
+
+use crate::{
+    bench::{run_bench, BenchMode},
+    check::run_check_links,
+    common::client::parse_log_level,
+};
 
 mod analyzer;
 mod bench;
+mod check;
 mod common;
 mod diagnostics;
 mod parser;
@@ -26,8 +33,47 @@ mod server;
 #[tokio::main]
 async fn main() {
     if let Ok(path) = std::env::var("GN_BENCH") {
-        run_bench(Path::new(&path)).await;
+        let mode_name = std::env::var("GN_BENCH_MODE").unwrap_or_else(|_| "analyze".to_string());
+        let Some(mode) = BenchMode::from_name(&mode_name) else {
+            eprintln!(
+                "Unknown benchmark mode: {mode_name}; \
+                 expected analyze, parse, expressions or latency"
+            );
+            std::process::exit(2);
+        };
+        run_bench(Path::new(&path), mode).await;
+        return;
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "--check-links") {
+        const LABEL_CHECK_ROOT: &str = "--label-check-root=";
+        let label_check_roots: Vec<String> = args[1..]
+            .iter()
+            .filter_map(|arg| arg.strip_prefix(LABEL_CHECK_ROOT))
+            .map(str::to_string)
+            .collect();
+        let paths: Vec<PathBuf> = args[1..]
+            .iter()
+            .filter(|arg| !arg.starts_with(LABEL_CHECK_ROOT))
+            .map(PathBuf::from)
+            .collect();
+        if run_check_links(&paths, &label_check_roots) > 0 {
+            std::process::exit(1);
+        }
         return;
     }
-    server::run().await;
+
+    let mut log_level = MessageType::LOG;
+    for arg in &args {
+        if arg == "--quiet" {
+            log_level = MessageType::WARNING;
+        } else if let Some(name) = arg.strip_prefix("--log-level=") {
+            let Some(level) = parse_log_level(name) else {
+                eprintln!("Unknown log level: {name}; expected error, warning, info or log");
+                std::process::exit(2);
+            };
+            log_level = level;
+        }
+    }
+    server::run(log_level).await;
 }
@@ -29,6 +29,18 @@ pub trait Node<'i> {
         None
     }
 
+    fn as_call(&self) -> Option<&Call<'i>> {
+        None
+    }
+
+    fn as_block(&self) -> Option<&Block<'i>> {
+        None
+    }
+
+    fn as_list(&self) -> Option<&ListLiteral<'i>> {
+        None
+    }
+
     fn as_identifier(&self) -> Option<&Identifier<'i>> {
         None
     }
@@ -37,6 +49,10 @@ pub trait Node<'i> {
         None
     }
 
+    fn as_scope_access(&self) -> Option<&ScopeAccess<'i>> {
+        None
+    }
+
     fn as_error<'n>(&'n self) -> Option<ErrorRef<'i, 'n>> {
         None
     }
@@ -223,12 +239,19 @@ impl<'i> Node<'i> for Call<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_call(&self) -> Option<&Call<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Condition<'i> {
+    pub if_keyword: Span<'i>,
     pub condition: Box<Expr<'i>>,
     pub then_block: Block<'i>,
+    /// Span of the `else` keyword. Present iff `else_block` is present.
+    pub else_keyword: Option<Span<'i>>,
     pub else_block: Option<Either<Box<Condition<'i>>, Box<Block<'i>>>>,
     pub span: Span<'i>,
 }
@@ -274,6 +297,10 @@ impl<'i> Node<'i> for Block<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_block(&self) -> Option<&Block<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -316,6 +343,10 @@ impl<'i> Node<'i> for ScopeAccess<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_scope_access(&self) -> Option<&ScopeAccess<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -634,6 +665,10 @@ impl<'i> Node<'i> for ListLiteral<'i> {
     fn span(&self) -> Span<'i> {
         self.span
     }
+
+    fn as_list(&self) -> Option<&ListLiteral<'i>> {
+        Some(self)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -771,12 +806,14 @@ impl<'i> Node<'i> for UnmatchedBrace<'i> {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ErrorPrimaryExpr<'i> {
     MissingComma(Box<MissingComma<'i>>),
+    IntegerOverflow(Box<IntegerOverflow<'i>>),
 }
 
 impl ErrorPrimaryExpr<'_> {
     pub fn diagnosis(&self) -> &'static str {
         match self {
             ErrorPrimaryExpr::MissingComma(missing_comma) => missing_comma.diagnosis(),
+            ErrorPrimaryExpr::IntegerOverflow(overflow) => overflow.diagnosis(),
         }
     }
 }
@@ -793,12 +830,14 @@ impl<'i> Node<'i> for ErrorPrimaryExpr<'i> {
     fn children(&self) -> Vec<&dyn Node<'i>> {
         match self {
             ErrorPrimaryExpr::MissingComma(missing_comma) => missing_comma.children(),
+            ErrorPrimaryExpr::IntegerOverflow(overflow) => overflow.children(),
         }
     }
 
     fn span(&self) -> Span<'i> {
         match self {
             ErrorPrimaryExpr::MissingComma(missing_comma) => missing_comma.span,
+            ErrorPrimaryExpr::IntegerOverflow(overflow) => overflow.span,
         }
     }
 }
@@ -828,20 +867,55 @@ impl<'i> Node<'i> for MissingComma<'i> {
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntegerOverflow<'i> {
+    pub span: Span<'i>,
+}
+
+impl IntegerOverflow<'_> {
+    pub fn diagnosis(&self) -> &'static str {
+        "Integer literal out of range"
+    }
+}
+
+impl<'i> Node<'i> for IntegerOverflow<'i> {
+    fn as_node(&self) -> &dyn Node<'i> {
+        self
+    }
+
+    fn children(&self) -> Vec<&dyn Node<'i>> {
+        Vec::new()
+    }
+
+    fn span(&self) -> Span<'i> {
+        self.span
+    }
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Comments<'i> {
+    /// Comment lines preceding the statement.
     pub lines: Vec<&'i str>,
+    /// Comment following the statement on its last line.
+    pub trailing: Option<&'i str>,
 }
 
 impl Comments<'_> {
     pub fn is_empty(&self) -> bool {
-        self.lines.is_empty()
+        self.lines.is_empty() && self.trailing.is_none()
+    }
+
+    /// Returns whether a line starts with a `DEPRECATED` marker.
+    pub fn is_deprecated(&self) -> bool {
+        self.lines
+            .iter()
+            .any(|line| line.trim_start().starts_with("DEPRECATED"))
     }
 }
 
 impl std::fmt::Display for Comments<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for line in &self.lines {
+        for line in self.lines.iter().chain(&self.trailing) {
             writeln!(f, "{line}")?;
         }
         Ok(())
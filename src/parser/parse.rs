@@ -12,9 +12,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::OnceLock;
+
 use itertools::Itertools;
 use pest::{
-    iterators::Pair,
+    iterators::{Pair, Pairs},
     pratt_parser::{Assoc, Op, PrattParser},
     Parser,
 };
@@ -49,11 +51,14 @@ fn convert_identifier(pair: Pair<Rule>) -> Identifier {
     }
 }
 
-fn convert_integer(pair: Pair<Rule>) -> IntegerLiteral {
+/// Converts an integer literal, including its optional minus sign. Literals
+/// out of the range of `i64` are errors.
+fn convert_integer(pair: Pair<Rule>) -> Result<IntegerLiteral, IntegerOverflow> {
     assert!(matches!(pair.as_rule(), Rule::integer));
-    IntegerLiteral {
-        value: pair.as_str().parse().unwrap(),
-        span: pair.as_span(),
+    let span = pair.as_span();
+    match pair.as_str().parse() {
+        Ok(value) => Ok(IntegerLiteral { value, span }),
+        Err(_) => Err(IntegerOverflow { span }),
     }
 }
 
@@ -124,33 +129,19 @@ fn convert_scope_access(pair: Pair<Rule>) -> ScopeAccess {
 fn convert_block(pair: Pair<Rule>) -> Block {
     assert!(matches!(pair.as_rule(), Rule::block));
     let span = pair.as_span();
-    let mut comments = Comments::default();
-    let statements = pair
-        .into_inner()
-        .filter_map(|pair| match pair.as_rule() {
-            Rule::statement => Some(convert_statement(pair, std::mem::take(&mut comments))),
-            Rule::error => Some(Statement::Error(Box::new(
-                ErrorStatement::UnknownStatement(Box::new(UnknownStatement {
-                    text: pair.as_str(),
-                    span: pair.as_span(),
-                })),
-            ))),
-            Rule::comment => {
-                comments
-                    .lines
-                    .push(pair.into_inner().exactly_one().unwrap().as_str());
-                None
-            }
-            _ => unreachable!(),
-        })
-        .collect();
+    let statements = convert_statements(pair.into_inner());
     Block { statements, span }
 }
 
 fn convert_primary(pair: Pair<Rule>) -> PrimaryExpr {
     match pair.as_rule() {
         Rule::identifier => PrimaryExpr::Identifier(Box::new(convert_identifier(pair))),
-        Rule::integer => PrimaryExpr::Integer(Box::new(convert_integer(pair))),
+        Rule::integer => match convert_integer(pair) {
+            Ok(integer) => PrimaryExpr::Integer(Box::new(integer)),
+            Err(overflow) => PrimaryExpr::Error(Box::new(ErrorPrimaryExpr::IntegerOverflow(
+                Box::new(overflow),
+            ))),
+        },
         Rule::string => PrimaryExpr::String(Box::new(convert_string(pair))),
         Rule::call => PrimaryExpr::Call(Box::new(convert_call(pair, Comments::default()))),
         Rule::array_access => PrimaryExpr::ArrayAccess(Box::new(convert_array_access(pair))),
@@ -162,22 +153,29 @@ fn convert_primary(pair: Pair<Rule>) -> PrimaryExpr {
     }
 }
 
+/// Returns the operator table, built once as expressions are parsed many
+/// times per file.
+fn pratt_parser() -> &'static PrattParser<Rule> {
+    static PRATT_PARSER: OnceLock<PrattParser<Rule>> = OnceLock::new();
+    PRATT_PARSER.get_or_init(|| {
+        PrattParser::new()
+            .op(Op::prefix(Rule::not))
+            .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+            .op(Op::infix(Rule::ge, Assoc::Left)
+                | Op::infix(Rule::gt, Assoc::Left)
+                | Op::infix(Rule::le, Assoc::Left)
+                | Op::infix(Rule::lt, Assoc::Left))
+            .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
+            .op(Op::infix(Rule::and, Assoc::Left))
+            .op(Op::infix(Rule::or, Assoc::Left))
+    })
+}
+
 fn convert_expr(pair: Pair<Rule>) -> Expr {
     assert!(matches!(pair.as_rule(), Rule::expr));
     let pairs = pair.into_inner();
 
-    // TODO: Cache PrattParser
-    let pratt_parser = PrattParser::new()
-        .op(Op::prefix(Rule::not))
-        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
-        .op(Op::infix(Rule::ge, Assoc::Left)
-            | Op::infix(Rule::gt, Assoc::Left)
-            | Op::infix(Rule::le, Assoc::Left)
-            | Op::infix(Rule::lt, Assoc::Left))
-        .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
-        .op(Op::infix(Rule::and, Assoc::Left))
-        .op(Op::infix(Rule::or, Assoc::Left));
-    pratt_parser
+    pratt_parser()
         .map_primary(|pair| Expr::Primary(Box::new(convert_primary(pair))))
         .map_prefix(|op, rhs| {
             let span = Span::new(
@@ -297,8 +295,14 @@ fn convert_condition(pair: Pair<Rule>) -> Condition {
     let span = pair.as_span();
     let mut pairs = pair.into_inner();
 
+    let if_keyword = pairs.next().unwrap();
+    assert!(matches!(if_keyword.as_rule(), Rule::if_keyword));
     let condition = convert_expr(pairs.next().unwrap());
     let then_block = convert_block(pairs.next().unwrap());
+    let else_keyword = pairs.next().map(|pair| {
+        assert!(matches!(pair.as_rule(), Rule::else_keyword));
+        pair.as_span()
+    });
     let else_block = match pairs.next() {
         Some(pair) if matches!(pair.as_rule(), Rule::condition) => {
             Some(Either::Left(Box::new(convert_condition(pair))))
@@ -311,8 +315,10 @@ fn convert_condition(pair: Pair<Rule>) -> Condition {
     };
 
     Condition {
+        if_keyword: if_keyword.as_span(),
         condition: Box::new(condition),
         then_block,
+        else_keyword,
         else_block,
         span,
     }
@@ -329,35 +335,57 @@ fn convert_statement<'i>(pair: Pair<'i, Rule>, comments: Comments<'i>) -> Statem
     }
 }
 
-fn convert_file(pair: Pair<Rule>) -> Block {
-    assert!(matches!(pair.as_rule(), Rule::file));
-    let span = pair.as_span();
+/// Converts the statements of a file or a block. Comments on their own lines
+/// are attached to the next statement, and a comment following a statement on
+/// the same line is attached to it as a trailing comment.
+fn convert_statements(pairs: Pairs<Rule>) -> Vec<Statement> {
+    let mut statements: Vec<Statement> = Vec::new();
     let mut comments = Comments::default();
-    let statements = pair
-        .into_inner()
-        .filter_map(|pair| match pair.as_rule() {
-            Rule::statement => Some(convert_statement(pair, std::mem::take(&mut comments))),
-            Rule::error => Some(Statement::Error(Box::new(
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::statement => {
+                statements.push(convert_statement(pair, std::mem::take(&mut comments)))
+            }
+            Rule::error => statements.push(Statement::Error(Box::new(
                 ErrorStatement::UnknownStatement(Box::new(UnknownStatement {
                     text: pair.as_str(),
                     span: pair.as_span(),
                 })),
             ))),
-            Rule::unmatched_brace => Some(Statement::Error(Box::new(
+            Rule::unmatched_brace => statements.push(Statement::Error(Box::new(
                 ErrorStatement::UnmatchedBrace(Box::new(UnmatchedBrace {
                     span: pair.as_span(),
                 })),
             ))),
             Rule::comment => {
-                comments
-                    .lines
-                    .push(pair.into_inner().exactly_one().unwrap().as_str());
-                None
+                // A comment on the line of the last token of the previous
+                // statement trails it. Statements end at their last token, so
+                // check for a line break in between.
+                let input = pair.get_input();
+                let start = pair.as_span().start();
+                let text = pair.into_inner().exactly_one().unwrap().as_str();
+                match statements.last_mut() {
+                    Some(last) if !input[last.span().end()..start].contains('\n') => match last {
+                        Statement::Assignment(assignment) => {
+                            assignment.comments.trailing = Some(text)
+                        }
+                        Statement::Call(call) => call.comments.trailing = Some(text),
+                        Statement::Condition(_) | Statement::Error(_) => {}
+                    },
+                    _ => comments.lines.push(text),
+                }
             }
-            Rule::EOI => None,
+            Rule::EOI => {}
             _ => unreachable!(),
-        })
-        .collect();
+        }
+    }
+    statements
+}
+
+fn convert_file(pair: Pair<Rule>) -> Block {
+    assert!(matches!(pair.as_rule(), Rule::file));
+    let span = pair.as_span();
+    let statements = convert_statements(pair.into_inner());
     Block { statements, span }
 }
 
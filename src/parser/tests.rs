@@ -14,7 +14,12 @@
 
 #![cfg(test)]
 
-use super::{parse, Node};
+use either::Either;
+use tower_lsp::lsp_types::Position;
+
+use crate::common::utils::LineIndex;
+
+use super::{parse, Expr, LValue, Node, PrimaryExpr, Statement};
 
 fn parse_no_errors(input: &str) {
     let block = parse(input);
@@ -103,3 +108,146 @@ fn missing_comma() {
         .collect();
     assert_eq!(errors, [(9, 9)]);
 }
+
+#[test]
+fn no_line_continuation() {
+    // A trailing backslash in a string does not continue it to the next line.
+    let input = "a = \"foo\\\nb = 1\n";
+    let block = parse(input);
+    let line_index = LineIndex::new(input);
+    let Some(Statement::Assignment(assignment)) = block.statements.last() else {
+        panic!("unexpected statements: {:?}", block.statements);
+    };
+    assert!(matches!(&assignment.lvalue, LValue::Identifier(identifier) if identifier.name == "b"));
+    assert_eq!(
+        line_index.position(assignment.span.start()),
+        Position::new(1, 0)
+    );
+    assert_eq!(
+        line_index.position(assignment.span.end()),
+        Position::new(1, 5)
+    );
+
+    // A stray backslash outside strings is an error confined to its line.
+    let input = "a = 1 \\\nb = 2\n";
+    let block = parse(input);
+    let line_index = LineIndex::new(input);
+    let errors: Vec<_> = block
+        .errors()
+        .map(|error| line_index.range(error.span()).start)
+        .collect();
+    assert_eq!(errors, [Position::new(0, 6)]);
+    let Some(Statement::Assignment(assignment)) = block.statements.last() else {
+        panic!("unexpected statements: {:?}", block.statements);
+    };
+    assert_eq!(
+        line_index.position(assignment.span.start()),
+        Position::new(1, 0)
+    );
+}
+
+#[test]
+fn condition_keywords() {
+    let input = "if (a) {\n} else  if (b) {\n} # comment\nelse {\n}\n";
+    let block = parse(input);
+    let Some(Statement::Condition(condition)) = block.statements.first() else {
+        panic!("unexpected statements: {:?}", block.statements);
+    };
+    assert_eq!(condition.if_keyword.as_str(), "if");
+    assert_eq!(condition.if_keyword.start(), 0);
+    let else_keyword = condition.else_keyword.unwrap();
+    assert_eq!(else_keyword.as_str(), "else");
+    assert_eq!(else_keyword.start(), 11);
+
+    let Some(Either::Left(else_condition)) = &condition.else_block else {
+        panic!("unexpected else block: {:?}", condition.else_block);
+    };
+    assert_eq!(else_condition.if_keyword.as_str(), "if");
+    assert_eq!(else_condition.if_keyword.start(), 17);
+    let else_keyword = else_condition.else_keyword.unwrap();
+    assert_eq!(else_keyword.as_str(), "else");
+    assert_eq!(else_keyword.start(), 38);
+    assert!(matches!(else_condition.else_block, Some(Either::Right(_))));
+
+    let input = "if (a) {\n}\n";
+    let block = parse(input);
+    let Some(Statement::Condition(condition)) = block.statements.first() else {
+        panic!("unexpected statements: {:?}", block.statements);
+    };
+    assert!(condition.else_keyword.is_none());
+}
+
+#[test]
+fn integers() {
+    let parse_integer = |input: &str| {
+        let block = parse(input);
+        let Some(Statement::Assignment(assignment)) = block.statements.first() else {
+            panic!("unexpected statements: {:?}", block.statements);
+        };
+        match assignment.rvalue.as_ref() {
+            Expr::Primary(primary) => match primary.as_ref() {
+                PrimaryExpr::Integer(integer) => Some(integer.value),
+                PrimaryExpr::Error(_) => None,
+                _ => panic!("unexpected primary: {primary:?}"),
+            },
+            rvalue => panic!("unexpected rvalue: {rvalue:?}"),
+        }
+    };
+
+    assert_eq!(parse_integer("a = 0"), Some(0));
+    assert_eq!(parse_integer("a = -1"), Some(-1));
+    assert_eq!(parse_integer("a = 9223372036854775807"), Some(i64::MAX));
+    assert_eq!(parse_integer("a = -9223372036854775808"), Some(i64::MIN));
+
+    // Out of range literals are errors rather than panics.
+    assert_eq!(parse_integer("a = 9223372036854775808"), None);
+    assert_eq!(parse_integer("a = -9223372036854775809"), None);
+    let block = parse("a = [1, 99999999999999999999]");
+    let errors: Vec<_> = block
+        .errors()
+        .map(|e| (e.diagnosis(), e.span().start(), e.span().end()))
+        .collect();
+    assert_eq!(errors, [("Integer literal out of range", 8, 28)]);
+
+    // A minus sign after an operand is subtraction.
+    parse_no_errors("a = b -1");
+    parse_no_errors("a = [-1, -2]");
+}
+
+#[test]
+fn trailing_comments() {
+    let input = "a = 1  # note\n# doc\nb = 2\n\
+                 foo() {\n  c = 3 # inner\n} # end\n\
+                 if (x) {\n} # dropped\nd = 4\n\
+                 if (y) {\n}\n# after\ne = 5\n";
+    let block = parse(input);
+    let comments: Vec<_> = block
+        .statements
+        .iter()
+        .filter_map(|statement| match statement {
+            Statement::Assignment(assignment) => Some(&assignment.comments),
+            Statement::Call(call) => Some(&call.comments),
+            _ => None,
+        })
+        .map(|comments| (comments.lines.clone(), comments.trailing))
+        .collect();
+    assert_eq!(
+        comments,
+        [
+            (vec![], Some("note")),
+            (vec!["doc"], None),
+            (vec![], Some("end")),
+            (vec![], None),
+            (vec!["after"], None),
+        ]
+    );
+
+    let Some(Statement::Call(call)) = block.statements.get(2) else {
+        panic!("unexpected statements: {:?}", block.statements);
+    };
+    let Some(Statement::Assignment(assignment)) = call.block.as_ref().unwrap().statements.first()
+    else {
+        panic!("unexpected call: {call:?}");
+    };
+    assert_eq!(assignment.comments.trailing, Some("inner"));
+}
@@ -36,6 +36,9 @@ pub async fn index(context: &RequestContext, workspace_root: &Path) {
             .analyze_shallow(&path, &context.finder, context.request_time)
             .ok();
         count += 1;
+        // Analyzed files are available to requests immediately, so let them
+        // interleave with indexing.
+        tokio::task::yield_now().await;
     }
 
     let elapsed = start_time.elapsed();
@@ -13,22 +13,32 @@
 // limitations under the License.
 
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    future::Future,
     path::{Path, PathBuf},
     sync::{Arc, Mutex, OnceLock},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
-use tokio::spawn;
+use futures::future::join_all;
+use tokio::{spawn, task::JoinHandle};
 use tower_lsp::{
     lsp_types::{
-        CompletionOptions, CompletionParams, CompletionResponse, DidChangeConfigurationParams,
-        DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
-        DocumentFormattingParams, DocumentLink, DocumentLinkOptions, DocumentLinkParams,
-        DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse,
-        Hover, HoverParams, HoverProviderCapability, InitializeParams, InitializeResult,
-        InitializedParams, Location, MessageType, OneOf, ReferenceParams, ServerCapabilities,
-        SymbolInformation, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
+        CodeActionParams, CodeActionProviderCapability, CodeActionResponse, CompletionItem,
+        CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
+        DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWatchedFilesParams,
+        DidCloseTextDocumentParams, DidOpenTextDocumentParams, DocumentFormattingParams,
+        DocumentHighlight, DocumentHighlightParams, DocumentLink, DocumentLinkOptions,
+        DocumentLinkParams, DocumentOnTypeFormattingOptions, DocumentOnTypeFormattingParams,
+        DocumentSymbolParams, DocumentSymbolResponse, ExecuteCommandOptions, ExecuteCommandParams,
+        FoldingRange, FoldingRangeParams, FoldingRangeProviderCapability, GotoDefinitionParams,
+        GotoDefinitionResponse, Hover, HoverParams, HoverProviderCapability, InitializeParams,
+        InitializeResult, InitializedParams, InlayHint, InlayHintParams, Location, MessageType,
+        OneOf, PrepareRenameResponse, ReferenceParams, RenameOptions, RenameParams,
+        SemanticTokensFullOptions, SemanticTokensOptions, SemanticTokensParams,
+        SemanticTokensResult, SemanticTokensServerCapabilities, ServerCapabilities, SignatureHelp,
+        SignatureHelpOptions, SignatureHelpParams, SymbolInformation, TextDocumentPositionParams,
+        TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url, WorkspaceEdit,
         WorkspaceSymbolParams,
     },
     LanguageServer, LspService, Server,
@@ -40,17 +50,30 @@ use crate::{
         client::TestableClient, error::RpcResult, storage::DocumentStorage, utils::AsyncSignal,
         workspace::WorkspaceFinder,
     },
+    diagnostics::DiagnosticsCache,
+    server::providers::{document::PendingChanges, hover::HoverCache},
 };
 
 mod indexing;
-mod providers;
+pub mod providers;
+
+/// Maximum time to wait for background tasks to stop on shutdown.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 struct ServerContext {
     pub storage: Arc<Mutex<DocumentStorage>>,
     pub analyzer: Arc<Analyzer>,
     pub finder: OnceLock<WorkspaceFinder>,
     pub indexed: Arc<Mutex<BTreeMap<PathBuf, AsyncSignal>>>,
+    pub diagnosed: Arc<Mutex<BTreeSet<Url>>>,
+    pub importers: Arc<Mutex<BTreeMap<PathBuf, BTreeSet<PathBuf>>>>,
+    pub diagnostics_cache: Arc<Mutex<DiagnosticsCache>>,
+    pub gen_diagnostics: Arc<Mutex<BTreeMap<Url, Vec<Diagnostic>>>>,
+    pub pending_changes: Arc<Mutex<PendingChanges>>,
+    pub hover_cache: Arc<Mutex<HoverCache>>,
     pub client: TestableClient,
+    /// Background tasks, such as indexing, cancelled on shutdown.
+    pub tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
 }
 
 impl ServerContext {
@@ -64,11 +87,17 @@ impl ServerContext {
             analyzer,
             finder: OnceLock::new(),
             indexed: Default::default(),
+            diagnosed: Default::default(),
+            importers: Default::default(),
+            diagnostics_cache: Default::default(),
+            gen_diagnostics: Default::default(),
+            pending_changes: Default::default(),
+            hover_cache: Default::default(),
             client,
+            tasks: Default::default(),
         }
     }
 
-    #[cfg(test)]
     pub fn new_for_testing() -> Self {
         let storage = Arc::new(Mutex::new(DocumentStorage::new()));
         let analyzer = Arc::new(Analyzer::new(&storage));
@@ -79,7 +108,40 @@ impl ServerContext {
             analyzer,
             finder,
             indexed: Default::default(),
+            diagnosed: Default::default(),
+            importers: Default::default(),
+            diagnostics_cache: Default::default(),
+            gen_diagnostics: Default::default(),
+            pending_changes: Default::default(),
+            hover_cache: Default::default(),
             client: TestableClient::new_for_testing(),
+            tasks: Default::default(),
+        }
+    }
+
+    /// Spawns a background task to be cancelled on shutdown.
+    pub fn spawn_task(&self, task: impl Future<Output = ()> + Send + 'static) {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(spawn(task));
+    }
+
+    /// Cancels background tasks and waits for them to stop.
+    pub async fn cancel_tasks(&self) {
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+        for task in &tasks {
+            task.abort();
+        }
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, join_all(tasks))
+            .await
+            .is_err()
+        {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    "Background tasks did not stop before shutdown".to_string(),
+                )
+                .await;
         }
     }
 
@@ -89,6 +151,12 @@ impl ServerContext {
             analyzer: self.analyzer.clone(),
             finder: self.finder.get().unwrap().clone(),
             indexed: self.indexed.clone(),
+            diagnosed: self.diagnosed.clone(),
+            importers: self.importers.clone(),
+            diagnostics_cache: self.diagnostics_cache.clone(),
+            gen_diagnostics: self.gen_diagnostics.clone(),
+            pending_changes: self.pending_changes.clone(),
+            hover_cache: self.hover_cache.clone(),
             client: self.client.clone(),
             request_time: Instant::now(),
         }
@@ -101,12 +169,26 @@ pub struct RequestContext {
     pub analyzer: Arc<Analyzer>,
     pub finder: WorkspaceFinder,
     pub indexed: Arc<Mutex<BTreeMap<PathBuf, AsyncSignal>>>,
+    pub diagnosed: Arc<Mutex<BTreeSet<Url>>>,
+    /// Maps imported files to the diagnosed files importing them.
+    pub importers: Arc<Mutex<BTreeMap<PathBuf, BTreeSet<PathBuf>>>>,
+    /// Results of diagnostic passes for diagnosed files.
+    pub diagnostics_cache: Arc<Mutex<DiagnosticsCache>>,
+    /// Errors reported by the last `gn gen`, published along with analysis
+    /// diagnostics.
+    pub gen_diagnostics: Arc<Mutex<BTreeMap<Url, Vec<Diagnostic>>>>,
+    /// Incremental changes of open files received ahead of the versions they
+    /// apply to, keyed by their versions.
+    pub pending_changes: Arc<Mutex<PendingChanges>>,
+    /// Recent hover results.
+    pub hover_cache: Arc<Mutex<HoverCache>>,
     pub client: TestableClient,
     pub request_time: Instant,
 }
 
 impl RequestContext {
-    #[cfg(test)]
+    /// Creates a context without a language client, for tests and
+    /// benchmarks.
     pub fn new_for_testing() -> Self {
         ServerContext::new_for_testing().request()
     }
@@ -128,10 +210,14 @@ impl Backend {
     }
 
     async fn maybe_index_workspace_for(&self, context: &RequestContext, path: &Path) {
-        let Some(workspace_root) = context.finder.find_for(path) else {
+        // Roots chosen without a `.gn` file, e.g. output directories of
+        // argument files, may be arbitrarily large and are not indexed.
+        let Ok((workspace_root, false)) = context
+            .analyzer
+            .resolve_workspace_root(path, &context.finder)
+        else {
             return;
         };
-        let workspace_root = workspace_root.to_path_buf();
 
         let mut indexed = match context
             .indexed
@@ -144,7 +230,7 @@ impl Backend {
         };
 
         let context = context.clone();
-        spawn(async move {
+        self.context.spawn_task(async move {
             indexing::index(&context, &workspace_root).await;
             indexed.set();
         });
@@ -165,7 +251,7 @@ impl LanguageServer for Backend {
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 definition_provider: Some(OneOf::Left(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
@@ -174,10 +260,46 @@ impl LanguageServer for Backend {
                     work_done_progress_options: Default::default(),
                 }),
                 document_symbol_provider: Some(OneOf::Left(true)),
-                completion_provider: Some(CompletionOptions::default()),
+                document_highlight_provider: Some(OneOf::Left(true)),
+                completion_provider: Some(CompletionOptions {
+                    resolve_provider: Some(true),
+                    ..Default::default()
+                }),
                 document_formatting_provider: Some(OneOf::Left(true)),
+                document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                    first_trigger_character: "\n".to_string(),
+                    more_trigger_character: Some(vec!["}".to_string()]),
+                }),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
+                signature_help_provider: Some(SignatureHelpOptions {
+                    trigger_characters: Some(vec!["(".to_string(), ",".to_string()]),
+                    retrigger_characters: None,
+                    work_done_progress_options: Default::default(),
+                }),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: providers::semantic_tokens::legend(),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
                 workspace_symbol_provider: Some(OneOf::Left(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: providers::execute_command::COMMANDS
+                        .iter()
+                        .map(|command| command.to_string())
+                        .collect(),
+                    work_done_progress_options: Default::default(),
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -192,12 +314,34 @@ impl LanguageServer for Backend {
             .await;
 
         let configurations = self.context.client.configurations().await;
+        context.analyzer.set_verify_interval(Duration::from_millis(
+            configurations.cache.check_interval_ms,
+        ));
+        context
+            .analyzer
+            .set_cache_capacity(configurations.cache.max_files);
+        if configurations.cache.freeze {
+            context.analyzer.freeze();
+        }
+        context
+            .analyzer
+            .set_manual_root(configurations.workspace.manual_root);
+        context
+            .analyzer
+            .set_args_file_patterns(configurations.workspace.args_file_patterns);
+        context
+            .analyzer
+            .set_label_search_roots(configurations.workspace.label_search_roots);
+        context
+            .analyzer
+            .set_case_insensitive_paths(configurations.workspace.case_insensitive_paths);
         if !configurations.background_indexing {
             return;
         }
     }
 
     async fn shutdown(&self) -> RpcResult<()> {
+        self.context.cancel_tasks().await;
         Ok(())
     }
 
@@ -221,6 +365,10 @@ impl LanguageServer for Backend {
         providers::document::did_close(&self.context.request(), params).await;
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        providers::document::did_change_watched_files(&self.context.request(), params).await;
+    }
+
     async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
         providers::configuration::did_change_configuration(&self.context.request(), params).await;
     }
@@ -254,14 +402,71 @@ impl LanguageServer for Backend {
         Ok(providers::document_symbol::document_symbol(&self.context.request(), params).await?)
     }
 
+    async fn document_highlight(
+        &self,
+        params: DocumentHighlightParams,
+    ) -> RpcResult<Option<Vec<DocumentHighlight>>> {
+        Ok(
+            providers::document_highlight::document_highlight(&self.context.request(), params)
+                .await?,
+        )
+    }
+
     async fn completion(&self, params: CompletionParams) -> RpcResult<Option<CompletionResponse>> {
         Ok(providers::completion::completion(&self.context.request(), params).await?)
     }
 
+    async fn completion_resolve(&self, item: CompletionItem) -> RpcResult<CompletionItem> {
+        Ok(providers::completion::completion_resolve(&self.context.request(), item).await?)
+    }
+
     async fn references(&self, params: ReferenceParams) -> RpcResult<Option<Vec<Location>>> {
         Ok(providers::references::references(&self.context.request(), params).await?)
     }
 
+    async fn inlay_hint(&self, params: InlayHintParams) -> RpcResult<Option<Vec<InlayHint>>> {
+        Ok(providers::inlay_hint::inlay_hint(&self.context.request(), params).await?)
+    }
+
+    async fn signature_help(
+        &self,
+        params: SignatureHelpParams,
+    ) -> RpcResult<Option<SignatureHelp>> {
+        Ok(providers::signature_help::signature_help(&self.context.request(), params).await?)
+    }
+
+    async fn folding_range(
+        &self,
+        params: FoldingRangeParams,
+    ) -> RpcResult<Option<Vec<FoldingRange>>> {
+        Ok(providers::folding_range::folding_range(&self.context.request(), params).await?)
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> RpcResult<Option<SemanticTokensResult>> {
+        Ok(
+            providers::semantic_tokens::semantic_tokens_full(&self.context.request(), params)
+                .await?,
+        )
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> RpcResult<Option<PrepareRenameResponse>> {
+        Ok(providers::rename::prepare_rename(&self.context.request(), params).await?)
+    }
+
+    async fn rename(&self, params: RenameParams) -> RpcResult<Option<WorkspaceEdit>> {
+        Ok(providers::rename::rename(&self.context.request(), params).await?)
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> RpcResult<Option<CodeActionResponse>> {
+        Ok(providers::code_action::code_action(&self.context.request(), params).await?)
+    }
+
     async fn formatting(
         &self,
         params: DocumentFormattingParams,
@@ -269,21 +474,99 @@ impl LanguageServer for Backend {
         Ok(providers::formatting::formatting(&self.context.request(), params).await?)
     }
 
+    async fn on_type_formatting(
+        &self,
+        params: DocumentOnTypeFormattingParams,
+    ) -> RpcResult<Option<Vec<TextEdit>>> {
+        Ok(
+            providers::on_type_formatting::on_type_formatting(&self.context.request(), params)
+                .await?,
+        )
+    }
+
     async fn symbol(
         &self,
         params: WorkspaceSymbolParams,
     ) -> RpcResult<Option<Vec<SymbolInformation>>> {
         Ok(providers::workspace_symbol::workspace_symbol(&self.context.request(), params).await?)
     }
+
+    async fn execute_command(
+        &self,
+        params: ExecuteCommandParams,
+    ) -> RpcResult<Option<serde_json::Value>> {
+        Ok(providers::execute_command::execute_command(&self.context.request(), params).await?)
+    }
 }
 
-pub async fn run() {
+pub async fn run(log_level: MessageType) {
     let storage = Arc::new(Mutex::new(DocumentStorage::new()));
     let analyzer = Arc::new(Analyzer::new(&storage));
-    let (service, socket) =
-        LspService::new(move |client| Backend::new(storage, analyzer, TestableClient::new(client)));
+    let (service, socket) = LspService::new(move |client| {
+        Backend::new(
+            storage,
+            analyzer,
+            TestableClient::new(client).with_log_level(log_level),
+        )
+    });
 
     let stdin = tokio::io::stdin();
     let stdout = tokio::io::stdout();
     Server::new(stdin, stdout, socket).serve(service).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn initialized_messages(client: TestableClient) -> Vec<(MessageType, String)> {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Arc::new(Analyzer::new(&storage));
+        let backend = Backend::new(storage, analyzer, client.clone());
+        backend
+            .initialize(InitializeParams::default())
+            .await
+            .unwrap();
+        backend.initialized(InitializedParams {}).await;
+        client.logged_messages()
+    }
+
+    #[tokio::test]
+    async fn test_log_level() {
+        let messages = initialized_messages(TestableClient::new_for_testing()).await;
+        assert_eq!(
+            messages,
+            vec![(
+                MessageType::INFO,
+                "GN language server initialized".to_string()
+            )]
+        );
+
+        let messages = initialized_messages(
+            TestableClient::new_for_testing().with_log_level(MessageType::WARNING),
+        )
+        .await;
+        assert!(messages.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_tasks() {
+        let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+        let analyzer = Arc::new(Analyzer::new(&storage));
+        let backend = Backend::new(storage, analyzer, TestableClient::new_for_testing());
+
+        // Simulate indexing that never finishes, holding a reference to
+        // observe when the task is dropped.
+        let in_flight = Arc::new(());
+        let held = in_flight.clone();
+        backend.context.spawn_task(async move {
+            let _held = held;
+            std::future::pending::<()>().await;
+        });
+        assert_eq!(Arc::strong_count(&in_flight), 2);
+
+        backend.shutdown().await.unwrap();
+        assert_eq!(Arc::strong_count(&in_flight), 1);
+        assert!(backend.context.tasks.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    Diagnostic, NumberOrString, Position, Range, TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{
+    analyzer::{AnalyzedFile, AnalyzedStatement, TopLevelStatementsExt},
+    common::error::Result,
+    diagnostics::{untabify_edits, NO_TABS},
+    parser::{ErrorPrimaryExpr, ErrorRef, ErrorStatement, Node},
+    server::{
+        providers::utils::{format_path, get_text_document_path},
+        RequestContext,
+    },
+};
+
+/// Returns actions rewriting import() strings overlapping the range to their
+/// canonical source-absolute form.
+fn normalize_import_actions(
+    current_file: &AnalyzedFile,
+    uri: &Url,
+    range: Range,
+) -> Vec<CodeActionOrCommand> {
+    let line_index = &current_file.document.line_index;
+    current_file
+        .analyzed_root
+        .top_level_statements()
+        .filter_map(|statement| {
+            let AnalyzedStatement::Import(import) = statement else {
+                return None;
+            };
+            let string = import.call.only_arg()?.as_primary_string()?;
+            if !string.embedded_exprs.is_empty() {
+                return None;
+            }
+            let string_range = line_index.range(string.span);
+            if string_range.end < range.start || range.end < string_range.start {
+                return None;
+            }
+            let normalized = format_path(&import.file.document.path, &current_file.workspace_root);
+            if normalized == string.raw_value {
+                return None;
+            }
+            let edit = TextEdit {
+                range: Range {
+                    start: line_index.position(string.span.start() + 1),
+                    end: line_index.position(string.span.end() - 1),
+                },
+                new_text: normalized.clone(),
+            };
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Normalize import to \"{normalized}\""),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))
+        })
+        .collect()
+}
+
+/// Returns actions inserting commas missing between list items on the lines
+/// of the range, plus one inserting all of them if there are several.
+fn missing_comma_actions(
+    current_file: &AnalyzedFile,
+    uri: &Url,
+    range: Range,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let line_index = &current_file.document.line_index;
+    let positions: Vec<Position> = current_file
+        .ast
+        .errors()
+        .filter_map(|error| match error {
+            ErrorRef::PrimaryExpr(ErrorPrimaryExpr::MissingComma(missing_comma)) => {
+                Some(line_index.position(missing_comma.span.start()))
+            }
+            _ => None,
+        })
+        .filter(|position| range.start.line <= position.line && position.line <= range.end.line)
+        .collect();
+
+    let action = |title: &str, positions: &[Position]| {
+        let edits = positions
+            .iter()
+            .map(|position| TextEdit {
+                range: Range::new(*position, *position),
+                new_text: ",".to_string(),
+            })
+            .collect();
+        let fixed_diagnostics = diagnostics
+            .iter()
+            .filter(|diagnostic| positions.contains(&diagnostic.range.start))
+            .filter(|diagnostic| diagnostic.message == "Missing comma")
+            .cloned()
+            .collect();
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(fixed_diagnostics),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), edits)])),
+                ..Default::default()
+            }),
+            is_preferred: Some(positions.len() == 1),
+            ..Default::default()
+        })
+    };
+
+    let mut actions: Vec<_> = positions
+        .iter()
+        .map(|position| action("Insert missing comma", std::slice::from_ref(position)))
+        .collect();
+    if positions.len() > 1 {
+        actions.push(action("Insert all missing commas", &positions));
+    }
+    actions
+}
+
+/// Returns actions commenting out or deleting unknown statements overlapping
+/// the range, e.g. text pasted by mistake.
+fn unknown_statement_actions(
+    current_file: &AnalyzedFile,
+    uri: &Url,
+    range: Range,
+    diagnostics: &[Diagnostic],
+) -> Vec<CodeActionOrCommand> {
+    let line_index = &current_file.document.line_index;
+    current_file
+        .ast
+        .errors()
+        .filter_map(|error| match error {
+            ErrorRef::Statement(ErrorStatement::UnknownStatement(unknown)) => Some(unknown),
+            _ => None,
+        })
+        .flat_map(|unknown| {
+            let unknown_range = line_index.range(unknown.span);
+            if unknown_range.end < range.start || range.end < unknown_range.start {
+                return Vec::new();
+            }
+            let fixed_diagnostics: Vec<_> = diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.range == unknown_range)
+                .filter(|diagnostic| diagnostic.message == unknown.diagnosis())
+                .cloned()
+                .collect();
+            [
+                (
+                    "Comment out unknown statement",
+                    format!("# {}", unknown.text),
+                ),
+                ("Delete unknown statement", String::new()),
+            ]
+            .into_iter()
+            .map(|(title, new_text)| {
+                let edit = TextEdit {
+                    range: unknown_range,
+                    new_text,
+                };
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: title.to_string(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(fixed_diagnostics.clone()),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            })
+            .collect()
+        })
+        .collect()
+}
+
+pub async fn code_action(
+    context: &RequestContext,
+    params: CodeActionParams,
+) -> Result<Option<CodeActionResponse>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let document = context.storage.lock().unwrap().read(&path);
+
+    let mut actions: Vec<CodeActionOrCommand> = params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.code == Some(NumberOrString::String(NO_TABS.to_string())))
+        .filter_map(|diagnostic| {
+            let edits = untabify_edits(&document, diagnostic.range);
+            if edits.is_empty() {
+                return None;
+            }
+            Some(CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Convert tabs to spaces".to_string(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(params.text_document.uri.clone(), edits)])),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }))
+        })
+        .collect();
+
+    if let Ok(current_file) = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)
+    {
+        actions.extend(missing_comma_actions(
+            &current_file,
+            &params.text_document.uri,
+            params.range,
+            &params.context.diagnostics,
+        ));
+        actions.extend(unknown_statement_actions(
+            &current_file,
+            &params.text_document.uri,
+            params.range,
+            &params.context.diagnostics,
+        ));
+        actions.extend(normalize_import_actions(
+            &current_file,
+            &params.text_document.uri,
+            params.range,
+        ));
+    }
+
+    Ok(Some(actions))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        CodeActionContext, PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn actions_at(position: Position) -> CodeActionResponse {
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(testdata("workspaces/normalize_import/dir/sub/BUILD.gn"))
+                    .unwrap(),
+            },
+            range: Range::new(position, position),
+            context: CodeActionContext::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        code_action(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_normalize_import() {
+        let actions = actions_at(Position::new(14, 10)).await;
+        let [CodeActionOrCommand::CodeAction(action)] = actions.as_slice() else {
+            panic!("unexpected actions: {actions:?}");
+        };
+        assert_eq!(action.title, "Normalize import to \"//dir/foo.gni\"");
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let edits: Vec<_> = changes.values().flatten().cloned().collect();
+        assert_eq!(
+            edits,
+            vec![TextEdit {
+                range: Range::new(Position::new(14, 8), Position::new(14, 18)),
+                new_text: "//dir/foo.gni".to_string(),
+            }]
+        );
+
+        // Already normalized imports get no action.
+        assert!(actions_at(Position::new(15, 10)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_comma() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/normalize_import/missing_comma.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "sources = [\n  \"a.cc\"\n  \"b.cc\"\n  \"c.cc\"\n]\n",
+            1,
+        );
+        let actions = |range: Range| {
+            let params = CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                range,
+                context: CodeActionContext::default(),
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                code_action(&context, params)
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .into_iter()
+                    .map(|action| {
+                        let CodeActionOrCommand::CodeAction(action) = action else {
+                            panic!("unexpected command");
+                        };
+                        let changes = action.edit.unwrap().changes.unwrap();
+                        let positions: Vec<_> = changes
+                            .into_values()
+                            .flatten()
+                            .map(|edit| {
+                                assert_eq!(edit.new_text, ",");
+                                edit.range.start
+                            })
+                            .collect();
+                        (action.title, positions)
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // A cursor on a line offers the fix for that line only.
+        let position = Position::new(1, 4);
+        assert_eq!(
+            actions(Range::new(position, position)).await,
+            [(
+                "Insert missing comma".to_string(),
+                vec![Position::new(1, 8)]
+            )]
+        );
+
+        // A selection offers each fix and one for all of them.
+        assert_eq!(
+            actions(Range::new(Position::new(0, 0), Position::new(4, 1))).await,
+            [
+                (
+                    "Insert missing comma".to_string(),
+                    vec![Position::new(1, 8)]
+                ),
+                (
+                    "Insert missing comma".to_string(),
+                    vec![Position::new(2, 8)]
+                ),
+                (
+                    "Insert all missing commas".to_string(),
+                    vec![Position::new(1, 8), Position::new(2, 8)]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_statement() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/normalize_import/unknown_statement.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "a = 1\nsee https://example.com\nb = 2\n",
+            1,
+        );
+        let position = Position::new(1, 4);
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(&path).unwrap(),
+            },
+            range: Range::new(position, position),
+            context: CodeActionContext::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let actions: Vec<_> = code_action(&context, params)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|action| {
+                let CodeActionOrCommand::CodeAction(action) = action else {
+                    panic!("unexpected command");
+                };
+                let mut changes = action.edit.unwrap().changes.unwrap();
+                let edits = changes
+                    .remove(&Url::from_file_path(&path).unwrap())
+                    .unwrap();
+                (action.title, edits)
+            })
+            .collect();
+
+        // The statement spans its trailing newline, so deleting it removes
+        // the whole line.
+        let range = Range::new(Position::new(1, 0), Position::new(2, 0));
+        assert_eq!(
+            actions,
+            [
+                (
+                    "Comment out unknown statement".to_string(),
+                    vec![TextEdit {
+                        range,
+                        new_text: "# see https://example.com\n".to_string(),
+                    }]
+                ),
+                (
+                    "Delete unknown statement".to_string(),
+                    vec![TextEdit {
+                        range,
+                        new_text: String::new(),
+                    }]
+                ),
+            ]
+        );
+    }
+}
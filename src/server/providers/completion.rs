@@ -12,24 +12,87 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use itertools::Itertools;
 use tower_lsp::lsp_types::{
-    Command, CompletionItem, CompletionItemKind, CompletionParams, CompletionResponse,
-    Documentation, MarkupContent, MarkupKind,
+    Command, CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionParams,
+    CompletionResponse, CompletionTextEdit, Documentation, InsertTextFormat, Location,
+    MarkupContent, MarkupKind, Position, Range, TextEdit, Url,
 };
 
 use crate::{
-    analyzer::AnalyzedFile,
-    common::{builtins::BUILTINS, error::Result},
-    parser::{Block, Node},
+    analyzer::{resolve_path, AnalyzedFile, Template, ValueType, Variable},
+    common::{
+        builtins::{BUILTINS, IMPORT},
+        error::{Error, Result},
+        utils::find_gn_files,
+    },
+    parser::{Block, LValue, Node, Statement},
     server::{
-        providers::utils::{format_template_help, format_variable_help, get_text_document_path},
+        providers::utils::{
+            format_path, format_template_help, format_variable_help, get_text_document_path,
+        },
         RequestContext,
     },
 };
 
+/// Kinds of symbols offered by identifier completions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum CompletionSymbolKind {
+    Variable,
+    Template,
+    Builtin,
+}
+
+/// Identifies the symbol of a completion item. Attached as `data` so that
+/// `completionItem/resolve` can tell items with the same label apart.
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompletionData {
+    /// File the completion was requested in.
+    path: PathBuf,
+    /// Position the completion was requested at.
+    position: Position,
+    kind: CompletionSymbolKind,
+    name: String,
+    /// Where the symbol is defined. `None` for builtins.
+    location: Option<Location>,
+    /// File to import for a template not in scope yet, e.g.
+    /// `//build/foo.gni`.
+    import: Option<String>,
+}
+
+impl CompletionData {
+    fn to_value(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+}
+
+fn variable_location(variable: &Variable) -> Option<Location> {
+    let assignment = variable.assignments.values().min_by_key(|assignment| {
+        (
+            &assignment.document.path,
+            assignment.primary_variable.start(),
+        )
+    })?;
+    Some(Location {
+        uri: Url::from_file_path(&assignment.document.path).ok()?,
+        range: assignment
+            .document
+            .line_index
+            .range(assignment.primary_variable),
+    })
+}
+
+fn template_location(template: &Template) -> Option<Location> {
+    Some(Location {
+        uri: Url::from_file_path(&template.document.path).ok()?,
+        range: template.document.line_index.range(template.call.span),
+    })
+}
+
 fn get_prefix_string_for_completion<'i>(ast: &Block<'i>, offset: usize) -> Option<&'i str> {
     ast.walk()
         .filter_map(|node| {
@@ -64,6 +127,11 @@ fn build_filename_completions(path: &Path, prefix: &str) -> Option<Vec<Completio
                 Some(CompletionItem {
                     label: format!("{basename}{type_suffix}"),
                     kind: Some(CompletionItemKind::FILE),
+                    // Typing a separator accepts a directory and continues
+                    // into it, so omit the separator from the inserted text.
+                    insert_text: is_dir.then(|| basename.clone()),
+                    commit_characters: is_dir
+                        .then(|| vec![std::path::MAIN_SEPARATOR_STR.to_string(), ":".to_string()]),
                     command: is_dir.then_some(Command {
                         command: "editor.action.triggerSuggest".to_string(),
                         ..Default::default()
@@ -76,6 +144,287 @@ fn build_filename_completions(path: &Path, prefix: &str) -> Option<Vec<Completio
     )
 }
 
+fn build_target_completions(
+    current_file: &AnalyzedFile,
+    offset: usize,
+    prefix: &str,
+) -> Vec<CompletionItem> {
+    let line_index = &current_file.document.line_index;
+    let range = Range {
+        start: line_index.position(offset - prefix.len()),
+        end: line_index.position(offset),
+    };
+    // Targets in the same file are likely dependencies, except the target
+    // being edited.
+    current_file
+        .analyzed_root
+        .targets()
+        .filter(|target| !target.implicit_name)
+        .filter(|target| !(target.call.span.start() < offset && offset < target.call.span.end()))
+        .enumerate()
+        .map(|(index, target)| {
+            let label = format!(":{}", target.name);
+            CompletionItem {
+                label: label.clone(),
+                label_details: label_details(target.call.function.name),
+                kind: Some(CompletionItemKind::REFERENCE),
+                // Rank sibling targets first, in the order of definitions.
+                sort_text: Some(format!("0{index:05}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: label,
+                })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Completes directory components of a source-absolute label, e.g. `//fo`.
+fn build_label_dir_completions(
+    current_file: &AnalyzedFile,
+    prefix: &str,
+) -> Option<Vec<CompletionItem>> {
+    let (dir, basename_prefix) = prefix.rsplit_once('/')?;
+    let complete_dir = resolve_path(
+        &format!("{dir}/"),
+        &current_file.workspace_root,
+        current_file
+            .document
+            .path
+            .parent()
+            .unwrap_or(&current_file.workspace_root),
+    );
+    Some(
+        std::fs::read_dir(&complete_dir)
+            .ok()?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let basename = entry.file_name().to_str()?.to_string();
+                if !basename.starts_with(basename_prefix)
+                    || basename.starts_with('.')
+                    || !entry.file_type().ok()?.is_dir()
+                {
+                    return None;
+                }
+                Some(CompletionItem {
+                    label: format!("{basename}/"),
+                    kind: Some(CompletionItemKind::FOLDER),
+                    // Typing a separator accepts a directory and continues
+                    // into it, so omit the separator from the inserted text.
+                    insert_text: Some(basename),
+                    commit_characters: Some(vec!["/".to_string(), ":".to_string()]),
+                    command: Some(Command {
+                        command: "editor.action.triggerSuggest".to_string(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            })
+            .sorted_by_key(|item| item.label.clone())
+            .collect(),
+    )
+}
+
+/// Completes target names of a label with a directory, e.g. `//foo:ba`, from
+/// the BUILD.gn file of the directory.
+fn build_label_target_completions(
+    context: &RequestContext,
+    current_file: &AnalyzedFile,
+    offset: usize,
+    prefix: &str,
+) -> Option<Vec<CompletionItem>> {
+    let (dir, name_prefix) = prefix.split_once(':')?;
+    let build_path = resolve_path(
+        dir,
+        &current_file.workspace_root,
+        current_file
+            .document
+            .path
+            .parent()
+            .unwrap_or(&current_file.workspace_root),
+    )
+    .join("BUILD.gn");
+    let build_file = context
+        .analyzer
+        .analyze_shallow(&build_path, &context.finder, context.request_time)
+        .ok()?;
+
+    let line_index = &current_file.document.line_index;
+    let range = Range {
+        start: line_index.position(offset - name_prefix.len()),
+        end: line_index.position(offset),
+    };
+    Some(
+        build_file
+            .environment
+            .targets
+            .locals()
+            .values()
+            .sorted_by_key(|target| target.call.span.start())
+            .map(|target| CompletionItem {
+                label: target.name.to_string(),
+                kind: Some(CompletionItemKind::CONSTANT),
+                detail: Some(target.call.function.name.to_string()),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: target.name.to_string(),
+                })),
+                ..Default::default()
+            })
+            .collect(),
+    )
+}
+
+/// Returns whether the cursor is in a list assigned to a dependency variable,
+/// e.g. `deps = [ ":a" | ]`.
+fn is_in_deps_list(ast: &Block, offset: usize) -> bool {
+    ast.walk()
+        .filter_map(|node| node.as_statement())
+        .any(|statement| {
+            let Statement::Assignment(assignment) = statement else {
+                return false;
+            };
+            let LValue::Identifier(identifier) = &assignment.lvalue else {
+                return false;
+            };
+            let Some(list) = assignment.rvalue.as_primary_list() else {
+                return false;
+            };
+            identifier.name.ends_with("deps")
+                && list.span.start() < offset
+                && offset < list.span.end()
+        })
+}
+
+/// Returns sibling target completions for a dependency list when the cursor
+/// is outside strings, quoting labels and inserting commas as needed.
+fn build_quoted_target_completions(
+    current_file: &AnalyzedFile,
+    offset: usize,
+) -> Vec<CompletionItem> {
+    let data: &str = &current_file.document.data;
+    let line_index = &current_file.document.line_index;
+    let start = data[..offset]
+        .trim_end_matches(|ch: char| ch.is_ascii_alphanumeric() || ch == '_')
+        .len();
+    let before = data[..start].trim_end();
+    let after = data[offset..].trim_start();
+
+    // Separate from the previous item, right after it if the cursor is on
+    // another line.
+    let mut leading = "";
+    let mut additional_text_edits = None;
+    if !before.ends_with(['[', ',']) {
+        if before.len() == start {
+            leading = ", ";
+        } else {
+            let position = line_index.position(before.len());
+            additional_text_edits = Some(vec![TextEdit {
+                range: Range::new(position, position),
+                new_text: ",".to_string(),
+            }]);
+        }
+    }
+
+    // Separate from the next item. Multi-line lists keep trailing commas as
+    // `gn format` does.
+    let multiline = data[offset..data.len() - after.len()].contains('\n');
+    let trailing = match (after.chars().next(), multiline) {
+        (Some(','), _) | (Some(']'), false) => "",
+        (_, true) => ",",
+        (_, false) => ", ",
+    };
+
+    build_target_completions(current_file, offset, &data[start..offset])
+        .into_iter()
+        .map(|mut item| {
+            if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+                edit.new_text = format!("{leading}\"{}\"{trailing}", item.label);
+            }
+            item.filter_text = Some(item.label.trim_start_matches(':').to_string());
+            item.additional_text_edits = additional_text_edits.clone();
+            item
+        })
+        .collect()
+}
+
+fn is_import_string(ast: &Block, offset: usize) -> bool {
+    ast.walk().any(|node| {
+        matches!(
+            node.as_statement(),
+            Some(Statement::Call(call))
+                if call.function.name == IMPORT
+                    && call.only_arg().is_some_and(|arg| {
+                        arg.span().start() < offset && offset < arg.span().end()
+                    })
+        )
+    })
+}
+
+/// Counts the directory steps needed to move from `from` to `to`.
+fn directory_distance(from: &Path, to: &Path) -> usize {
+    let from: Vec<_> = from.components().collect();
+    let to: Vec<_> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    (from.len() - common) + (to.len() - common)
+}
+
+fn build_import_completions(
+    current_file: &AnalyzedFile,
+    offset: usize,
+    prefix: &str,
+    common_dirs: &[String],
+) -> Vec<CompletionItem> {
+    let line_index = &current_file.document.line_index;
+    let range = Range {
+        start: line_index.position(offset - prefix.len()),
+        end: line_index.position(offset),
+    };
+    let current_dir = current_file
+        .document
+        .path
+        .parent()
+        .unwrap_or(&current_file.workspace_root);
+    let build_config = current_file.build_config();
+    let is_common = |label: &str| {
+        common_dirs.iter().any(|dir| {
+            label
+                .strip_prefix(dir.trim_end_matches('/'))
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    };
+    // BUILDCONFIG.gn and files in common directories are imported from
+    // anywhere, so rank them first. Otherwise files near the current file are
+    // more likely to be imported, so rank them by the distance between
+    // directories.
+    find_gn_files(&current_file.workspace_root)
+        .filter(|path| path.extension().is_some_and(|ext| ext == "gni"))
+        .chain(build_config.map(Path::to_path_buf))
+        .filter_map(|path| {
+            let label = format_path(&path, &current_file.workspace_root)
+                .replace(std::path::MAIN_SEPARATOR, "/");
+            if !label.starts_with(prefix) {
+                return None;
+            }
+            let common = build_config == Some(path.as_path()) || is_common(&label);
+            let distance = directory_distance(current_dir, path.parent()?);
+            Some((!common, distance, label))
+        })
+        .sorted()
+        .map(|(uncommon, distance, label)| CompletionItem {
+            label: label.clone(),
+            kind: Some(CompletionItemKind::FILE),
+            sort_text: Some(format!("{}{distance:05}{label}", uncommon as u8)),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: label,
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
 fn is_after_dot(data: &str, offset: usize) -> bool {
     for ch in data[..offset].chars().rev() {
         match ch {
@@ -87,6 +436,107 @@ fn is_after_dot(data: &str, offset: usize) -> bool {
     false
 }
 
+/// Returns whether the cursor is at the argument of `defined()`, e.g.
+/// `defined(fo|`.
+fn is_defined_argument(data: &str, offset: usize) -> bool {
+    let is_identifier_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '_';
+    let Some(rest) = data[..offset]
+        .trim_end_matches(is_identifier_char)
+        .trim_end()
+        .strip_suffix('(')
+    else {
+        return false;
+    };
+    rest.trim_end()
+        .strip_suffix("defined")
+        .is_some_and(|rest| !rest.ends_with(is_identifier_char))
+}
+
+fn label_details(description: &str) -> Option<CompletionItemLabelDetails> {
+    Some(CompletionItemLabelDetails {
+        detail: None,
+        description: Some(description.to_string()),
+    })
+}
+
+/// Returns the name of the variable assigned on the current line if the
+/// cursor is at the start of its right-hand side, e.g. `enable_foo = |`.
+fn get_assigned_variable_for_completion(data: &str, offset: usize) -> Option<&str> {
+    let line = &data[data[..offset].rfind('\n').map_or(0, |pos| pos + 1)..offset];
+    let rest = line
+        .trim_end_matches(|ch: char| ch.is_ascii_alphanumeric() || ch == '_')
+        .trim_end()
+        .strip_suffix('=')?;
+    if rest.ends_with(['+', '-', '=', '!', '<', '>']) {
+        return None;
+    }
+    let name = rest.trim();
+    let is_identifier = name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+    is_identifier.then_some(name)
+}
+
+/// Returns whether the cursor is at the right-hand side of an assignment to a
+/// variable known to be boolean.
+fn is_boolean_assignment_rhs(current_file: &AnalyzedFile, offset: usize) -> bool {
+    let Some(name) = get_assigned_variable_for_completion(&current_file.document.data, offset)
+    else {
+        return false;
+    };
+    let variables = current_file.variables_at(offset);
+    let Some(variable) = variables.get(name) else {
+        return false;
+    };
+    variable.inferred_type() == Some(ValueType::Boolean)
+}
+
+/// Snippets offered in an empty BUILD.gn, as (name, snippet) pairs.
+const SCAFFOLDS: &[(&str, &str)] = &[
+    ("import", "import(\"$1\")\n$0"),
+    (
+        "executable",
+        "executable(\"${1:name}\") {\n  sources = [ $2 ]\n}\n$0",
+    ),
+    ("group", "group(\"${1:name}\") {\n  deps = [ $2 ]\n}\n$0"),
+];
+
+/// Returns whether a BUILD.gn file has no statements other than the one being
+/// typed at the cursor, e.g. a new file with only a license header.
+fn is_empty_build_file(current_file: &AnalyzedFile, offset: usize) -> bool {
+    current_file
+        .document
+        .path
+        .file_name()
+        .is_some_and(|name| name == "BUILD.gn")
+        && current_file.ast.statements.iter().all(|statement| {
+            matches!(statement, Statement::Error(_))
+                && statement.span().start() <= offset
+                && offset <= statement.span().end()
+        })
+}
+
+fn scaffold_completions(current_file: &AnalyzedFile, offset: usize) -> Vec<CompletionItem> {
+    if !is_empty_build_file(current_file, offset) {
+        return Vec::new();
+    }
+    SCAFFOLDS
+        .iter()
+        .enumerate()
+        .map(|(index, (name, snippet))| CompletionItem {
+            label: name.to_string(),
+            label_details: label_details("scaffold"),
+            kind: Some(CompletionItemKind::SNIPPET),
+            // Rank scaffolds before other identifiers.
+            sort_text: Some(format!("0{index}")),
+            insert_text: Some(snippet.to_string()),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            ..Default::default()
+        })
+        .collect()
+}
+
 fn identifier_completions(current_file: &AnalyzedFile, offset: usize) -> Vec<CompletionItem> {
     // Handle identifier completions.
     // If the cursor is after a dot, we can't make suggestions.
@@ -96,31 +546,54 @@ fn identifier_completions(current_file: &AnalyzedFile, offset: usize) -> Vec<Com
 
     let variables = current_file.variables_at(offset);
     let templates = current_file.templates_at(offset);
+    let data = |kind, name: &str, location| {
+        CompletionData {
+            path: current_file.document.path.clone(),
+            position: current_file.document.line_index.position(offset),
+            kind,
+            name: name.to_string(),
+            location,
+            import: None,
+        }
+        .to_value()
+    };
 
     // Enumerate variables at the current scope.
-    let variable_items = variables.all_items().into_iter().map(|(name, variable)| {
-        let paragraphs = format_variable_help(&variable, &current_file.workspace_root);
-        CompletionItem {
+    // Documentation is filled in by `completionItem/resolve` as scopes may
+    // contain many variables with many assignments each.
+    let variable_items = variables
+        .all_items()
+        .into_iter()
+        .map(|(name, variable)| CompletionItem {
             label: name.to_string(),
+            label_details: variable
+                .inferred_type()
+                .and_then(|ty| label_details(ty.name())),
             kind: Some(CompletionItemKind::VARIABLE),
-            documentation: Some(Documentation::MarkupContent(MarkupContent {
-                kind: MarkupKind::Markdown,
-                value: paragraphs.join("\n\n"),
-            })),
+            data: data(
+                CompletionSymbolKind::Variable,
+                name,
+                variable_location(&variable),
+            ),
             ..Default::default()
-        }
-    });
+        });
 
     // Enumerate templates defined at the current position.
     let template_items = templates.all_items().into_values().map(|template| {
         let paragraphs = format_template_help(&template, &current_file.workspace_root);
         CompletionItem {
             label: template.name.to_string(),
+            label_details: label_details("template"),
             kind: Some(CompletionItemKind::FUNCTION),
             documentation: Some(Documentation::MarkupContent(MarkupContent {
                 kind: MarkupKind::Markdown,
                 value: paragraphs.join("\n\n"),
             })),
+            data: data(
+                CompletionSymbolKind::Template,
+                template.name,
+                template_location(&template),
+            ),
             ..Default::default()
         }
     });
@@ -132,11 +605,13 @@ fn identifier_completions(current_file: &AnalyzedFile, offset: usize) -> Vec<Com
         .chain(BUILTINS.targets.iter())
         .map(|symbol| CompletionItem {
             label: symbol.name.to_string(),
+            label_details: label_details("builtin"),
             kind: Some(CompletionItemKind::FUNCTION),
             documentation: Some(Documentation::MarkupContent(MarkupContent {
                 kind: MarkupKind::Markdown,
                 value: symbol.doc.to_string(),
             })),
+            data: data(CompletionSymbolKind::Builtin, symbol.name, None),
             ..Default::default()
         });
     let builtin_variable_items = BUILTINS
@@ -145,19 +620,32 @@ fn identifier_completions(current_file: &AnalyzedFile, offset: usize) -> Vec<Com
         .chain(BUILTINS.target_variables.iter())
         .map(|symbol| CompletionItem {
             label: symbol.name.to_string(),
+            label_details: label_details("builtin"),
             kind: Some(CompletionItemKind::VARIABLE),
             documentation: Some(Documentation::MarkupContent(MarkupContent {
                 kind: MarkupKind::Markdown,
                 value: symbol.doc.to_string(),
             })),
+            data: data(CompletionSymbolKind::Builtin, symbol.name, None),
             ..Default::default()
         });
 
-    // Keywords.
-    let keyword_items = ["true", "false", "if", "else"].map(|name| CompletionItem {
-        label: name.to_string(),
-        kind: Some(CompletionItemKind::KEYWORD),
-        ..Default::default()
+    // defined() checks only variables.
+    if is_defined_argument(&current_file.document.data, offset) {
+        return variable_items.chain(builtin_variable_items).collect();
+    }
+
+    // Keywords. Rank boolean literals first when assigning to a boolean.
+    let is_boolean = is_boolean_assignment_rhs(current_file, offset);
+    let keyword_items = ["true", "false", "if", "else"].map(|name| {
+        let is_literal = is_boolean && (name == "true" || name == "false");
+        CompletionItem {
+            label: name.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            sort_text: is_literal.then(|| format!("0{name}")),
+            preselect: (is_literal && name == "true").then_some(true),
+            ..Default::default()
+        }
     });
 
     variable_items
@@ -185,11 +673,24 @@ pub async fn completion(
 
     // Handle string completions.
     if let Some(prefix) = get_prefix_string_for_completion(&current_file.ast, offset) {
-        // Target completions are not supported yet.
-        if prefix.starts_with('/')
-            || prefix.starts_with(':')
-            || prefix.starts_with(std::path::MAIN_SEPARATOR)
-        {
+        if prefix.starts_with(':') {
+            let items = build_target_completions(&current_file, offset, prefix);
+            return Ok(Some(CompletionResponse::Array(items)));
+        }
+        if prefix.starts_with("//") && is_import_string(&current_file.ast, offset) {
+            let common_dirs = context.client.configurations().await.import.common_dirs;
+            let items = build_import_completions(&current_file, offset, prefix, &common_dirs);
+            return Ok(Some(CompletionResponse::Array(items)));
+        }
+        if prefix.contains(':') {
+            let items = build_label_target_completions(context, &current_file, offset, prefix);
+            return Ok(items.map(CompletionResponse::Array));
+        }
+        if prefix.starts_with("//") {
+            let items = build_label_dir_completions(&current_file, prefix);
+            return Ok(items.map(CompletionResponse::Array));
+        }
+        if prefix.starts_with('/') || prefix.starts_with(std::path::MAIN_SEPARATOR) {
             return Ok(None);
         }
         if let Some(items) = build_filename_completions(&current_file.document.path, prefix) {
@@ -199,6 +700,865 @@ pub async fn completion(
     }
 
     // Handle identifier completions.
-    let items = identifier_completions(&current_file, offset);
+    let mut items = scaffold_completions(&current_file, offset);
+    if is_in_deps_list(&current_file.ast, offset) {
+        items.extend(build_quoted_target_completions(&current_file, offset));
+    }
+    items.extend(identifier_completions(&current_file, offset));
+    if is_defined_argument(&current_file.document.data, offset) {
+        return Ok(Some(CompletionResponse::Array(items)));
+    }
+    items.extend(template_import_completions(
+        context,
+        &current_file,
+        offset,
+        params.text_document_position.position,
+    ));
     Ok(Some(CompletionResponse::Array(items)))
 }
+
+fn template_import_completions(
+    context: &RequestContext,
+    current_file: &AnalyzedFile,
+    offset: usize,
+    position: Position,
+) -> Vec<CompletionItem> {
+    if is_after_dot(&current_file.document.data, offset) {
+        return Vec::new();
+    }
+
+    // Offer templates defined in indexed .gni files that are not in scope yet.
+    let templates = current_file.templates_at(offset);
+    context
+        .analyzer
+        .cached_files(&current_file.workspace_root)
+        .into_iter()
+        .filter(|file| {
+            file.document
+                .path
+                .extension()
+                .is_some_and(|ext| ext == "gni")
+        })
+        .flat_map(|file| {
+            let import = format_path(&file.document.path, &current_file.workspace_root);
+            file.environment
+                .templates
+                .locals()
+                .values()
+                .filter(|template| !templates.contains(template.name))
+                .map(|template| {
+                    let paragraphs = format_template_help(template, &current_file.workspace_root);
+                    CompletionItem {
+                        label: template.name.to_string(),
+                        label_details: label_details("template"),
+                        kind: Some(CompletionItemKind::FUNCTION),
+                        documentation: Some(Documentation::MarkupContent(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value: paragraphs.join("\n\n"),
+                        })),
+                        data: CompletionData {
+                            path: current_file.document.path.clone(),
+                            position,
+                            kind: CompletionSymbolKind::Template,
+                            name: template.name.to_string(),
+                            location: template_location(template),
+                            import: Some(import.clone()),
+                        }
+                        .to_value(),
+                        ..Default::default()
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .sorted_by(|a, b| a.label.cmp(&b.label))
+        .collect()
+}
+
+fn compute_import_edit(current_file: &AnalyzedFile, import: &str) -> TextEdit {
+    let line_index = &current_file.document.line_index;
+    let last_import =
+        current_file
+            .ast
+            .statements
+            .iter()
+            .rev()
+            .find_map(|statement| match statement {
+                Statement::Call(call) if call.function.name == IMPORT => Some(call),
+                _ => None,
+            });
+
+    // Insert after the last top-level import if any, or before the first
+    // statement otherwise.
+    let (position, new_text) = if let Some(call) = last_import {
+        let end = line_index.position(call.span.end());
+        (
+            Position::new(end.line + 1, 0),
+            format!("import(\"{import}\")\n"),
+        )
+    } else {
+        let start = current_file
+            .ast
+            .statements
+            .first()
+            .map(|statement| line_index.position(statement.span().start()))
+            .unwrap_or_default();
+        (
+            Position::new(start.line, 0),
+            format!("import(\"{import}\")\n\n"),
+        )
+    };
+
+    TextEdit {
+        range: Range::new(position, position),
+        new_text,
+    }
+}
+
+pub async fn completion_resolve(
+    context: &RequestContext,
+    mut item: CompletionItem,
+) -> Result<CompletionItem> {
+    let Some(data) = item.data.take() else {
+        return Ok(item);
+    };
+    let Ok(data) = serde_json::from_value::<CompletionData>(data) else {
+        return Err(Error::General("corrupted completion item data".to_string()));
+    };
+    // Only variables need documentation and only templates not in scope yet
+    // need more edits.
+    let needs_documentation =
+        data.kind == CompletionSymbolKind::Variable && item.documentation.is_none();
+    if !needs_documentation && data.import.is_none() {
+        return Ok(item);
+    }
+
+    let current_file =
+        context
+            .analyzer
+            .analyze(&data.path, &context.finder, context.request_time)?;
+    let offset = current_file
+        .document
+        .line_index
+        .offset(data.position)
+        .unwrap_or(0);
+
+    if needs_documentation {
+        if let Some(variable) = current_file.variables_at(offset).get(&data.name) {
+            let paragraphs = format_variable_help(variable, &current_file.workspace_root);
+            item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: paragraphs.join("\n\n"),
+            }));
+        }
+    }
+
+    let Some(import) = data.import else {
+        return Ok(item);
+    };
+
+    // The template may have been imported since the completion was requested.
+    if current_file.templates_at(offset).contains(&data.name) {
+        return Ok(item);
+    }
+
+    item.additional_text_edits = Some(vec![compute_import_edit(&current_file, &import)]);
+    Ok(item)
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        PartialResultParams, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+        WorkDoneProgressParams,
+    };
+
+    use crate::common::testutils::{testdata, TestWorkspace};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_template_auto_import() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/BUILD.gn");
+
+        // Populate the cache as the background indexing would do.
+        context
+            .analyzer
+            .analyze_shallow(
+                &testdata("workspaces/completion/build/unimported.gni"),
+                &context.finder,
+                context.request_time,
+            )
+            .unwrap();
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(16, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        // Templates in scope need no import.
+        let imported = items
+            .iter()
+            .find(|item| item.label == "imported_template")
+            .unwrap();
+        let data: CompletionData = serde_json::from_value(imported.data.clone().unwrap()).unwrap();
+        assert_eq!(data.import, None);
+
+        let unimported = items
+            .iter()
+            .find(|item| item.label == "unimported_template")
+            .unwrap()
+            .clone();
+        let resolved = completion_resolve(&context, unimported).await.unwrap();
+        assert_eq!(
+            resolved.additional_text_edits,
+            Some(vec![TextEdit {
+                range: Range::new(Position::new(16, 0), Position::new(16, 0)),
+                new_text: "import(\"//build/unimported.gni\")\n".to_string(),
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_completion_data_round_trip() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/data.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "template(\"foo\") {\n}\n\nfoo_enabled = true\n",
+            1,
+        );
+        let uri = Url::from_file_path(&path).unwrap();
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(4, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        let item = items
+            .iter()
+            .find(|item| item.label == "foo_enabled")
+            .unwrap()
+            .clone();
+        let data: CompletionData = serde_json::from_value(item.data.clone().unwrap()).unwrap();
+        assert_eq!(
+            data,
+            CompletionData {
+                path: path.clone(),
+                position: Position::new(4, 0),
+                kind: CompletionSymbolKind::Variable,
+                name: "foo_enabled".to_string(),
+                location: Some(Location {
+                    uri: uri.clone(),
+                    range: Range::new(Position::new(3, 0), Position::new(3, 11)),
+                }),
+                import: None,
+            }
+        );
+
+        // The payload survives a JSON round trip as clients send it back.
+        let json = serde_json::to_string(&item).unwrap();
+        let item: CompletionItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            serde_json::from_value::<CompletionData>(item.data.clone().unwrap()).unwrap(),
+            data
+        );
+        assert_eq!(item.documentation, None);
+        let resolved = completion_resolve(&context, item).await.unwrap();
+        assert_eq!(resolved.additional_text_edits, None);
+        let Some(Documentation::MarkupContent(documentation)) = resolved.documentation else {
+            panic!("documentation not resolved");
+        };
+        assert!(documentation.value.contains("foo_enabled = true"));
+
+        let template = items.iter().find(|item| item.label == "foo").unwrap();
+        let data: CompletionData = serde_json::from_value(template.data.clone().unwrap()).unwrap();
+        assert_eq!(data.kind, CompletionSymbolKind::Template);
+        assert_eq!(data.location.unwrap().range.start, Position::new(0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_boolean_completion() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/boolean.gn");
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "enable_foo = ", 1);
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(0, 13),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        // `enable_foo` is a boolean declared in BUILDCONFIG.gn, so boolean
+        // literals are ranked first.
+        let first_two: Vec<&str> = items
+            .iter()
+            .sorted_by_key(|item| item.sort_text.as_ref().unwrap_or(&item.label))
+            .take(2)
+            .map(|item| item.label.as_str())
+            .collect();
+        assert_eq!(first_two, ["false", "true"]);
+    }
+
+    #[tokio::test]
+    async fn test_label_details() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/BUILD.gn");
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(16, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        let description = |label: &str| {
+            items
+                .iter()
+                .find(|item| item.label == label)
+                .unwrap()
+                .label_details
+                .as_ref()
+                .and_then(|details| details.description.clone())
+        };
+        assert_eq!(description("enable_foo"), Some("bool".to_string()));
+        assert_eq!(
+            description("imported_template"),
+            Some("template".to_string())
+        );
+        assert_eq!(description("group"), Some("builtin".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_sibling_target_completion() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/siblings.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            r#"static_library("first") {}
+source_set("second") {}
+executable("third") {
+  deps = [ ":" ]
+}
+"#,
+            1,
+        );
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(3, 13),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, [":first", ":second"]);
+        assert_eq!(
+            items[0].text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(3, 12), Position::new(3, 13)),
+                new_text: ":first".to_string(),
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_partial_sibling_target_completion() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/siblings.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            r#"static_library("first") {}
+source_set("second") {}
+executable("third") {
+  deps = [ ":se" ]
+}
+"#,
+            1,
+        );
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(3, 15),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        // Clients filter candidates by the typed label, which the edits
+        // replace entirely.
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, [":first", ":second"]);
+        assert_eq!(
+            items[1].text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(3, 12), Position::new(3, 15)),
+                new_text: ":second".to_string(),
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_quoted_target_completion() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/siblings.gn");
+        let complete = |data: &'static str, revision: i32, position: Position| {
+            context
+                .storage
+                .lock()
+                .unwrap()
+                .load_to_memory(&path, data, revision);
+            let params = CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position,
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+                context: None,
+            };
+            let context = context.clone();
+            async move {
+                let Some(CompletionResponse::Array(items)) =
+                    completion(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected completion response");
+                };
+                items.into_iter().find(|item| item.label == ":second")
+            }
+        };
+
+        // Labels typed as identifiers in a multi-line list are quoted and
+        // followed by a trailing comma.
+        let item = complete(
+            r#"static_library("first") {}
+source_set("second") {}
+executable("third") {
+  deps = [
+    ":first",
+    se
+  ]
+}
+"#,
+            1,
+            Position::new(5, 6),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            item.text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(5, 4), Position::new(5, 6)),
+                new_text: "\":second\",".to_string(),
+            }))
+        );
+        assert_eq!(item.filter_text, Some("second".to_string()));
+        assert_eq!(item.additional_text_edits, None);
+
+        // The previous item gets a comma, right after it if it is on another
+        // line.
+        let item = complete(
+            r#"static_library("first") {}
+source_set("second") {}
+executable("third") {
+  deps = [ ":first" ]
+}
+"#,
+            2,
+            Position::new(3, 19),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            item.text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(3, 19), Position::new(3, 19)),
+                new_text: ", \":second\"".to_string(),
+            }))
+        );
+        let item = complete(
+            r#"static_library("first") {}
+source_set("second") {}
+executable("third") {
+  deps = [
+    ":first"
+    
+  ]
+}
+"#,
+            3,
+            Position::new(5, 4),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            item.text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(5, 4), Position::new(5, 4)),
+                new_text: "\":second\",".to_string(),
+            }))
+        );
+        assert_eq!(
+            item.additional_text_edits,
+            Some(vec![TextEdit {
+                range: Range::new(Position::new(4, 12), Position::new(4, 12)),
+                new_text: ",".to_string(),
+            }])
+        );
+
+        // Lists other than dependencies do not take labels.
+        let item = complete(
+            r#"static_library("first") {}
+source_set("second") {}
+executable("third") {
+  sources = [
+    se
+  ]
+}
+"#,
+            4,
+            Position::new(4, 6),
+        )
+        .await;
+        assert_eq!(item, None);
+    }
+
+    #[tokio::test]
+    async fn test_label_completion() {
+        let workspace = TestWorkspace::new([
+            (
+                "foo/bar/BUILD.gn",
+                "static_library(\"bar\") {\n}\n\nsource_set(\"bar_test\") {\n}\n",
+            ),
+            ("foo/baz/BUILD.gn", ""),
+            ("foo/qux.gni", ""),
+            ("app/BUILD.gn", "deps = [ \"//foo/ba\" ]\n"),
+            ("tools/BUILD.gn", "deps = [ \"//foo/bar:b\" ]\n"),
+        ]);
+        let context = RequestContext::new_for_testing();
+        let complete = |name: &str, position: Position| {
+            let params = CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(workspace.path(name)).unwrap(),
+                    },
+                    position,
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+                context: None,
+            };
+            let context = context.clone();
+            async move {
+                let Some(CompletionResponse::Array(items)) =
+                    completion(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected completion response");
+                };
+                items
+            }
+        };
+
+        // Only directories are completed after `//`.
+        let items = complete("app/BUILD.gn", Position::new(0, 18)).await;
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["bar/", "baz/"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::FOLDER));
+
+        // Targets are completed after `:`, in the order of definitions.
+        let items = complete("tools/BUILD.gn", Position::new(0, 21)).await;
+        let labels: Vec<&str> = items.iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["bar", "bar_test"]);
+        assert_eq!(items[1].kind, Some(CompletionItemKind::CONSTANT));
+        assert_eq!(items[1].detail, Some("source_set".to_string()));
+        assert_eq!(
+            items[1].text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit {
+                range: Range::new(Position::new(0, 20), Position::new(0, 21)),
+                new_text: "bar_test".to_string(),
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_defined_argument_completion() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/defined.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "enable_foo = true\nif (defined(en)) {\n}\n",
+            1,
+        );
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(1, 14),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        // Only variables are offered, not functions, targets or keywords.
+        assert!(items.iter().any(|item| item.label == "enable_foo"));
+        assert!(items
+            .iter()
+            .all(|item| item.kind == Some(CompletionItemKind::VARIABLE)));
+    }
+
+    #[tokio::test]
+    async fn test_directory_commit_characters() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/paths.gn");
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "sources = [ \"\" ]\n", 1);
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(0, 13),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        let directory = items
+            .iter()
+            .find(|item| item.label == format!("build{}", std::path::MAIN_SEPARATOR))
+            .unwrap();
+        assert_eq!(directory.insert_text.as_deref(), Some("build"));
+        assert_eq!(
+            directory.commit_characters,
+            Some(vec![
+                std::path::MAIN_SEPARATOR_STR.to_string(),
+                ":".to_string()
+            ])
+        );
+
+        let file = items.iter().find(|item| item.label == "BUILD.gn").unwrap();
+        assert_eq!(file.commit_characters, None);
+    }
+
+    #[tokio::test]
+    async fn test_import_completion_proximity() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/import_completion/foo/BUILD.gn");
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "import(\"//\")\n", 1);
+
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(0, 10),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) = completion(&context, params).await.unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        // The sibling file outranks the distant one despite sorting later
+        // alphabetically.
+        let items: Vec<_> = items
+            .into_iter()
+            .sorted_by(|a, b| a.sort_text.cmp(&b.sort_text))
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                "//BUILDCONFIG.gn",
+                "//foo/sibling.gni",
+                "//bar/baz/distant.gni"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_completion_common_files() {
+        let workspace = TestWorkspace::new([
+            ("foo/BUILD.gn", "import(\"//\")\n"),
+            ("foo/sibling.gni", ""),
+            ("build/config/compiler.gni", ""),
+        ]);
+        let path = workspace.path("foo/BUILD.gn");
+        let params = CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(0, 10),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        let Some(CompletionResponse::Array(items)) =
+            completion(&RequestContext::new_for_testing(), params)
+                .await
+                .unwrap()
+        else {
+            panic!("unexpected completion response");
+        };
+
+        // BUILDCONFIG.gn and files under //build/config outrank the sibling.
+        let items: Vec<_> = items
+            .into_iter()
+            .sorted_by(|a, b| a.sort_text.cmp(&b.sort_text))
+            .map(|item| item.label)
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                "//BUILDCONFIG.gn",
+                "//build/config/compiler.gni",
+                "//foo/sibling.gni"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scaffold_completion() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/completion/new/BUILD.gn");
+        // Each call loads a new revision so that analysis is not reused.
+        let scaffolds = |revision: i32, data: &str, position: Position| {
+            context
+                .storage
+                .lock()
+                .unwrap()
+                .load_to_memory(&path, data, revision);
+            let params = CompletionParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position,
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+                context: None,
+            };
+            let context = context.clone();
+            async move {
+                let Some(CompletionResponse::Array(items)) =
+                    completion(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected completion response");
+                };
+                items
+                    .into_iter()
+                    .filter(|item| item.kind == Some(CompletionItemKind::SNIPPET))
+                    .map(|item| item.label)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Empty files, even with a partially typed word, offer scaffolds.
+        assert_eq!(
+            scaffolds(1, "", Position::new(0, 0)).await,
+            ["import", "executable", "group"]
+        );
+        assert_eq!(
+            scaffolds(2, "# Comment\nex", Position::new(1, 2)).await,
+            ["import", "executable", "group"]
+        );
+
+        // Files with statements do not.
+        assert!(scaffolds(3, "group(\"foo\") {}\n", Position::new(1, 0))
+            .await
+            .is_empty());
+
+        // Emptiness follows the latest revision within the same request.
+        assert_eq!(
+            scaffolds(4, "", Position::new(0, 0)).await,
+            ["import", "executable", "group"]
+        );
+    }
+}
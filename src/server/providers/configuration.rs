@@ -12,6 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::time::Duration;
+
 use futures::{future::join_all, FutureExt};
 use tower_lsp::lsp_types::{DidChangeConfigurationParams, Url};
 
@@ -25,6 +27,28 @@ pub async fn did_change_configuration(
 ) {
     let config = context.client.configurations().await;
 
+    context
+        .analyzer
+        .set_verify_interval(Duration::from_millis(config.cache.check_interval_ms));
+    context.analyzer.set_cache_capacity(config.cache.max_files);
+    if config.cache.freeze {
+        context.analyzer.freeze();
+    } else {
+        context.analyzer.thaw();
+    }
+    context
+        .analyzer
+        .set_manual_root(config.workspace.manual_root);
+    context
+        .analyzer
+        .set_args_file_patterns(config.workspace.args_file_patterns);
+    context
+        .analyzer
+        .set_label_search_roots(config.workspace.label_search_roots);
+    context
+        .analyzer
+        .set_case_insensitive_paths(config.workspace.case_insensitive_paths);
+
     let documents = context.storage.lock().unwrap().memory_docs();
 
     let mut tasks = Vec::new();
@@ -52,3 +76,52 @@ pub async fn did_change_configuration(
 
     join_all(tasks).await;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::common::{
+        config::{Configurations, ExperimentalConfigurations},
+        testutils::testdata,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_toggle_undefined_variable_analysis() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/smoke/undefined.gn");
+        let uri = Url::from_file_path(&path).unwrap();
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "print(undefined_value)\n", 1);
+
+        let toggle = |enabled: bool| {
+            context.client.set_configurations(Configurations {
+                experimental: ExperimentalConfigurations {
+                    undefined_variable_analysis: enabled,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            did_change_configuration(
+                &context,
+                DidChangeConfigurationParams {
+                    settings: serde_json::Value::Null,
+                },
+            )
+        };
+        let last_diagnostics = || {
+            let (last_uri, diagnostics) = context.client.published_diagnostics().pop().unwrap();
+            assert_eq!(last_uri, uri);
+            diagnostics
+        };
+
+        // Open documents are re-diagnosed as soon as the flag changes.
+        toggle(true).await;
+        assert_eq!(last_diagnostics().len(), 1);
+        toggle(false).await;
+        assert!(last_diagnostics().is_empty());
+    }
+}
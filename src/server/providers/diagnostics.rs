@@ -12,12 +12,39 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tower_lsp::lsp_types::Url;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Url};
 
 use crate::{
-    common::storage::DocumentVersion, diagnostics::compute_diagnostics, server::RequestContext,
+    analyzer::DotGnError,
+    common::{error::Error, storage::DocumentVersion},
+    diagnostics::compute_diagnostics,
+    server::RequestContext,
 };
 
+/// Reports an error evaluating `.gn` on the `.gn` file itself, since it breaks
+/// analysis of every file in the workspace.
+async fn publish_dot_gn_error(context: &RequestContext, error: &DotGnError) {
+    let Ok(uri) = Url::from_file_path(&error.path) else {
+        return;
+    };
+    let diagnostic = Diagnostic {
+        range: error.range.unwrap_or_default(),
+        severity: Some(DiagnosticSeverity::ERROR),
+        message: error.message.clone(),
+        ..Default::default()
+    };
+    context.diagnosed.lock().unwrap().insert(uri.clone());
+    context
+        .client
+        .publish_diagnostics(uri, vec![diagnostic], None)
+        .await;
+}
+
 pub async fn publish_diagnostics(context: &RequestContext, uri: &Url) {
     let Ok(path) = uri.to_file_path() else {
         return;
@@ -28,14 +55,52 @@ pub async fn publish_diagnostics(context: &RequestContext, uri: &Url) {
         return;
     }
 
-    let Ok(current_file) = context
+    let current_file = match context
         .analyzer
         .analyze(&path, &context.finder, context.request_time)
-    else {
-        return;
+    {
+        Ok(current_file) => current_file,
+        Err(Error::DotGn(error)) => {
+            publish_dot_gn_error(context, &error).await;
+            return;
+        }
+        Err(_) => return,
     };
 
-    let diagnostics = compute_diagnostics(&current_file.analyzed_root, &config);
+    // Record files imported directly or indirectly so that importers can be
+    // re-diagnosed when imported files change.
+    {
+        let mut importers = context.importers.lock().unwrap();
+        for files in importers.values_mut() {
+            files.remove(&path);
+        }
+        for imported_path in current_file.node.dependencies() {
+            importers
+                .entry(imported_path)
+                .or_default()
+                .insert(path.clone());
+        }
+    }
+
+    let mut diagnostics = compute_diagnostics(
+        &current_file,
+        &config,
+        &mut context.diagnostics_cache.lock().unwrap(),
+    );
+    if let Some(gen_diagnostics) = context.gen_diagnostics.lock().unwrap().get(uri) {
+        diagnostics.extend(gen_diagnostics.iter().cloned());
+    }
+
+    // Track URIs with outstanding diagnostics so that they can be cleared
+    // when the file goes away.
+    {
+        let mut diagnosed = context.diagnosed.lock().unwrap();
+        if diagnostics.is_empty() {
+            diagnosed.remove(uri);
+        } else {
+            diagnosed.insert(uri.clone());
+        }
+    }
 
     let version = if let DocumentVersion::InMemory { revision } = current_file.document.version {
         Some(revision)
@@ -49,9 +114,122 @@ pub async fn publish_diagnostics(context: &RequestContext, uri: &Url) {
         .await;
 }
 
+/// Republishes diagnostics of open files importing the given file.
+pub async fn publish_importer_diagnostics(context: &RequestContext, path: &Path) {
+    let open_importers: Vec<Url> = {
+        let importers = context.importers.lock().unwrap();
+        let Some(files) = importers.get(path) else {
+            return;
+        };
+        let memory_paths: BTreeSet<PathBuf> = context
+            .storage
+            .lock()
+            .unwrap()
+            .memory_docs()
+            .into_iter()
+            .map(|document| document.path.clone())
+            .collect();
+        files
+            .iter()
+            .filter(|file| memory_paths.contains(*file))
+            .filter_map(|file| Url::from_file_path(file).ok())
+            .collect()
+    };
+    for uri in open_importers {
+        publish_diagnostics(context, &uri).await;
+    }
+}
+
 pub async fn unpublish_diagnostics(context: &RequestContext, uri: &Url) {
+    context.diagnosed.lock().unwrap().remove(uri);
+    if let Ok(path) = uri.to_file_path() {
+        context.diagnostics_cache.lock().unwrap().remove(&path);
+    }
     context
         .client
         .publish_diagnostics(uri.clone(), Vec::new(), None)
         .await;
 }
+
+pub async fn unpublish_stale_diagnostics(context: &RequestContext, uris: &[Url]) {
+    let stale_uris: Vec<Url> = {
+        let diagnosed = context.diagnosed.lock().unwrap();
+        uris.iter()
+            .filter(|uri| diagnosed.contains(uri))
+            .cloned()
+            .collect()
+    };
+    for uri in stale_uris {
+        unpublish_diagnostics(context, &uri).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Range};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dot_gn_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(
+            root.join(".gn"),
+            "buildconfig = \"//BUILDCONFIG.gn\"\nbuildconfig = \"//other/BUILDCONFIG.gn\"  # again\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+        std::fs::write(root.join("BUILD.gn"), "").unwrap();
+
+        let context = RequestContext::new_for_testing();
+        publish_diagnostics(
+            &context,
+            &Url::from_file_path(root.join("BUILD.gn")).unwrap(),
+        )
+        .await;
+
+        let (uri, diagnostics) = context.client.published_diagnostics().pop().unwrap();
+        assert_eq!(uri, Url::from_file_path(root.join(".gn")).unwrap());
+        assert_eq!(diagnostics.len(), 1);
+        // The range ends at the value, excluding the trailing comment.
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(1, 0), Position::new(1, 38))
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "buildconfig is assigned multiple times"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_gen_diagnostics() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join(".gn"), "buildconfig = \"//BUILDCONFIG.gn\"\n").unwrap();
+        std::fs::write(root.join("BUILDCONFIG.gn"), "").unwrap();
+        let path = root.join("BUILD.gn");
+        std::fs::write(&path, "").unwrap();
+        let uri = Url::from_file_path(&path).unwrap();
+
+        let context = RequestContext::new_for_testing();
+        let gen_diagnostic = Diagnostic {
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("gn gen".to_string()),
+            message: "Undefined identifier".to_string(),
+            ..Default::default()
+        };
+        context
+            .gen_diagnostics
+            .lock()
+            .unwrap()
+            .insert(uri.clone(), vec![gen_diagnostic.clone()]);
+        publish_diagnostics(&context, &uri).await;
+
+        // Analysis diagnostics do not clobber errors reported by gn gen.
+        let (published_uri, diagnostics) = context.client.published_diagnostics().pop().unwrap();
+        assert_eq!(published_uri, uri);
+        assert_eq!(diagnostics, vec![gen_diagnostic]);
+    }
+}
@@ -12,13 +12,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::{collections::BTreeMap, path::PathBuf};
+
 use tower_lsp::lsp_types::{
-    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidChangeTextDocumentParams, DidChangeWatchedFilesParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, FileChangeType, MessageType, TextDocumentContentChangeEvent, Url,
+};
+
+use crate::{
+    common::{storage::DocumentVersion, utils::LineIndex},
+    server::RequestContext,
 };
 
-use crate::server::RequestContext;
+use super::diagnostics::{
+    publish_diagnostics, publish_importer_diagnostics, unpublish_diagnostics,
+    unpublish_stale_diagnostics,
+};
 
-use super::diagnostics::{publish_diagnostics, unpublish_diagnostics};
+/// Incremental changes of open files received ahead of the versions they
+/// apply to, keyed by file and version.
+pub type PendingChanges = BTreeMap<PathBuf, BTreeMap<i32, Vec<TextDocumentContentChangeEvent>>>;
 
 pub async fn did_open(context: &RequestContext, params: DidOpenTextDocumentParams) {
     let Ok(path) = params.text_document.uri.to_file_path() else {
@@ -30,25 +43,105 @@ pub async fn did_open(context: &RequestContext, params: DidOpenTextDocumentParam
         &params.text_document.text,
         params.text_document.version,
     );
+    context.pending_changes.lock().unwrap().remove(&path);
 
     publish_diagnostics(context, &params.text_document.uri).await;
 }
 
+/// Applies content changes in order, each range referring to the text after
+/// the preceding changes. A change without a range replaces the whole text.
+/// Characters past the end of a line refer to the end of the line. Returns
+/// `None` if a range is out of the text.
+fn apply_changes(text: &str, changes: &[TextDocumentContentChangeEvent]) -> Option<String> {
+    let mut text = text.to_string();
+    for change in changes {
+        let Some(range) = change.range else {
+            text = change.text.clone();
+            continue;
+        };
+        let line_index = LineIndex::new(&text);
+        let start = line_index.clamped_offset(range.start)?;
+        let end = line_index.clamped_offset(range.end)?;
+        if start > end {
+            return None;
+        }
+        text.replace_range(start..end, &change.text);
+    }
+    Some(text)
+}
+
 pub async fn did_change(context: &RequestContext, params: DidChangeTextDocumentParams) {
     let Ok(path) = params.text_document.uri.to_file_path() else {
         return;
     };
-    let Some(change) = params.content_changes.first() else {
+    if params.content_changes.is_empty() {
         return;
-    };
+    }
 
-    context.storage.lock().unwrap().load_to_memory(
-        &path,
-        &change.text,
-        params.text_document.version,
-    );
+    let applied = {
+        let mut storage = context.storage.lock().unwrap();
+        let mut pending_changes = context.pending_changes.lock().unwrap();
+        let mut version = params.text_document.version;
+        let mut changes = params.content_changes;
+        if let DocumentVersion::InMemory { revision } = storage.read_version(&path) {
+            // Notifications may be handled out of order. Drop changes older
+            // than the loaded content so that stale text never overwrites
+            // newer text.
+            if version <= revision {
+                return;
+            }
+            // Ranges refer to the text of the preceding version. Hold changes
+            // arriving ahead of it until it is loaded.
+            if version != revision + 1 && changes[0].range.is_some() {
+                pending_changes
+                    .entry(path)
+                    .or_default()
+                    .insert(version, changes);
+                return;
+            }
+        }
+
+        let pending = pending_changes.entry(path.clone()).or_default();
+        let mut text = storage.read(&path).data.to_string();
+        let applied = loop {
+            let Some(new_text) = apply_changes(&text, &changes) else {
+                break false;
+            };
+            storage.load_to_memory(&path, &new_text, version);
+            text = new_text;
+            match pending.remove(&(version + 1)) {
+                Some(next_changes) => {
+                    version += 1;
+                    changes = next_changes;
+                }
+                None => break true,
+            }
+        };
+        pending.retain(|&pending_version, _| pending_version > version);
+        if pending.is_empty() {
+            pending_changes.remove(&path);
+        }
+        applied
+    };
+    if !applied {
+        context
+            .client
+            .log_message(
+                MessageType::WARNING,
+                format!(
+                    "Ignored out-of-range changes to {}; the document may be out of sync",
+                    path.display()
+                ),
+            )
+            .await;
+        return;
+    }
 
     publish_diagnostics(context, &params.text_document.uri).await;
+
+    if path.extension().is_some_and(|ext| ext == "gni") {
+        publish_importer_diagnostics(context, &path).await;
+    }
 }
 
 pub async fn did_close(context: &RequestContext, params: DidCloseTextDocumentParams) {
@@ -59,4 +152,360 @@ pub async fn did_close(context: &RequestContext, params: DidCloseTextDocumentPar
     unpublish_diagnostics(context, &params.text_document.uri).await;
 
     context.storage.lock().unwrap().unload_from_memory(&path);
+    context.pending_changes.lock().unwrap().remove(&path);
+}
+
+pub async fn did_change_watched_files(
+    context: &RequestContext,
+    params: DidChangeWatchedFilesParams,
+) {
+    let deleted_uris: Vec<Url> = params
+        .changes
+        .into_iter()
+        .filter(|event| event.typ == FileChangeType::DELETED)
+        .map(|event| event.uri)
+        .collect();
+    unpublish_stale_diagnostics(context, &deleted_uris).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        Position, Range, TextDocumentIdentifier, TextDocumentItem, VersionedTextDocumentIdentifier,
+    };
+
+    use crate::common::{
+        config::{Configurations, ExperimentalConfigurations},
+        testutils::{testdata, TestWorkspace},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_did_close_clears_diagnostics() {
+        let context = RequestContext::new_for_testing();
+        let uri = Url::from_file_path(testdata("workspaces/hover/BUILD.gn")).unwrap();
+
+        did_open(
+            &context,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "gn".to_string(),
+                    version: 1,
+                    text: "a = [1, 2 3]\n".to_string(),
+                },
+            },
+        )
+        .await;
+        assert!(context.diagnosed.lock().unwrap().contains(&uri));
+
+        did_close(
+            &context,
+            DidCloseTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+            },
+        )
+        .await;
+        assert!(!context.diagnosed.lock().unwrap().contains(&uri));
+
+        let (last_uri, last_diagnostics) = context.client.published_diagnostics().pop().unwrap();
+        assert_eq!(last_uri, uri);
+        assert!(last_diagnostics.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_did_change_imported_file() {
+        let context = RequestContext::new_for_testing();
+        context.client.set_configurations(Configurations {
+            experimental: ExperimentalConfigurations {
+                undefined_variable_analysis: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let build_path = testdata("workspaces/importers/BUILD.gn");
+        let gni_path = testdata("workspaces/importers/values.gni");
+        let build_uri = Url::from_file_path(&build_path).unwrap();
+        let gni_uri = Url::from_file_path(&gni_path).unwrap();
+
+        for (uri, path) in [(&build_uri, &build_path), (&gni_uri, &gni_path)] {
+            did_open(
+                &context,
+                DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem {
+                        uri: uri.clone(),
+                        language_id: "gn".to_string(),
+                        version: 1,
+                        text: std::fs::read_to_string(path).unwrap(),
+                    },
+                },
+            )
+            .await;
+        }
+        assert!(!context.diagnosed.lock().unwrap().contains(&build_uri));
+
+        // Removing the definition from the imported file flags the importer.
+        did_change(
+            &context,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: gni_uri.clone(),
+                    version: 2,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "other_value = 1\n".to_string(),
+                }],
+            },
+        )
+        .await;
+        assert!(context.diagnosed.lock().unwrap().contains(&build_uri));
+
+        let (last_uri, last_diagnostics) = context.client.published_diagnostics().pop().unwrap();
+        assert_eq!(last_uri, build_uri);
+        assert!(!last_diagnostics.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_did_change_indirectly_imported_file() {
+        let workspace = TestWorkspace::new([
+            ("BUILD.gn", "import(\"//outer.gni\")\n\nprint(value)\n"),
+            ("outer.gni", "import(\"//inner.gni\")\n"),
+            ("inner.gni", "value = 1\n"),
+        ]);
+        let context = RequestContext::new_for_testing();
+        context.client.set_configurations(Configurations {
+            experimental: ExperimentalConfigurations {
+                undefined_variable_analysis: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let build_uri = Url::from_file_path(workspace.path("BUILD.gn")).unwrap();
+        let inner_uri = Url::from_file_path(workspace.path("inner.gni")).unwrap();
+
+        for (uri, text) in [
+            (&build_uri, "import(\"//outer.gni\")\n\nprint(value)\n"),
+            (&inner_uri, "value = 1\n"),
+        ] {
+            did_open(
+                &context,
+                DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem {
+                        uri: uri.clone(),
+                        language_id: "gn".to_string(),
+                        version: 1,
+                        text: text.to_string(),
+                    },
+                },
+            )
+            .await;
+        }
+        assert!(!context.diagnosed.lock().unwrap().contains(&build_uri));
+
+        // Files importing the changed file through another import are
+        // re-diagnosed too.
+        did_change(
+            &context,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: inner_uri.clone(),
+                    version: 2,
+                },
+                content_changes: vec![TextDocumentContentChangeEvent {
+                    range: None,
+                    range_length: None,
+                    text: "other_value = 1\n".to_string(),
+                }],
+            },
+        )
+        .await;
+        assert!(context.diagnosed.lock().unwrap().contains(&build_uri));
+    }
+
+    #[tokio::test]
+    async fn test_did_change_out_of_order() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/BUILD.gn");
+        let uri = Url::from_file_path(&path).unwrap();
+
+        did_open(
+            &context,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "gn".to_string(),
+                    version: 1,
+                    text: "a = 1\n".to_string(),
+                },
+            },
+        )
+        .await;
+
+        for (version, text) in [(3, "b = 1\n"), (2, "c = 1\n")] {
+            did_change(
+                &context,
+                DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier {
+                        uri: uri.clone(),
+                        version,
+                    },
+                    content_changes: vec![TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text: text.to_string(),
+                    }],
+                },
+            )
+            .await;
+        }
+
+        // The older change arriving late is ignored.
+        let document = context.storage.lock().unwrap().read(&path);
+        assert_eq!(&*document.data, "b = 1\n");
+        assert_eq!(document.version, DocumentVersion::InMemory { revision: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_did_change_incremental() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/BUILD.gn");
+        let uri = Url::from_file_path(&path).unwrap();
+
+        did_open(
+            &context,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "gn".to_string(),
+                    version: 1,
+                    text: "a = 1\nb = \"\u{e9}\u{e9}\"\n".to_string(),
+                },
+            },
+        )
+        .await;
+
+        let change = |start: Position, end: Position, text: &str| TextDocumentContentChangeEvent {
+            range: Some(Range::new(start, end)),
+            range_length: None,
+            text: text.to_string(),
+        };
+        did_change(
+            &context,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 2,
+                },
+                // Later ranges refer to the text after earlier changes, and
+                // characters are counted in UTF-16 code units.
+                content_changes: vec![
+                    change(Position::new(0, 4), Position::new(0, 5), "42"),
+                    change(Position::new(1, 6), Position::new(1, 7), "e"),
+                    change(Position::new(2, 0), Position::new(2, 0), "c = a\n"),
+                ],
+            },
+        )
+        .await;
+
+        let document = context.storage.lock().unwrap().read(&path);
+        assert_eq!(&*document.data, "a = 42\nb = \"\u{e9}e\"\nc = a\n");
+        assert_eq!(document.version, DocumentVersion::InMemory { revision: 2 });
+
+        // Changes out of the text are dropped as a whole.
+        did_change(
+            &context,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 3,
+                },
+                content_changes: vec![
+                    change(Position::new(0, 0), Position::new(0, 1), "x"),
+                    change(Position::new(9, 0), Position::new(9, 0), "y"),
+                ],
+            },
+        )
+        .await;
+        let document = context.storage.lock().unwrap().read(&path);
+        assert_eq!(&*document.data, "a = 42\nb = \"\u{e9}e\"\nc = a\n");
+
+        // Characters past the end of a line refer to the end of the line.
+        did_change(
+            &context,
+            DidChangeTextDocumentParams {
+                text_document: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 3,
+                },
+                content_changes: vec![change(Position::new(0, 6), Position::new(0, 99), "3")],
+            },
+        )
+        .await;
+        let document = context.storage.lock().unwrap().read(&path);
+        assert_eq!(&*document.data, "a = 423\nb = \"\u{e9}e\"\nc = a\n");
+        assert_eq!(document.version, DocumentVersion::InMemory { revision: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_did_change_incremental_out_of_order() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/BUILD.gn");
+        let uri = Url::from_file_path(&path).unwrap();
+
+        did_open(
+            &context,
+            DidOpenTextDocumentParams {
+                text_document: TextDocumentItem {
+                    uri: uri.clone(),
+                    language_id: "gn".to_string(),
+                    version: 1,
+                    text: "a = 1\n".to_string(),
+                },
+            },
+        )
+        .await;
+
+        let change = |start: Position, end: Position, text: &str| TextDocumentContentChangeEvent {
+            range: Some(Range::new(start, end)),
+            range_length: None,
+            text: text.to_string(),
+        };
+        for (version, changes) in [
+            (
+                3,
+                vec![change(Position::new(0, 6), Position::new(0, 6), "3")],
+            ),
+            (
+                2,
+                vec![change(Position::new(0, 4), Position::new(0, 5), "22")],
+            ),
+        ] {
+            did_change(
+                &context,
+                DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier {
+                        uri: uri.clone(),
+                        version,
+                    },
+                    content_changes: changes,
+                },
+            )
+            .await;
+
+            // The change arriving early waits for the version it applies to.
+            if version == 3 {
+                let document = context.storage.lock().unwrap().read(&path);
+                assert_eq!(&*document.data, "a = 1\n");
+                assert_eq!(document.version, DocumentVersion::InMemory { revision: 1 });
+            }
+        }
+
+        let document = context.storage.lock().unwrap().read(&path);
+        assert_eq!(&*document.data, "a = 223\n");
+        assert_eq!(document.version, DocumentVersion::InMemory { revision: 3 });
+        assert!(context.pending_changes.lock().unwrap().is_empty());
+    }
 }
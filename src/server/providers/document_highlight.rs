@@ -0,0 +1,190 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::{DocumentHighlight, DocumentHighlightKind, DocumentHighlightParams};
+
+use crate::{
+    common::error::Result,
+    parser::Node,
+    server::{
+        providers::utils::{get_text_document_path, is_same_variable, lookup_identifier_at},
+        RequestContext,
+    },
+};
+
+pub async fn document_highlight(
+    context: &RequestContext,
+    params: DocumentHighlightParams,
+) -> Result<Option<Vec<DocumentHighlight>>> {
+    let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+
+    let Some(ident) =
+        lookup_identifier_at(&current_file, params.text_document_position_params.position)
+    else {
+        return Ok(None);
+    };
+    let variables = current_file.variables_at(ident.span.start());
+    let Some(variable) = variables.get(ident.name) else {
+        return Ok(None);
+    };
+
+    // Highlight occurrences resolving to the same variable, rather than all
+    // identifiers sharing the name.
+    let line_index = &current_file.document.line_index;
+    let writes: Vec<_> = variable
+        .assignments
+        .values()
+        .filter(|assignment| assignment.document.path == current_file.document.path)
+        .map(|assignment| line_index.range(assignment.primary_variable))
+        .collect();
+    // Identifiers in the same subscope resolve alike, so resolve each
+    // subscope once.
+    let mut resolved_scopes: HashMap<(usize, usize), bool> = HashMap::new();
+    let reads: Vec<_> = current_file
+        .ast
+        .identifiers()
+        .filter(|other| other.name == ident.name)
+        .filter(|other| {
+            let scope = current_file.analyzed_root.scope_at(other.span.start());
+            *resolved_scopes
+                .entry((scope.span.start(), scope.span.end()))
+                .or_insert_with(|| {
+                    current_file
+                        .variables_at(other.span.start())
+                        .get(other.name)
+                        .is_some_and(|other_variable| is_same_variable(variable, other_variable))
+                })
+        })
+        .map(|other| line_index.range(other.span))
+        .filter(|range| !writes.contains(range))
+        .collect();
+
+    let mut highlights: Vec<DocumentHighlight> = writes
+        .into_iter()
+        .map(|range| DocumentHighlight {
+            range,
+            kind: Some(DocumentHighlightKind::WRITE),
+        })
+        .chain(reads.into_iter().map(|range| DocumentHighlight {
+            range,
+            kind: Some(DocumentHighlightKind::READ),
+        }))
+        .collect();
+    highlights.sort_by_key(|highlight| (highlight.range.start, highlight.range.end));
+    highlights.dedup_by_key(|highlight| highlight.range);
+
+    Ok(Some(highlights))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        PartialResultParams, Position, TextDocumentIdentifier, TextDocumentPositionParams, Url,
+        WorkDoneProgressParams,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn highlight_at(line: u32, character: u32) -> Vec<(u32, u32, DocumentHighlightKind)> {
+        let uri = Url::from_file_path(testdata("workspaces/references/BUILD.gn")).unwrap();
+        let params = DocumentHighlightParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position::new(line, character),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        document_highlight(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|highlight| {
+                let Position { line, character } = highlight.range.start;
+                (line, character, highlight.kind.unwrap())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_document_highlight_scopes() {
+        use DocumentHighlightKind as Kind;
+
+        // `a` assigned in the conditional is the same variable as the outer
+        // `a`, while `a` in the template is not.
+        assert_eq!(
+            highlight_at(19, 4).await,
+            vec![
+                (15, 0, Kind::WRITE),
+                (16, 4, Kind::READ),
+                (17, 2, Kind::WRITE),
+                (19, 4, Kind::READ),
+                (19, 8, Kind::READ),
+            ]
+        );
+        assert_eq!(
+            highlight_at(23, 8).await,
+            vec![(22, 2, Kind::WRITE), (23, 8, Kind::READ)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_document_highlight_lvalues() {
+        use DocumentHighlightKind as Kind;
+
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/references/lvalues.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "x = [ 1 ]\nx += [ 2 ]\nx[0] = 3\nprint(x)\n",
+            1,
+        );
+        let params = DocumentHighlightParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(3, 6),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let highlights: Vec<_> = document_highlight(&context, params)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|highlight| (highlight.range.start.line, highlight.kind.unwrap()))
+            .collect();
+
+        // Every kind of assignment lvalue is a write.
+        assert_eq!(
+            highlights,
+            vec![
+                (0, Kind::WRITE),
+                (1, Kind::WRITE),
+                (2, Kind::WRITE),
+                (3, Kind::READ),
+            ]
+        );
+    }
+}
@@ -44,10 +44,12 @@ pub async fn document_link(
         .links
         .iter()
         .map(|link| match link {
-            AnalyzedLink::File { path, span } => DocumentLink {
+            AnalyzedLink::File { path, span, exists } => DocumentLink {
                 target: Some(Url::from_file_path(path).unwrap()),
                 range: current_file.document.line_index.range(*span),
-                tooltip: None,
+                // Only imports link to missing files. Opening the link lets
+                // the user create the file.
+                tooltip: (!exists).then(|| "File not found".to_string()),
                 data: None,
             },
             AnalyzedLink::Target { path, name, span } => DocumentLink {
@@ -0,0 +1,190 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serde_json::Value;
+use tower_lsp::lsp_types::{ExecuteCommandParams, TextDocumentIdentifier};
+
+use crate::{
+    common::error::{Error, Result},
+    server::{
+        providers::{
+            formatting::format_check, gn_gen::run_gen, list_args::list_args, self_test::self_test,
+            unresolved_imports::unresolved_imports, workspace_diagnostics::workspace_diagnostics,
+        },
+        RequestContext,
+    },
+};
+
+pub const ANALYSIS_STATS_COMMAND: &str = "gn.analysisStats";
+pub const FORMAT_CHECK_COMMAND: &str = "gn.formatCheck";
+pub const FREEZE_INDEX_COMMAND: &str = "gn.freezeIndex";
+pub const GEN_COMMAND: &str = "gn.gen";
+pub const LIST_ARGS_COMMAND: &str = "gn.listArgs";
+pub const SELF_TEST_COMMAND: &str = "gn.selfTest";
+pub const UNRESOLVED_IMPORTS_COMMAND: &str = "gn.unresolvedImports";
+pub const WORKSPACE_DIAGNOSTICS_COMMAND: &str = "gn.workspaceDiagnostics";
+
+pub const COMMANDS: &[&str] = &[
+    ANALYSIS_STATS_COMMAND,
+    FORMAT_CHECK_COMMAND,
+    FREEZE_INDEX_COMMAND,
+    GEN_COMMAND,
+    LIST_ARGS_COMMAND,
+    SELF_TEST_COMMAND,
+    UNRESOLVED_IMPORTS_COMMAND,
+    WORKSPACE_DIAGNOSTICS_COMMAND,
+];
+
+fn parse_text_document(arguments: Vec<Value>) -> Result<TextDocumentIdentifier> {
+    let Some(argument) = arguments.into_iter().next() else {
+        return Err(Error::InvalidParams(
+            "missing text document argument".to_string(),
+        ));
+    };
+    serde_json::from_value(argument)
+        .map_err(|err| Error::InvalidParams(format!("invalid text document argument: {err}")))
+}
+
+/// Parses the optional argument of the freeze command, which freezes unless
+/// it is `false`.
+fn parse_freeze(arguments: Vec<Value>) -> Result<bool> {
+    match arguments.into_iter().next() {
+        None => Ok(true),
+        Some(Value::Bool(freeze)) => Ok(freeze),
+        Some(argument) => Err(Error::InvalidParams(format!(
+            "invalid freeze argument: {argument}"
+        ))),
+    }
+}
+
+pub async fn execute_command(
+    context: &RequestContext,
+    params: ExecuteCommandParams,
+) -> Result<Option<Value>> {
+    match params.command.as_str() {
+        ANALYSIS_STATS_COMMAND => Ok(Some(
+            serde_json::to_value(context.analyzer.stats()).unwrap(),
+        )),
+        FORMAT_CHECK_COMMAND => {
+            let text_document = parse_text_document(params.arguments)?;
+            let result = format_check(context, &text_document.uri).await?;
+            Ok(Some(serde_json::to_value(result).unwrap()))
+        }
+        FREEZE_INDEX_COMMAND => {
+            if parse_freeze(params.arguments)? {
+                context.analyzer.freeze();
+            } else {
+                context.analyzer.thaw();
+            }
+            Ok(Some(Value::Bool(context.analyzer.is_frozen())))
+        }
+        GEN_COMMAND => {
+            let text_document = parse_text_document(params.arguments)?;
+            let result = run_gen(context, &text_document).await?;
+            Ok(Some(serde_json::to_value(result).unwrap()))
+        }
+        LIST_ARGS_COMMAND => {
+            let text_document = parse_text_document(params.arguments)?;
+            let args = list_args(context, &text_document).await?;
+            Ok(Some(serde_json::to_value(args).unwrap()))
+        }
+        SELF_TEST_COMMAND => {
+            let result = tokio::task::spawn_blocking(self_test)
+                .await
+                .map_err(|err| Error::General(format!("self test panicked: {err}")))?;
+            Ok(Some(serde_json::to_value(result).unwrap()))
+        }
+        UNRESOLVED_IMPORTS_COMMAND => {
+            let text_document = parse_text_document(params.arguments)?;
+            let imports = unresolved_imports(context, &text_document).await?;
+            Ok(Some(serde_json::to_value(imports).unwrap()))
+        }
+        WORKSPACE_DIAGNOSTICS_COMMAND => {
+            let text_document = parse_text_document(params.arguments)?;
+            let diagnostics = workspace_diagnostics(context, &text_document).await?;
+            Ok(Some(serde_json::to_value(diagnostics).unwrap()))
+        }
+        command => Err(Error::InvalidParams(format!("unknown command: {command}"))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_analysis_stats() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/smoke/BUILD.gn");
+        context
+            .analyzer
+            .analyze(&path, &context.finder, context.request_time)
+            .unwrap();
+        context
+            .analyzer
+            .analyze(&path, &context.finder, context.request_time)
+            .unwrap();
+
+        let params = ExecuteCommandParams {
+            command: ANALYSIS_STATS_COMMAND.to_string(),
+            arguments: Vec::new(),
+            work_done_progress_params: Default::default(),
+        };
+        let result = execute_command(&context, params).await.unwrap().unwrap();
+        let stats = &result[testdata("workspaces/smoke").to_str().unwrap()];
+        assert!(stats["cacheMisses"].as_u64().unwrap() > 0, "{result}");
+        assert!(stats["cacheHits"].as_u64().unwrap() > 0, "{result}");
+        assert!(stats["fullAnalysisMs"].as_f64().unwrap() > 0.0, "{result}");
+    }
+
+    #[tokio::test]
+    async fn test_self_test() {
+        let params = ExecuteCommandParams {
+            command: SELF_TEST_COMMAND.to_string(),
+            arguments: Vec::new(),
+            work_done_progress_params: Default::default(),
+        };
+        let result = execute_command(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(result["passed"], true, "{result}");
+        assert!(result["elapsedMs"].is_number());
+    }
+
+    #[tokio::test]
+    async fn test_freeze_index() {
+        let context = RequestContext::new_for_testing();
+        let freeze = |arguments: Vec<Value>| {
+            let params = ExecuteCommandParams {
+                command: FREEZE_INDEX_COMMAND.to_string(),
+                arguments,
+                work_done_progress_params: Default::default(),
+            };
+            let context = context.clone();
+            async move { execute_command(&context, params).await }
+        };
+
+        assert_eq!(freeze(Vec::new()).await.unwrap(), Some(Value::Bool(true)));
+        assert!(context.analyzer.is_frozen());
+        assert_eq!(
+            freeze(vec![Value::Bool(false)]).await.unwrap(),
+            Some(Value::Bool(false))
+        );
+        assert!(!context.analyzer.is_frozen());
+        assert!(freeze(vec![Value::from("yes")]).await.is_err());
+    }
+}
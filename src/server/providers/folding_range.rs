@@ -0,0 +1,134 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use pest::Span;
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams};
+
+use crate::{
+    common::{error::Result, utils::LineIndex},
+    parser::{Node, Statement},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Folds a bracketed span, keeping the closing bracket visible. Returns
+/// `None` if there is nothing to hide.
+fn bracket_range(line_index: &LineIndex, span: Span) -> Option<FoldingRange> {
+    let start_line = line_index.position(span.start()).line;
+    let end_line = line_index.position(span.end()).line.checked_sub(1)?;
+    (start_line < end_line).then(|| FoldingRange {
+        start_line,
+        end_line,
+        ..Default::default()
+    })
+}
+
+/// Returns the offset of a slice borrowed from `data`.
+fn offset_in(data: &str, slice: &str) -> Option<usize> {
+    let offset = (slice.as_ptr() as usize).checked_sub(data.as_ptr() as usize)?;
+    (offset <= data.len()).then_some(offset)
+}
+
+pub async fn folding_range(
+    context: &RequestContext,
+    params: FoldingRangeParams,
+) -> Result<Option<Vec<FoldingRange>>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+    let data: &str = &current_file.document.data;
+    let line_index = &current_file.document.line_index;
+
+    let mut ranges = Vec::new();
+    // Skip the root block spanning the whole file. Conditions fold through
+    // their then and else blocks.
+    for node in current_file.ast.walk().skip(1) {
+        if let Some(block) = node.as_block() {
+            ranges.extend(bracket_range(line_index, block.span));
+        } else if let Some(list) = node.as_list() {
+            ranges.extend(bracket_range(line_index, list.span));
+        } else if let Some(statement) = node.as_statement() {
+            let comments = match statement {
+                Statement::Assignment(assignment) => &assignment.comments,
+                Statement::Call(call) => &call.comments,
+                Statement::Condition(_) | Statement::Error(_) => continue,
+            };
+            // Comments attached to a statement may be separated by blank
+            // lines, so fold each run of consecutive lines separately.
+            let lines: Vec<u32> = comments
+                .lines
+                .iter()
+                .filter_map(|line| offset_in(data, line))
+                .map(|offset| line_index.position(offset).line)
+                .collect();
+            for run in lines.chunk_by(|a, b| a + 1 == *b) {
+                if let [first, .., last] = run {
+                    ranges.push(FoldingRange {
+                        start_line: *first,
+                        end_line: *last,
+                        kind: Some(FoldingRangeKind::Comment),
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+    }
+
+    ranges.sort_by_key(|range| (range.start_line, range.end_line));
+    Ok(Some(ranges))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_folding_range() {
+        let params = FoldingRangeParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(testdata("workspaces/folding_range/BUILD.gn")).unwrap(),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let ranges = folding_range(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let range = |start_line: u32, end_line: u32, kind: Option<FoldingRangeKind>| FoldingRange {
+            start_line,
+            end_line,
+            kind,
+            ..Default::default()
+        };
+        // Single-line blocks and lists, and single comment lines, do not
+        // fold.
+        assert_eq!(
+            ranges,
+            vec![
+                range(0, 12, Some(FoldingRangeKind::Comment)),
+                range(14, 15, Some(FoldingRangeKind::Comment)),
+                range(16, 26, None),
+                range(17, 19, None),
+                range(22, 23, None),
+                range(24, 25, None),
+            ]
+        );
+    }
+}
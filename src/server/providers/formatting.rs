@@ -12,48 +12,38 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::process::Stdio;
+use std::{path::Path, pin::Pin, process::Stdio, sync::Arc};
 
 use pest::Span;
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
     process::Command,
 };
-use tower_lsp::lsp_types::{DocumentFormattingParams, TextEdit};
+use tower_lsp::lsp_types::{DocumentFormattingParams, TextDocumentIdentifier, TextEdit, Url};
 
 use crate::{
     common::{
-        binary::find_gn_binary,
+        builtins::DECLARE_ARGS,
+        config::FormattingConfigurations,
         error::{Error, Result},
+        storage::Document,
+    },
+    parser::{parse, Block, LValue, ListLiteral, Node, Statement},
+    server::{
+        providers::utils::{get_text_document_path, resolve_gn_binary},
+        RequestContext,
     },
-    server::{providers::utils::get_text_document_path, RequestContext},
 };
 
-pub async fn formatting(
+/// Formats a document with `gn format`, returning the document and the
+/// formatted text.
+async fn format_document(
     context: &RequestContext,
-    params: DocumentFormattingParams,
-) -> Result<Option<Vec<TextEdit>>> {
-    let file_path = get_text_document_path(&params.text_document)?;
-
-    let configs = context.client.configurations().await;
-    let gn_path = if let Some(gn_path) = &configs.binary_path {
-        if gn_path.exists() {
-            gn_path.to_path_buf()
-        } else {
-            return Err(Error::General(format!(
-                "gn binary not found at {}; check configuration value gn.binaryPath",
-                gn_path.display()
-            )));
-        }
-    } else if let Some(gn_path) = find_gn_binary(context.finder.find_for(&file_path)) {
-        gn_path
-    } else {
-        return Err(Error::General(
-            "gn binary not found; specify configuration value gn.binaryPath".to_string(),
-        ));
-    };
+    file_path: &Path,
+) -> Result<(Pin<Arc<Document>>, String)> {
+    let gn_path = resolve_gn_binary(context, file_path).await?;
 
-    let document = context.storage.lock().unwrap().read(&file_path);
+    let document = context.storage.lock().unwrap().read(file_path);
 
     let mut process = Command::new(gn_path)
         .args(["format", "--stdin"])
@@ -89,6 +79,210 @@ pub async fn formatting(
     // Check the IO result then.
     io_result?;
 
+    Ok((document, formatted))
+}
+
+/// Formats a list on a single line if it is short enough, or one element per
+/// line otherwise, as `gn format` does. Returns `None` if the list contains
+/// anything but elements, e.g. comments, which would be lost.
+fn format_list(
+    data: &str,
+    list: &ListLiteral,
+    config: &FormattingConfigurations,
+) -> Option<String> {
+    let mut elements = Vec::new();
+    let mut gap_start = list.span.start() + 1;
+    for value in &list.values {
+        elements.push(value.span().as_str());
+        if !data[gap_start..value.span().start()]
+            .chars()
+            .all(|ch| ch.is_whitespace() || ch == ',')
+        {
+            return None;
+        }
+        gap_start = value.span().end();
+    }
+    if !data[gap_start..list.span.end() - 1]
+        .chars()
+        .all(|ch| ch.is_whitespace() || ch == ',')
+    {
+        return None;
+    }
+
+    if elements.is_empty() {
+        return Some("[]".to_string());
+    }
+
+    let line_start = data[..list.span.start()]
+        .rfind('\n')
+        .map_or(0, |pos| pos + 1);
+    let column = data[line_start..list.span.start()].chars().count();
+    let inline = format!("[ {} ]", elements.join(", "));
+    if elements.len() <= config.inline_list_limit
+        && !inline.contains('\n')
+        && column + inline.chars().count() <= config.line_width
+    {
+        return Some(inline);
+    }
+
+    // Close the list at the indentation of the statement.
+    let indent: String = data[line_start..]
+        .chars()
+        .take_while(|ch| *ch == ' ')
+        .collect();
+    let mut wrapped = "[\n".to_string();
+    for element in elements {
+        wrapped.push_str(&format!("{indent}  {element},\n"));
+    }
+    wrapped.push_str(&format!("{indent}]"));
+    Some(wrapped)
+}
+
+/// Wraps lists assigned to variables following the `gn format` rules. Used
+/// when the gn binary is unavailable.
+fn format_lists(data: &str, config: &FormattingConfigurations) -> String {
+    let ast = parse(data);
+    let mut replacements: Vec<(usize, usize, String)> = Vec::new();
+    for node in ast.walk() {
+        let Some(Statement::Assignment(assignment)) = node.as_statement() else {
+            continue;
+        };
+        let Some(list) = assignment.rvalue.as_primary_list() else {
+            continue;
+        };
+        // Nested lists are left as they are in the enclosing list.
+        if replacements
+            .last()
+            .is_some_and(|(_, end, _)| list.span.start() < *end)
+        {
+            continue;
+        }
+        if let Some(formatted) = format_list(data, list, config) {
+            replacements.push((list.span.start(), list.span.end(), formatted));
+        }
+    }
+
+    let mut formatted = String::new();
+    let mut last = 0;
+    for (start, end, replacement) in replacements {
+        formatted.push_str(&data[last..start]);
+        formatted.push_str(&replacement);
+        last = end;
+    }
+    formatted.push_str(&data[last..]);
+    formatted
+}
+
+/// Returns the start of the line containing `offset`.
+fn line_start(data: &str, offset: usize) -> usize {
+    data[..offset].rfind('\n').map_or(0, |pos| pos + 1)
+}
+
+/// Returns the start of the line following the one containing `offset`.
+fn next_line_start(data: &str, offset: usize) -> usize {
+    data[offset..]
+        .find('\n')
+        .map_or(data.len(), |pos| offset + pos + 1)
+}
+
+/// Returns the sort key and the text range of each assignment in a
+/// `declare_args` block. Ranges cover whole lines, from comments above the
+/// assignment to the end of its last line including a trailing comment.
+/// Returns `None` if the block contains anything but assignments, whose order
+/// may matter, or if an assignment shares a line with other code.
+fn declare_args_entries<'i>(data: &str, block: &Block<'i>) -> Option<Vec<(&'i str, usize, usize)>> {
+    let mut entries = Vec::new();
+    let mut last_end = next_line_start(data, block.span.start());
+    for statement in &block.statements {
+        let Statement::Assignment(assignment) = statement else {
+            return None;
+        };
+        let name = match &assignment.lvalue {
+            LValue::Identifier(identifier) => identifier.name,
+            LValue::ArrayAccess(array_access) => array_access.array.name,
+            LValue::ScopeAccess(scope_access) => scope_access.scope.name,
+        };
+        let assignment_start = line_start(data, assignment.span.start());
+        if assignment_start < last_end
+            || !data[assignment_start..assignment.span.start()]
+                .trim()
+                .is_empty()
+        {
+            return None;
+        }
+        // Comments on the line of the block opening stay there.
+        let start = assignment
+            .comments
+            .lines
+            .iter()
+            .map(|line| line_start(data, line.as_ptr() as usize - data.as_ptr() as usize))
+            .find(|start| *start >= last_end)
+            .unwrap_or(assignment_start);
+        let end = next_line_start(data, assignment.span.end());
+        let rest = data[assignment.span.end()..end].trim();
+        if !(rest.is_empty() || rest.starts_with('#')) {
+            return None;
+        }
+        entries.push((name, start, end));
+        last_end = end;
+    }
+    Some(entries)
+}
+
+/// Sorts assignments in `declare_args` blocks by variable name, moving
+/// comments along with them. Arguments in the same block cannot refer to
+/// each other, so reordering them keeps the meaning.
+fn sort_declare_args(data: &str) -> String {
+    let ast = parse(data);
+    let mut replacements: Vec<(usize, usize, String)> = Vec::new();
+    for call in ast.walk().filter_map(|node| node.as_call()) {
+        if call.function.name != DECLARE_ARGS {
+            continue;
+        }
+        let Some(block) = &call.block else {
+            continue;
+        };
+        let Some(entries) = declare_args_entries(data, block) else {
+            continue;
+        };
+        let mut sorted = entries.clone();
+        sorted.sort_by_key(|(name, _, _)| *name);
+        // Keep the gaps between entries, such as blank lines, in place.
+        for ((_, start, end), (_, sorted_start, sorted_end)) in entries.iter().zip(&sorted) {
+            if start != sorted_start {
+                replacements.push((*start, *end, data[*sorted_start..*sorted_end].to_string()));
+            }
+        }
+    }
+
+    let mut formatted = String::new();
+    let mut last = 0;
+    for (start, end, replacement) in replacements {
+        formatted.push_str(&data[last..start]);
+        formatted.push_str(&replacement);
+        last = end;
+    }
+    formatted.push_str(&data[last..]);
+    formatted
+}
+
+pub async fn formatting(
+    context: &RequestContext,
+    params: DocumentFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let file_path = get_text_document_path(&params.text_document)?;
+    let config = context.client.configurations().await;
+    let (document, mut formatted) = if resolve_gn_binary(context, &file_path).await.is_ok() {
+        format_document(context, &file_path).await?
+    } else {
+        let document = context.storage.lock().unwrap().read(&file_path);
+        let formatted = format_lists(&document.data, &config.formatting);
+        (document, formatted)
+    };
+    if config.formatting.sort_declare_args {
+        formatted = sort_declare_args(&formatted);
+    }
+
     let whole_range = document
         .line_index
         .range(Span::new(&document.data, 0, document.data.len()).unwrap());
@@ -97,3 +291,168 @@ pub async fn formatting(
         new_text: formatted,
     }]))
 }
+
+#[derive(serde::Serialize)]
+pub struct FormatCheckResult {
+    pub formatted: bool,
+    pub diff: String,
+}
+
+/// Computes a single-hunk line diff between the original and the formatted
+/// text, after trimming their common leading and trailing lines.
+fn diff_lines(original: &str, formatted: &str) -> String {
+    let original: Vec<&str> = original.lines().collect();
+    let formatted: Vec<&str> = formatted.lines().collect();
+
+    let prefix = original
+        .iter()
+        .zip(&formatted)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = original[prefix..]
+        .iter()
+        .rev()
+        .zip(formatted[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let removed = &original[prefix..original.len() - suffix];
+    let added = &formatted[prefix..formatted.len() - suffix];
+
+    let mut diff = format!(
+        "@@ -{},{} +{},{} @@\n",
+        prefix + 1,
+        removed.len(),
+        prefix + 1,
+        added.len()
+    );
+    for line in removed {
+        diff.push_str(&format!("-{line}\n"));
+    }
+    for line in added {
+        diff.push_str(&format!("+{line}\n"));
+    }
+    diff
+}
+
+pub async fn format_check(context: &RequestContext, uri: &Url) -> Result<FormatCheckResult> {
+    let file_path = get_text_document_path(&TextDocumentIdentifier { uri: uri.clone() })?;
+    let (document, formatted) = format_document(context, &file_path).await?;
+
+    if *document.data == formatted {
+        return Ok(FormatCheckResult {
+            formatted: true,
+            diff: String::new(),
+        });
+    }
+    Ok(FormatCheckResult {
+        formatted: false,
+        diff: diff_lines(&document.data, &formatted),
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[cfg(test)]
+mod tests {
+    use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_format_check() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root_dir = temp_dir.path();
+        std::fs::write(root_dir.join(".gn"), "").unwrap();
+
+        // Create a fake prebuilt that collapses repeated spaces.
+        std::fs::create_dir_all(root_dir.join("buildtools/linux64")).unwrap();
+        std::fs::write(
+            root_dir.join("buildtools/linux64/gn"),
+            b"#!/bin/sh\nexec sed -e 's/  */ /g'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(
+            root_dir.join("buildtools/linux64/gn"),
+            Permissions::from_mode(0o755),
+        )
+        .unwrap();
+
+        let context = RequestContext::new_for_testing();
+
+        let path = root_dir.join("formatted.gn");
+        std::fs::write(&path, "a = 1\nb = 2\n").unwrap();
+        let result = format_check(&context, &Url::from_file_path(&path).unwrap())
+            .await
+            .unwrap();
+        assert!(result.formatted);
+        assert_eq!(result.diff, "");
+
+        let path = root_dir.join("messy.gn");
+        std::fs::write(&path, "a = 1\nb  =   2\n").unwrap();
+        let result = format_check(&context, &Url::from_file_path(&path).unwrap())
+            .await
+            .unwrap();
+        assert!(!result.formatted);
+        assert_eq!(result.diff, "@@ -2,1 +2,1 @@\n-b  =   2\n+b = 2\n");
+    }
+
+    #[test]
+    fn test_format_lists_inline() {
+        let config = FormattingConfigurations::default();
+        assert_eq!(
+            format_lists("sources = [\n  \"a.cc\",\n]\ndeps = []\n", &config),
+            "sources = [ \"a.cc\" ]\ndeps = []\n"
+        );
+    }
+
+    #[test]
+    fn test_format_lists_wrapped() {
+        let config = FormattingConfigurations::default();
+        assert_eq!(
+            format_lists(
+                "executable(\"main\") {\n  deps = [ \":a\", \":b\" ]\n}\n",
+                &config
+            ),
+            "executable(\"main\") {\n  deps = [\n    \":a\",\n    \":b\",\n  ]\n}\n"
+        );
+
+        // A single element is wrapped if it does not fit in the width.
+        let config = FormattingConfigurations {
+            line_width: 20,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_lists("sources = [ \"long_name.cc\" ]\n", &config),
+            "sources = [\n  \"long_name.cc\",\n]\n"
+        );
+
+        // Lists with comments are left as they are.
+        let data = "sources = [\n  # Comment.\n  \"a.cc\",\n]\n";
+        assert_eq!(format_lists(data, &config), data);
+    }
+
+    #[test]
+    fn test_sort_declare_args_golden() {
+        let data =
+            std::fs::read_to_string(testdata("workspaces/formatting/declare_args.gn")).unwrap();
+        let expected = std::fs::read_to_string(testdata("golden/sort_declare_args.txt")).unwrap();
+        assert_eq!(sort_declare_args(&data), expected);
+
+        // Sorting is idempotent.
+        assert_eq!(sort_declare_args(&expected), expected);
+    }
+
+    #[test]
+    fn test_sort_declare_args_lines() {
+        // Comments on the opening line stay there.
+        assert_eq!(
+            sort_declare_args("declare_args() {  # Args.\n  b = 1\n  a = 2\n}\n"),
+            "declare_args() {  # Args.\n  a = 2\n  b = 1\n}\n"
+        );
+
+        // Assignments sharing a line with other code are left alone.
+        let data = "declare_args() {\n  b = 1\n  a = 2 }\n";
+        assert_eq!(sort_declare_args(data), data);
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+
+use tokio::process::Command;
+use tower_lsp::lsp_types::{
+    Diagnostic, DiagnosticSeverity, Position, Range, TextDocumentIdentifier, Url,
+};
+
+use crate::{
+    common::{
+        error::{Error, Result},
+        storage::DocumentVersion,
+    },
+    server::{
+        providers::{
+            diagnostics::publish_diagnostics,
+            utils::{get_text_document_path, resolve_gn_binary},
+        },
+        RequestContext,
+    },
+};
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenResult {
+    pub success: bool,
+    pub error_count: usize,
+}
+
+/// Parses a location like `//foo/BUILD.gn:12:3`, returning the resolved file
+/// path and the 0-based position.
+fn parse_location(location: &str, root: &Path) -> Option<(PathBuf, Position)> {
+    let mut parts = location.rsplitn(3, ':');
+    let column: u32 = parts.next()?.parse().ok()?;
+    let line: u32 = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+    let path = if let Some(rel_path) = file.strip_prefix("//") {
+        root.join(rel_path)
+    } else {
+        root.join(file)
+    };
+    Some((
+        path,
+        Position {
+            line: line.checked_sub(1)?,
+            character: column.checked_sub(1)?,
+        },
+    ))
+}
+
+/// Parses an error line printed by `gn gen`, either in GN's own format
+/// (`ERROR at //foo/BUILD.gn:12:3: message`) or the compiler-style format
+/// (`foo/BUILD.gn:12:3: error: message`).
+pub fn parse_gen_error(line: &str, root: &Path) -> Option<(PathBuf, Diagnostic)> {
+    let (location, message) = if let Some(rest) = line.strip_prefix("ERROR at ") {
+        rest.split_once(": ")?
+    } else {
+        let (location, rest) = line.split_once(": ")?;
+        (location, rest.strip_prefix("error: ")?)
+    };
+    let (path, position) = parse_location(location, root)?;
+    Some((
+        path,
+        Diagnostic {
+            range: Range {
+                start: position,
+                end: position,
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("gn gen".to_string()),
+            message: message.trim().to_string(),
+            ..Default::default()
+        },
+    ))
+}
+
+/// Runs `gn gen` for the workspace containing the document and publishes its
+/// errors as diagnostics.
+pub async fn run_gen(
+    context: &RequestContext,
+    text_document: &TextDocumentIdentifier,
+) -> Result<GenResult> {
+    let path = get_text_document_path(text_document)?;
+    let root = context.analyzer.workspace_root(&path, &context.finder)?;
+    let Some(out_dir) = context.client.configurations().await.out_dir else {
+        return Err(Error::General(
+            "output directory not set; specify configuration value gn.outDir".to_string(),
+        ));
+    };
+    let gn_path = resolve_gn_binary(context, &path).await?;
+
+    let output = Command::new(gn_path)
+        .arg("gen")
+        .arg(&out_dir)
+        .current_dir(&root)
+        .stdin(Stdio::null())
+        .output()
+        .await?;
+
+    // GN prints errors to stdout, but also check stderr just in case.
+    let mut diagnostics: BTreeMap<PathBuf, Vec<Diagnostic>> = BTreeMap::new();
+    for line in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+    {
+        if let Some((path, diagnostic)) = parse_gen_error(line, &root) {
+            diagnostics.entry(path).or_default().push(diagnostic);
+        }
+    }
+
+    let diagnostics: BTreeMap<Url, Vec<Diagnostic>> = diagnostics
+        .into_iter()
+        .filter_map(|(path, diagnostics)| Some((Url::from_file_path(&path).ok()?, diagnostics)))
+        .collect();
+    let error_count = diagnostics.values().map(Vec::len).sum();
+
+    // Replace the errors of the previous run, so that fixed files are
+    // cleared as well.
+    let previous_diagnostics = std::mem::replace(
+        &mut *context.gen_diagnostics.lock().unwrap(),
+        diagnostics.clone(),
+    );
+    let uris: BTreeSet<Url> = previous_diagnostics
+        .into_keys()
+        .chain(diagnostics.keys().cloned())
+        .collect();
+    for uri in uris {
+        let is_open = uri.to_file_path().is_ok_and(|path: PathBuf| {
+            matches!(
+                context.storage.lock().unwrap().read_version(&path),
+                DocumentVersion::InMemory { .. }
+            )
+        });
+        if is_open {
+            // Keep analysis diagnostics of open files.
+            publish_diagnostics(context, &uri).await;
+            continue;
+        }
+        let diagnostics = diagnostics.get(&uri).cloned().unwrap_or_default();
+        {
+            let mut diagnosed = context.diagnosed.lock().unwrap();
+            if diagnostics.is_empty() {
+                diagnosed.remove(&uri);
+            } else {
+                diagnosed.insert(uri.clone());
+            }
+        }
+        context
+            .client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+
+    Ok(GenResult {
+        success: output.status.success(),
+        error_count,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gen_error() {
+        let root = Path::new("/src");
+        let (path, diagnostic) = parse_gen_error(
+            "ERROR at //foo/BUILD.gn:12:3: Undefined identifier in string expansion.",
+            root,
+        )
+        .unwrap();
+        assert_eq!(path, Path::new("/src/foo/BUILD.gn"));
+        assert_eq!(
+            diagnostic.range.start,
+            Position {
+                line: 11,
+                character: 2
+            }
+        );
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diagnostic.message,
+            "Undefined identifier in string expansion."
+        );
+
+        let (path, diagnostic) =
+            parse_gen_error("bar/baz.gni:1:5: error: Assignment had no effect.", root).unwrap();
+        assert_eq!(path, Path::new("/src/bar/baz.gni"));
+        assert_eq!(
+            diagnostic.range.start,
+            Position {
+                line: 0,
+                character: 4
+            }
+        );
+        assert_eq!(diagnostic.message, "Assignment had no effect.");
+
+        assert!(parse_gen_error("Done. Made 42 targets from 7 files in 5ms", root).is_none());
+        assert!(parse_gen_error("      ^--", root).is_none());
+    }
+}
@@ -18,9 +18,9 @@ use tower_lsp::lsp_types::{
 };
 
 use crate::{
-    analyzer::AnalyzedLink,
+    analyzer::{AnalyzedFile, AnalyzedLink, TopLevelStatementsExt, Variable},
     common::error::Result,
-    parser::Node,
+    parser::{Identifier, LValue, Node, PrimaryExpr, ScopeAccess, Statement},
     server::{
         providers::utils::{
             find_target, get_text_document_path, lookup_identifier_at, lookup_target_name_string_at,
@@ -29,6 +29,86 @@ use crate::{
     },
 };
 
+/// Links to the assignments of a variable.
+fn variable_links(
+    current_file: &AnalyzedFile,
+    ident: &Identifier,
+    variable: &Variable,
+) -> Vec<LocationLink> {
+    variable
+        .sorted_assignments()
+        .into_iter()
+        .map(|assignment| LocationLink {
+            origin_selection_range: Some(current_file.document.line_index.range(ident.span)),
+            target_uri: Url::from_file_path(&assignment.document.path).unwrap(),
+            target_range: assignment.document.line_index.range(assignment.span()),
+            target_selection_range: assignment
+                .document
+                .line_index
+                .range(assignment.primary_variable),
+        })
+        .collect()
+}
+
+/// Links `member` in `scope.member` to its assignments in `{ ... }` blocks
+/// assigned to `scope` and in `scope.member = ...`, falling back to the
+/// assignments of `scope` if the scope is opaque.
+fn scope_member_links(current_file: &AnalyzedFile, access: &ScopeAccess) -> Vec<LocationLink> {
+    let variables = current_file.variables_at(access.scope.span.start());
+    let Some(variable) = variables.get(access.scope.name) else {
+        return Vec::new();
+    };
+
+    let mut links = Vec::new();
+    for assignment in variable.sorted_assignments() {
+        let Either::Left(assignment_statement) = assignment.assignment_or_call else {
+            continue;
+        };
+        let line_index = &assignment.document.line_index;
+        let member_assignments: Vec<_> = match &assignment_statement.lvalue {
+            LValue::Identifier(_) => match assignment_statement.rvalue.as_primary() {
+                Some(PrimaryExpr::Block(block)) => block
+                    .top_level_statements()
+                    .filter_map(|statement| match statement {
+                        Statement::Assignment(member_assignment) => match &member_assignment.lvalue
+                        {
+                            LValue::Identifier(identifier)
+                                if identifier.name == access.member.name =>
+                            {
+                                Some((member_assignment.span, identifier.span))
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            },
+            LValue::ScopeAccess(scope_access) if scope_access.member.name == access.member.name => {
+                vec![(assignment_statement.span, scope_access.member.span)]
+            }
+            _ => Vec::new(),
+        };
+        links.extend(
+            member_assignments
+                .into_iter()
+                .map(|(span, selection_span)| LocationLink {
+                    origin_selection_range: Some(
+                        current_file.document.line_index.range(access.member.span),
+                    ),
+                    target_uri: Url::from_file_path(&assignment.document.path).unwrap(),
+                    target_range: line_index.range(span),
+                    target_selection_range: line_index.range(selection_span),
+                }),
+        );
+    }
+
+    if links.is_empty() {
+        return variable_links(current_file, &access.member, variable);
+    }
+    links
+}
+
 pub async fn goto_definition(
     context: &RequestContext,
     params: GotoDefinitionParams,
@@ -100,6 +180,19 @@ pub async fn goto_definition(
         return Ok(None);
     };
 
+    // Check members of scopes, e.g. `bar` in `foo.bar`.
+    if let Some(access) = current_file
+        .ast
+        .walk()
+        .filter_map(|node| node.as_scope_access())
+        .find(|access| access.member.span == ident.span)
+    {
+        return Ok(Some(GotoDefinitionResponse::Link(scope_member_links(
+            &current_file,
+            access,
+        ))));
+    }
+
     let mut links: Vec<LocationLink> = Vec::new();
 
     // Check templates.
@@ -121,22 +214,315 @@ pub async fn goto_definition(
     // Check variables.
     let variables = current_file.variables_at(ident.span.start());
     if let Some(variable) = variables.get(ident.name) {
-        links.extend(variable.assignments.values().map(|assignment| {
-            let span = match &assignment.assignment_or_call {
-                Either::Left(assignment) => assignment.span,
-                Either::Right(call) => call.span,
+        links.extend(variable_links(&current_file, ident, variable));
+    }
+
+    Ok(Some(GotoDefinitionResponse::Link(links)))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        PartialResultParams, TextDocumentIdentifier, TextDocumentPositionParams,
+        WorkDoneProgressParams,
+    };
+
+    use crate::common::testutils::{testdata, TestWorkspace};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_goto_definition_deterministic() {
+        let uri =
+            Url::from_file_path(testdata("workspaces/multiple_definitions/BUILD.gn")).unwrap();
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri },
+                position: Position {
+                    line: 24,
+                    character: 6,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        // Each analysis builds fresh hash maps, so repeat it to catch
+        // iteration order leaking into results.
+        for _ in 0..10 {
+            let Some(GotoDefinitionResponse::Link(links)) =
+                goto_definition(&RequestContext::new_for_testing(), params.clone())
+                    .await
+                    .unwrap()
+            else {
+                panic!("unexpected response");
             };
-            LocationLink {
-                origin_selection_range: Some(current_file.document.line_index.range(ident.span)),
-                target_uri: Url::from_file_path(&assignment.document.path).unwrap(),
-                target_range: assignment.document.line_index.range(span),
-                target_selection_range: assignment
-                    .document
-                    .line_index
-                    .range(assignment.primary_variable),
+            let lines: Vec<_> = links
+                .iter()
+                .map(|link| link.target_selection_range.start.line)
+                .collect();
+            assert_eq!(lines, vec![15, 17, 19, 21]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_target_labels() {
+        let workspace = TestWorkspace::new([
+            (
+                "foo/BUILD.gn",
+                "group(\"foo\") {\n}\n\ngroup(\"bar\") {\n}\n",
+            ),
+            (
+                "BUILD.gn",
+                r#"group("local") {
+}
+
+group("all") {
+  deps = [
+    "//foo:bar",
+    ":local",
+    "//foo",
+  ]
+}
+"#,
+            ),
+        ]);
+        let context = RequestContext::new_for_testing();
+        let goto = |line: u32, character: u32| {
+            let params = GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(workspace.path("BUILD.gn")).unwrap(),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                let Some(GotoDefinitionResponse::Scalar(location)) =
+                    goto_definition(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected response");
+                };
+                (location.uri, location.range.start)
             }
-        }))
+        };
+
+        let foo_uri = Url::from_file_path(workspace.path("foo/BUILD.gn")).unwrap();
+        let root_uri = Url::from_file_path(workspace.path("BUILD.gn")).unwrap();
+        assert_eq!(goto(5, 10).await, (foo_uri.clone(), Position::new(3, 0)));
+        // Labels without a directory refer to the current file.
+        assert_eq!(goto(6, 6).await, (root_uri, Position::new(0, 0)));
+        // Labels without a name refer to the target named after the
+        // directory.
+        assert_eq!(goto(7, 6).await, (foo_uri, Position::new(0, 0)));
     }
 
-    Ok(Some(GotoDefinitionResponse::Link(links)))
+    #[tokio::test]
+    async fn test_goto_definition_template_target_name() {
+        let workspace = TestWorkspace::new([(
+            "BUILD.gn",
+            "template(\"foo\") {\n  executable(target_name) {\n  }\n}\n",
+        )]);
+        let uri = Url::from_file_path(workspace.path("BUILD.gn")).unwrap();
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(1, 16),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        // Targets named by `target_name` are targets like string-named ones.
+        let response = goto_definition(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+        let Some(GotoDefinitionResponse::Scalar(location)) = response else {
+            panic!("unexpected response: {response:?}");
+        };
+        assert_eq!(location.uri, uri);
+        assert_eq!(
+            location.range,
+            Range::new(Position::new(1, 13), Position::new(1, 24))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_case_insensitive_labels() {
+        let workspace = TestWorkspace::new([
+            (
+                "Foo/BUILD.gn",
+                "group(\"foo\") {\n}\n\ngroup(\"Bar\") {\n}\n",
+            ),
+            (
+                "BUILD.gn",
+                "deps = [\n  \"//foo:bar\",\n  \"//FOO:BAR\",\n]\n",
+            ),
+        ]);
+        let context = RequestContext::new_for_testing();
+        context.analyzer.set_case_insensitive_paths(Some(true));
+        let goto = |line: u32| {
+            let params = GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(workspace.path("BUILD.gn")).unwrap(),
+                    },
+                    position: Position::new(line, 6),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                let Some(GotoDefinitionResponse::Scalar(location)) =
+                    goto_definition(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected response");
+                };
+                (location.uri, location.range.start)
+            }
+        };
+
+        // Both spellings resolve to the on-disk directory and one target.
+        let foo_uri = Url::from_file_path(workspace.path("Foo/BUILD.gn")).unwrap();
+        assert_eq!(goto(1).await, (foo_uri.clone(), Position::new(3, 0)));
+        assert_eq!(goto(2).await, (foo_uri, Position::new(3, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_pool_label() {
+        let workspace = TestWorkspace::new([
+            (
+                "build/BUILD.gn",
+                "toolchain(\"gcc\") {\n}\n\npool(\"link_pool\") {\n  depth = 4\n}\n",
+            ),
+            (
+                "BUILD.gn",
+                r#"action("gen") {
+  pool = "//build:link_pool"
+}
+
+action("gen_host") {
+  pool = "//build:link_pool(//build:gcc)"
+}
+"#,
+            ),
+        ]);
+        let context = RequestContext::new_for_testing();
+        let goto = |line: u32, character: u32| {
+            let params = GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(workspace.path("BUILD.gn")).unwrap(),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                let Some(GotoDefinitionResponse::Scalar(location)) =
+                    goto_definition(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected response");
+                };
+                (location.uri, location.range.start)
+            }
+        };
+
+        // Pools are declared like targets, so their labels link to them,
+        // even when qualified with a toolchain.
+        let build_uri = Url::from_file_path(workspace.path("build/BUILD.gn")).unwrap();
+        assert_eq!(goto(1, 12).await, (build_uri.clone(), Position::new(3, 0)));
+        assert_eq!(goto(5, 12).await, (build_uri, Position::new(3, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_scope_member() {
+        let workspace = TestWorkspace::new([(
+            "BUILD.gn",
+            r#"foo = {
+  bar = 1
+  if (true) {
+    baz = 2
+  }
+}
+foo.qux = 3
+a = foo.bar
+b = foo.baz
+c = foo.qux
+opaque = read_file("x.json", "json")
+d = opaque.member
+"#,
+        )]);
+        let context = RequestContext::new_for_testing();
+        let goto = |line: u32, character: u32| {
+            let params = GotoDefinitionParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(workspace.path("BUILD.gn")).unwrap(),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                let Some(GotoDefinitionResponse::Link(links)) =
+                    goto_definition(&context, params).await.unwrap()
+                else {
+                    panic!("unexpected response");
+                };
+                links
+                    .iter()
+                    .map(|link| link.target_selection_range.start)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(goto(7, 8).await, vec![Position::new(1, 2)]);
+        assert_eq!(goto(8, 8).await, vec![Position::new(3, 4)]);
+        assert_eq!(goto(9, 8).await, vec![Position::new(6, 4)]);
+        // Members of opaque scopes link to the scope itself.
+        assert_eq!(goto(11, 11).await, vec![Position::new(10, 0)]);
+    }
+
+    #[tokio::test]
+    async fn test_goto_definition_own_directory_label() {
+        let workspace = TestWorkspace::new([(
+            "foo/BUILD.gn",
+            r#"group("foo") {
+}
+
+group("all") {
+  deps = [ "//foo" ]
+}
+"#,
+        )]);
+        let context = RequestContext::new_for_testing();
+        let uri = Url::from_file_path(workspace.path("foo/BUILD.gn")).unwrap();
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(4, 14),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        // A label naming the current directory refers to the target named
+        // after it in the same file.
+        let Some(GotoDefinitionResponse::Scalar(location)) =
+            goto_definition(&context, params).await.unwrap()
+        else {
+            panic!("unexpected response");
+        };
+        assert_eq!(location.uri, uri);
+        assert_eq!(location.range.start, Position::new(0, 0));
+    }
 }
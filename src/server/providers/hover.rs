@@ -12,30 +12,288 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use tower_lsp::lsp_types::{Hover, HoverContents, HoverParams, MarkedString};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Weak},
+};
+
+use either::Either;
+use tower_lsp::lsp_types::{Hover, HoverContents, HoverParams, MarkedString, Position, Url};
 
 use crate::{
+    analyzer::{
+        evaluate_dot_gn, interpolate_constants, AnalyzedFile, AnalyzedLink, CacheNode, Variable,
+    },
     common::{builtins::BUILTINS, error::Result},
+    parser::{parse, AssignOp, Expr, Identifier, LValue, Node, PrimaryExpr, Statement},
     server::{
         providers::utils::{
-            format_template_help, format_variable_help, get_text_document_path,
-            lookup_identifier_at,
+            find_target, format_label, format_path, format_target_help, format_template_help,
+            format_variable_help, get_text_document_path, lookup_identifier_at,
         },
         RequestContext,
     },
 };
 
+/// Maximum number of hover results cached.
+const HOVER_CACHE_CAPACITY: usize = 32;
+
+type HoverCacheKey = (PathBuf, (u32, u32));
+
+/// Recently computed hover results for identifiers, keyed by position. A
+/// result is reused while the analysis it was computed from, which covers
+/// imported files, is still current.
+#[derive(Default)]
+pub struct HoverCache {
+    entries: HashMap<HoverCacheKey, HoverCacheEntry>,
+    clock: u64,
+    #[cfg(test)]
+    hits: usize,
+}
+
+struct HoverCacheEntry {
+    node: Weak<CacheNode>,
+    last_used: u64,
+    hover: Option<Hover>,
+}
+
+impl HoverCache {
+    fn get(&mut self, key: &HoverCacheKey, node: &Arc<CacheNode>) -> Option<Option<Hover>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        if !std::ptr::eq(entry.node.as_ptr(), Arc::as_ptr(node)) {
+            return None;
+        }
+        entry.last_used = self.clock;
+        #[cfg(test)]
+        {
+            self.hits += 1;
+        }
+        Some(entry.hover.clone())
+    }
+
+    fn insert(&mut self, key: HoverCacheKey, node: &Arc<CacheNode>, hover: Option<Hover>) {
+        // Drop results of analyses that were dropped or replaced.
+        self.entries.retain(|(path, _), entry| {
+            entry.node.strong_count() > 0
+                && (*path != key.0 || std::ptr::eq(entry.node.as_ptr(), Arc::as_ptr(node)))
+        });
+        if self.entries.len() >= HOVER_CACHE_CAPACITY {
+            if let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(
+            key,
+            HoverCacheEntry {
+                node: Arc::downgrade(node),
+                last_used: self.clock,
+                hover,
+            },
+        );
+    }
+}
+
+/// Shows where `buildconfig` in a `.gn` file points to.
+fn dot_gn_hover(context: &RequestContext, path: &Path, position: Position) -> Option<Hover> {
+    let workspace_root = path.parent()?;
+    let document = context.storage.lock().unwrap().read(path);
+    let offset = document.line_index.offset(position)?;
+    let ast = parse(&document.data);
+    let assignment = ast
+        .statements
+        .iter()
+        .find_map(|statement| match statement {
+            Statement::Assignment(assignment) => match &assignment.lvalue {
+                LValue::Identifier(identifier)
+                    if identifier.name == "buildconfig"
+                        && identifier.span.start() <= offset
+                        && offset <= identifier.span.end() =>
+                {
+                    Some(assignment)
+                }
+                _ => None,
+            },
+            _ => None,
+        })?;
+
+    let build_config = evaluate_dot_gn(workspace_root, &document.data).ok()?;
+    let uri = Url::from_file_path(&build_config).ok()?;
+    let LValue::Identifier(identifier) = &assignment.lvalue else {
+        return None;
+    };
+    Some(Hover {
+        contents: HoverContents::Array(vec![
+            MarkedString::from_markdown(format!("```gn\n{}\n```", assignment.span.as_str())),
+            MarkedString::from_markdown(format!(
+                "`buildconfig` resolves to [{}]({uri})",
+                format_path(&build_config, workspace_root)
+            )),
+        ]),
+        range: Some(document.line_index.range(identifier.span)),
+    })
+}
+
+/// Explains what `defined()` checks if the identifier is its argument.
+fn defined_argument_note(current_file: &AnalyzedFile, ident: &Identifier) -> Option<String> {
+    let arg = current_file
+        .ast
+        .walk()
+        .filter_map(|node| node.as_call())
+        .filter(|call| call.function.name == "defined")
+        .filter_map(|call| call.only_arg())
+        .find(|arg| {
+            arg.span().start() <= ident.span.start() && ident.span.end() <= arg.span().end()
+        })?;
+    match arg.as_primary()? {
+        PrimaryExpr::Identifier(identifier) => Some(format!(
+            "`defined({0})` is true if the variable `{0}` is defined in the current scope \
+             or an enclosing one",
+            identifier.name
+        )),
+        PrimaryExpr::ScopeAccess(access) => Some(format!(
+            "`defined({0}.{1})` is true if the scope `{0}` is defined and has the member `{1}`",
+            access.scope.name, access.member.name
+        )),
+        _ => None,
+    }
+}
+
+/// Maximum number of items in a list literal whose value is shown.
+const MAX_LIST_VALUE_ITEMS: usize = 8;
+
+/// Evaluates a literal, resolving interpolations of constant strings in scope.
+fn evaluate_literal(current_file: &AnalyzedFile, expr: &Expr) -> Option<String> {
+    match expr.as_primary()? {
+        PrimaryExpr::Integer(integer) => Some(integer.value.to_string()),
+        PrimaryExpr::Identifier(identifier) if matches!(identifier.name, "true" | "false") => {
+            Some(identifier.name.to_string())
+        }
+        PrimaryExpr::String(string) => {
+            let value = interpolate_constants(string, &|name, offset| {
+                let variables = current_file.variables_at(offset);
+                variables.get(name)?.constant_string().map(str::to_string)
+            })?;
+            Some(format!("\"{value}\""))
+        }
+        PrimaryExpr::List(list) if list.values.len() <= MAX_LIST_VALUE_ITEMS => {
+            let items: Vec<String> = list
+                .values
+                .iter()
+                .map(|item| evaluate_literal(current_file, item))
+                .collect::<Option<_>>()?;
+            if items.is_empty() {
+                Some("[]".to_string())
+            } else {
+                Some(format!("[ {} ]", items.join(", ")))
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Describes the value of a variable assigned once with a literal if it
+/// differs from the assignment as written, e.g. with interpolations resolved.
+fn constant_value_note(current_file: &AnalyzedFile, variable: &Variable) -> Option<String> {
+    if variable.assignments.len() != 1 {
+        return None;
+    }
+    let Either::Left(assignment) = variable.assignments.values().next()?.assignment_or_call else {
+        return None;
+    };
+    if assignment.op != AssignOp::Assign {
+        return None;
+    }
+    let value = evaluate_literal(current_file, &assignment.rvalue)?;
+    (value != assignment.rvalue.span().as_str()).then(|| format!("Value: `{value}`"))
+}
+
 pub async fn hover(context: &RequestContext, params: HoverParams) -> Result<Option<Hover>> {
     let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    if path.file_name().is_some_and(|name| name == ".gn") {
+        return Ok(dot_gn_hover(
+            context,
+            &path,
+            params.text_document_position_params.position,
+        ));
+    }
     let current_file = context
         .analyzer
         .analyze(&path, &context.finder, context.request_time)?;
 
-    let Some(ident) =
-        lookup_identifier_at(&current_file, params.text_document_position_params.position)
-    else {
-        return Ok(None);
-    };
+    // Check target labels.
+    if let Some(offset) = current_file
+        .document
+        .line_index
+        .offset(params.text_document_position_params.position)
+    {
+        if let Some(AnalyzedLink::Target { path, name, span }) = current_file
+            .links
+            .iter()
+            .find(|link| link.span().start() <= offset && offset <= link.span().end())
+        {
+            let target_file =
+                context
+                    .analyzer
+                    .analyze_shallow(path, &context.finder, context.request_time)?;
+            let Some(target) = find_target(&target_file, name) else {
+                return Ok(None);
+            };
+            let label = format_label(
+                path,
+                target.name,
+                &current_file.workspace_root,
+                current_file
+                    .document
+                    .path
+                    .parent()
+                    .unwrap_or(&current_file.workspace_root),
+                context.client.configurations().await.display.label_style,
+            );
+            return Ok(Some(Hover {
+                contents: HoverContents::Array(
+                    format_target_help(target, &label, &current_file.workspace_root)
+                        .into_iter()
+                        .map(MarkedString::from_markdown)
+                        .collect(),
+                ),
+                range: Some(current_file.document.line_index.range(*span)),
+            }));
+        }
+    }
+
+    // Hover is requested repeatedly as the mouse moves, so reuse results for
+    // the same position while the analysis is unchanged.
+    let position = params.text_document_position_params.position;
+    let key = (path, (position.line, position.character));
+    if let Some(hover) = context
+        .hover_cache
+        .lock()
+        .unwrap()
+        .get(&key, &current_file.node)
+    {
+        return Ok(hover);
+    }
+    let hover = identifier_hover(&current_file, position);
+    context
+        .hover_cache
+        .lock()
+        .unwrap()
+        .insert(key, &current_file.node, hover.clone());
+    Ok(hover)
+}
+
+/// Describes templates, variables and builtins named by the identifier at a
+/// position.
+fn identifier_hover(current_file: &AnalyzedFile, position: Position) -> Option<Hover> {
+    let ident = lookup_identifier_at(current_file, position)?;
 
     let mut sections: Vec<Vec<MarkedString>> = Vec::new();
 
@@ -53,8 +311,12 @@ pub async fn hover(context: &RequestContext, params: HoverParams) -> Result<Opti
     // Check variables.
     let variables = current_file.variables_at(ident.span.start());
     if let Some(variable) = variables.get(ident.name) {
+        let mut paragraphs = format_variable_help(variable, &current_file.workspace_root);
+        if let Some(note) = constant_value_note(current_file, variable) {
+            paragraphs.insert(1, note);
+        }
         sections.push(
-            format_variable_help(variable, &current_file.workspace_root)
+            paragraphs
                 .into_iter()
                 .map(MarkedString::from_markdown)
                 .collect(),
@@ -66,25 +328,37 @@ pub async fn hover(context: &RequestContext, params: HoverParams) -> Result<Opti
         sections.push(vec![MarkedString::from_markdown(symbol.doc.to_string())]);
     }
 
+    // Check arguments of defined(), which may well be undefined.
+    if let Some(note) = defined_argument_note(current_file, ident) {
+        sections.push(vec![MarkedString::from_markdown(note)]);
+    }
+
     if sections.is_empty() {
-        return Ok(None);
+        return None;
     }
 
     let contents = sections.join(&MarkedString::from_markdown("---".to_string()));
-    Ok(Some(Hover {
+    Some(Hover {
         contents: HoverContents::Array(contents),
         range: Some(current_file.document.line_index.range(ident.span)),
-    }))
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Instant;
+
     use tower_lsp::lsp_types::{
-        Position, Range, TextDocumentIdentifier, TextDocumentPositionParams, Url,
-        WorkDoneProgressParams,
+        Range, TextDocumentIdentifier, TextDocumentPositionParams, WorkDoneProgressParams,
     };
 
-    use crate::common::testutils::testdata;
+    use tower_lsp::jsonrpc::ErrorCode;
+
+    use crate::common::{
+        config::{Configurations, DisplayConfigurations, LabelStyle},
+        error::{Error, RpcError, WORKSPACE_NOT_FOUND_ERROR_CODE},
+        testutils::testdata,
+    };
 
     use super::*;
 
@@ -129,4 +403,451 @@ mod tests {
             })
         );
     }
+
+    #[tokio::test]
+    async fn test_hover_defined_argument() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/defined.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "if (defined(foo) && defined(invoker.bar)) {\n}\n",
+            1,
+        );
+        let hover_at = |character: u32| {
+            let params = HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position: Position::new(0, character),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            };
+            let context = context.clone();
+            async move { hover(&context, params).await.unwrap().unwrap().contents }
+        };
+
+        assert_eq!(
+            hover_at(13).await,
+            HoverContents::Array(vec![MarkedString::from_markdown(
+                "`defined(foo)` is true if the variable `foo` is defined in the current scope \
+                 or an enclosing one"
+                    .to_string()
+            )])
+        );
+        let HoverContents::Array(contents) = hover_at(37).await else {
+            panic!("unexpected hover contents");
+        };
+        assert_eq!(
+            contents.last(),
+            Some(&MarkedString::from_markdown(
+                "`defined(invoker.bar)` is true if the scope `invoker` is defined and has the \
+                 member `bar`"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_constant_value() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/constant.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "prefix = \"foo\"\nb = \"${prefix}_bar\"\nc = [1, \"$prefix\"]\nd = \"$unknown/x\"\n",
+            1,
+        );
+        let hover_at = |line: u32| {
+            let params = HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position: Position::new(line, 0),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                let HoverContents::Array(contents) =
+                    hover(&context, params).await.unwrap().unwrap().contents
+                else {
+                    panic!("unexpected hover contents");
+                };
+                contents
+            }
+        };
+
+        assert_eq!(
+            hover_at(1).await[1],
+            MarkedString::from_markdown("Value: `\"foo_bar\"`".to_string())
+        );
+        assert_eq!(
+            hover_at(2).await[1],
+            MarkedString::from_markdown("Value: `[ 1, \"foo\" ]`".to_string())
+        );
+        // Unknown interpolations fall back to the snippet alone, as do
+        // values written in their normalized form.
+        for line in [0, 3] {
+            assert!(!hover_at(line).await.iter().any(|content| matches!(
+                content,
+                MarkedString::String(value) if value.starts_with("Value:")
+            )));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hover_trailing_comment() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/trailing.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "# Leading doc.\na = 1  # Trailing note.\nb = 2\n",
+            1,
+        );
+        let hover_at = |line: u32| {
+            let params = HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position: Position::new(line, 0),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            };
+            let context = context.clone();
+            async move {
+                let HoverContents::Array(contents) =
+                    hover(&context, params).await.unwrap().unwrap().contents
+                else {
+                    panic!("unexpected hover contents");
+                };
+                contents
+            }
+        };
+
+        assert_eq!(
+            hover_at(1).await[1],
+            MarkedString::from_markdown("```text\nLeading doc.\nTrailing note.\n```".to_string())
+        );
+        // The trailing comment is not documentation of the next statement.
+        assert_eq!(
+            hover_at(2).await[1],
+            MarkedString::from_markdown("```text\n\n```".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_cache() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/cache.gn");
+        let hover_at = |character: u32| {
+            let params = HoverParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position: Position::new(0, character),
+                },
+                work_done_progress_params: WorkDoneProgressParams::default(),
+            };
+            // Each request verifies the analysis anew, as in the server.
+            let context = RequestContext {
+                request_time: Instant::now(),
+                ..context.clone()
+            };
+            async move { hover(&context, params).await.unwrap() }
+        };
+        let hits = || context.hover_cache.lock().unwrap().hits;
+
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "foo = 1\n", 1);
+        let first = hover_at(0).await;
+        assert_eq!(hits(), 0);
+        // An identical request reuses the result.
+        assert_eq!(hover_at(0).await, first);
+        assert_eq!(hits(), 1);
+        // A different position is computed anew.
+        hover_at(4).await;
+        assert_eq!(hits(), 1);
+
+        // A new version invalidates the result.
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, "foo = 2\n", 2);
+        let second = hover_at(0).await;
+        assert_eq!(hits(), 1);
+        assert_ne!(second, first);
+
+        // So does a change in an imported file.
+        let imported_path = testdata("workspaces/hover/cache_imported.gni");
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&imported_path, "bar = 1\n", 1);
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "import(\"cache_imported.gni\")\nfoo = bar\n",
+            3,
+        );
+        let before = hover_at(0).await;
+        assert_eq!(hover_at(0).await, before);
+        assert_eq!(hits(), 2);
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&imported_path, "bar = 2\n", 2);
+        hover_at(0).await;
+        assert_eq!(hits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_hover_many_assignments() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/many_assignments.gn");
+        let data = format!("foo = 0\n{}", "foo += 1\n".repeat(99));
+        context
+            .storage
+            .lock()
+            .unwrap()
+            .load_to_memory(&path, &data, 1);
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(0, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let HoverContents::Array(contents) =
+            hover(&context, params).await.unwrap().unwrap().contents
+        else {
+            panic!("unexpected hover contents");
+        };
+        let Some(MarkedString::String(locations)) = contents.last() else {
+            panic!("unexpected hover contents");
+        };
+        let lines: Vec<&str> = locations.lines().collect();
+        assert_eq!(lines[0], "Defined and modified in 100 locations:");
+        assert_eq!(lines.len(), 8);
+        assert!(lines[2].starts_with("- [//"));
+        assert!(lines[2].contains(":1:1]"));
+        assert!(lines[6].contains(":5:1]"));
+        assert_eq!(lines[7], "- and 95 more");
+    }
+
+    #[tokio::test]
+    async fn test_hover_foreach_scopes() {
+        let uri = Url::from_file_path(testdata("workspaces/hover/BUILD.gn")).unwrap();
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position {
+                    line: 49,
+                    character: 8,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let response = hover(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            response.contents,
+            HoverContents::Array(vec![
+                MarkedString::from_markdown("```gn\nforeach(item, ...)\n```".to_string()),
+                MarkedString::from_markdown(
+                    "Iterates over `scope` items with members `name`, `value`".to_string()
+                ),
+                MarkedString::from_markdown("```text\n\n```".to_string()),
+                MarkedString::from_markdown(format!("Defined at [//BUILD.gn:38:1]({uri}#L38,1)")),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_target_label() {
+        let uri = Url::from_file_path(testdata("workspaces/links/BUILD.gn")).unwrap();
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position {
+                    line: 19,
+                    character: 6,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let response = hover(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response,
+            Some(Hover {
+                contents: HoverContents::Array(vec![
+                    MarkedString::from_markdown("```gn\ngroup(\"valid\") { ... }\n```".to_string()),
+                    MarkedString::from_markdown("Label: `//:valid`".to_string()),
+                    MarkedString::from_markdown(format!(
+                        "Defined at [//BUILD.gn:15:1]({uri}#L15,1)"
+                    )),
+                ]),
+                range: Some(Range {
+                    start: Position {
+                        line: 19,
+                        character: 4,
+                    },
+                    end: Position {
+                        line: 19,
+                        character: 12,
+                    },
+                }),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_label_style() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/links/BUILD.gn");
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position {
+                    line: 19,
+                    character: 6,
+                },
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let mut labels = Vec::new();
+        for label_style in [LabelStyle::Absolute, LabelStyle::Relative] {
+            context.client.set_configurations(Configurations {
+                display: DisplayConfigurations { label_style },
+                ..Default::default()
+            });
+            let response = hover(&context, params.clone()).await.unwrap().unwrap();
+            let HoverContents::Array(contents) = response.contents else {
+                panic!("unexpected hover contents");
+            };
+            labels.push(contents[1].clone());
+        }
+
+        assert_eq!(
+            labels,
+            [
+                MarkedString::from_markdown("Label: `//:valid`".to_string()),
+                MarkedString::from_markdown("Label: `:valid`".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_dot_gn_buildconfig() {
+        let root = testdata("workspaces/hover");
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(root.join(".gn")).unwrap(),
+                },
+                position: Position::new(14, 3),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let response = hover(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+
+        let build_config_uri = Url::from_file_path(root.join("BUILDCONFIG.gn")).unwrap();
+        assert_eq!(
+            response,
+            Some(Hover {
+                contents: HoverContents::Array(vec![
+                    MarkedString::from_markdown(
+                        "```gn\nbuildconfig = \"//BUILDCONFIG.gn\"\n```".to_string()
+                    ),
+                    MarkedString::from_markdown(format!(
+                        "`buildconfig` resolves to [//BUILDCONFIG.gn]({build_config_uri})"
+                    )),
+                ]),
+                range: Some(Range::new(Position::new(14, 0), Position::new(14, 11))),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_missing_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("missing/BUILD.gn");
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(0, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let error = hover(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&error, Error::WorkspaceNotFound(error_path) if *error_path == path),
+            "{error:?}"
+        );
+
+        let rpc_error = RpcError::from(error);
+        assert_eq!(
+            rpc_error.code,
+            ErrorCode::ServerError(WORKSPACE_NOT_FOUND_ERROR_CODE)
+        );
+        assert_eq!(
+            rpc_error.message,
+            format!("Workspace not found for {}", path.display())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hover_private_template() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/hover/private.gni");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "template(\"_private\") {\n}\n_private(\"x\") {\n}\n",
+            1,
+        );
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(&path).unwrap(),
+                },
+                position: Position::new(2, 2),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let response = hover(&context, params).await.unwrap().unwrap();
+        assert_eq!(
+            response.range,
+            Some(Range::new(Position::new(2, 0), Position::new(2, 8)))
+        );
+    }
 }
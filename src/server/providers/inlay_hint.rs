@@ -0,0 +1,259 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{InlayHint, InlayHintKind, InlayHintLabel, InlayHintParams, Range};
+
+use crate::{
+    analyzer::{AnalyzedFile, AnalyzedLink, AnalyzedStatement, Template, TopLevelStatementsExt},
+    common::{
+        builtins::{BUILTINS, DECLARE_ARGS, FOREACH, SET_DEFAULTS, TEMPLATE},
+        config::LabelStyle,
+        error::Result,
+    },
+    parser::Statement,
+    server::{
+        providers::utils::{format_label, get_text_document_path},
+        RequestContext,
+    },
+};
+
+/// Returns the builtin target type a template expands to, if its body
+/// defines exactly one target.
+fn template_target_type(template: &Template) -> Option<&'static str> {
+    let body = template.call.block.as_ref()?;
+    let mut calls = body
+        .top_level_statements()
+        .filter_map(|statement| match statement {
+            Statement::Call(call)
+                if call.block.is_some()
+                    && ![TEMPLATE, SET_DEFAULTS, DECLARE_ARGS, FOREACH]
+                        .contains(&call.function.name) =>
+            {
+                Some(call.function.name)
+            }
+            _ => None,
+        });
+    let name = calls.next()?;
+    if calls.next().is_some() {
+        return None;
+    }
+    BUILTINS
+        .targets
+        .iter()
+        .find(|symbol| symbol.name == name)
+        .map(|symbol| symbol.name)
+}
+
+fn template_type_hints(current_file: &AnalyzedFile, range: Range) -> Vec<InlayHint> {
+    let line_index = &current_file.document.line_index;
+    current_file
+        .analyzed_root
+        .top_level_statements()
+        .filter_map(|statement| match statement {
+            AnalyzedStatement::Target(target) => Some(target.call),
+            _ => None,
+        })
+        .filter_map(|call| {
+            let position = line_index.position(call.span.start());
+            if position < range.start || range.end < position {
+                return None;
+            }
+            let templates = current_file.templates_at(call.span.start());
+            let target_type = template_target_type(templates.get(call.function.name)?)?;
+            Some(InlayHint {
+                position,
+                label: InlayHintLabel::String(target_type.to_string()),
+                kind: Some(InlayHintKind::TYPE),
+                text_edits: None,
+                tooltip: None,
+                padding_left: None,
+                padding_right: Some(true),
+                data: None,
+            })
+        })
+        .collect()
+}
+
+/// Returns hints showing the absolute form of labels written otherwise, e.g.
+/// `//foo:bar` after `":bar"`.
+fn label_hints(current_file: &AnalyzedFile, range: Range) -> Vec<InlayHint> {
+    let line_index = &current_file.document.line_index;
+    current_file
+        .links
+        .iter()
+        .filter_map(|link| {
+            let AnalyzedLink::Target { path, name, span } = link else {
+                return None;
+            };
+            let position = line_index.position(span.end());
+            if position < range.start || range.end < position {
+                return None;
+            }
+            let label = format_label(
+                path,
+                name,
+                &current_file.workspace_root,
+                &current_file.workspace_root,
+                LabelStyle::Absolute,
+            );
+            if span.as_str().trim_matches('"') == label {
+                return None;
+            }
+            Some(InlayHint {
+                position,
+                label: InlayHintLabel::String(label),
+                kind: None,
+                text_edits: None,
+                tooltip: None,
+                padding_left: Some(true),
+                padding_right: None,
+                data: None,
+            })
+        })
+        .collect()
+}
+
+pub async fn inlay_hint(
+    context: &RequestContext,
+    params: InlayHintParams,
+) -> Result<Option<Vec<InlayHint>>> {
+    let experimental = context.client.configurations().await.experimental;
+    if !experimental.template_type_hints && !experimental.label_inlay_hints {
+        return Ok(None);
+    }
+
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+
+    let mut hints = Vec::new();
+    if experimental.template_type_hints {
+        hints.extend(template_type_hints(&current_file, params.range));
+    }
+    if experimental.label_inlay_hints {
+        hints.extend(label_hints(&current_file, params.range));
+    }
+    hints.sort_by_key(|hint| hint.position);
+
+    Ok(Some(hints))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        Position, Range, TextDocumentIdentifier, Url, WorkDoneProgressParams,
+    };
+
+    use crate::common::{
+        config::{Configurations, ExperimentalConfigurations},
+        testutils::{testdata, TestWorkspace},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_template_type_hints() {
+        let context = RequestContext::new_for_testing();
+        let params = InlayHintParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(testdata("workspaces/inlay_hints/BUILD.gn")).unwrap(),
+            },
+            range: Range::new(Position::new(0, 0), Position::new(100, 0)),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        // Hints are disabled by default.
+        assert!(inlay_hint(&context, params.clone())
+            .await
+            .unwrap()
+            .is_none());
+
+        context.client.set_configurations(Configurations {
+            experimental: ExperimentalConfigurations {
+                template_type_hints: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        // Only the template wrapping a single executable gets a hint.
+        let hints = inlay_hint(&context, params).await.unwrap().unwrap();
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].position, Position::new(27, 0));
+        assert!(
+            matches!(&hints[0].label, InlayHintLabel::String(label) if label == "executable"),
+            "{:?}",
+            hints[0].label
+        );
+        assert_eq!(hints[0].kind, Some(InlayHintKind::TYPE));
+    }
+
+    #[tokio::test]
+    async fn test_label_hints() {
+        let workspace = TestWorkspace::new([
+            ("base/BUILD.gn", "group(\"base\") {\n}\n"),
+            (
+                "app/BUILD.gn",
+                r#"group("app") {
+  deps = [
+    ":lib",
+    "//base",
+    "//base:base",
+  ]
+}
+
+group("lib") {
+}
+"#,
+            ),
+        ]);
+        let context = RequestContext::new_for_testing();
+        context.client.set_configurations(Configurations {
+            experimental: ExperimentalConfigurations {
+                label_inlay_hints: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let params = InlayHintParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(workspace.path("app/BUILD.gn")).unwrap(),
+            },
+            range: Range::new(Position::new(0, 0), Position::new(100, 0)),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        // Labels already in the absolute form get no hint.
+        let hints: Vec<_> = inlay_hint(&context, params)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|hint| {
+                let InlayHintLabel::String(label) = hint.label else {
+                    panic!("unexpected label parts");
+                };
+                (hint.position, label)
+            })
+            .collect();
+        assert_eq!(
+            hints,
+            [
+                (Position::new(2, 10), "//app:lib".to_string()),
+                (Position::new(3, 12), "//base:base".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use crate::{
+    analyzer::ArgInfo,
+    common::error::{Error, Result},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Returns build arguments declared in the workspace containing a file.
+pub async fn list_args(
+    context: &RequestContext,
+    text_document: &TextDocumentIdentifier,
+) -> Result<Vec<ArgInfo>> {
+    let path = get_text_document_path(text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+
+    // Wait for the workspace indexing to finish.
+    let workspace_root = &current_file.workspace_root;
+    let Some(indexed) = context.indexed.lock().unwrap().get(workspace_root).cloned() else {
+        return Err(Error::General(format!(
+            "Indexing for {} not started",
+            workspace_root.display()
+        )));
+    };
+    indexed.wait().await;
+
+    Ok(context.analyzer.build_args(workspace_root))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Range, Url};
+
+    use crate::{
+        common::{testutils::testdata, utils::AsyncSignal},
+        server::indexing::index,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_list_args() {
+        let context = RequestContext::new_for_testing();
+        let workspace_root = testdata("workspaces/list_args");
+        let mut signal = AsyncSignal::new();
+        context
+            .indexed
+            .lock()
+            .unwrap()
+            .insert(workspace_root.clone(), signal.clone());
+        index(&context, &workspace_root).await;
+        signal.set();
+
+        let text_document = TextDocumentIdentifier {
+            uri: Url::from_file_path(workspace_root.join("BUILD.gn")).unwrap(),
+        };
+        let args = list_args(&context, &text_document).await.unwrap();
+
+        let arg = |name: &str, default_value: &str, path: &str, line: u32, len: u32| ArgInfo {
+            name: name.to_string(),
+            default_value: Some(default_value.to_string()),
+            path: workspace_root.join(path),
+            range: Range::new(Position::new(line, 2), Position::new(line, 2 + len)),
+        };
+        assert_eq!(
+            args,
+            vec![
+                arg("enable_foo", "false", "BUILD.gn", 17, 10),
+                arg("foo_level", "2", "BUILD.gn", 21, 9),
+                arg("use_bar", "true", "bar/args.gni", 15, 7),
+            ]
+        );
+    }
+}
@@ -12,15 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod code_action;
 pub mod completion;
 pub mod configuration;
 pub mod diagnostics;
 pub mod document;
+pub mod document_highlight;
 pub mod document_link;
 pub mod document_symbol;
+pub mod execute_command;
+pub mod folding_range;
 pub mod formatting;
+pub mod gn_gen;
 pub mod goto_definition;
 pub mod hover;
+pub mod inlay_hint;
+pub mod list_args;
+pub mod on_type_formatting;
 pub mod references;
+pub mod rename;
+pub mod self_test;
+pub mod semantic_tokens;
+pub mod signature_help;
+pub mod unresolved_imports;
 pub mod utils;
+pub mod workspace_diagnostics;
 pub mod workspace_symbol;
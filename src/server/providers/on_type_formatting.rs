@@ -0,0 +1,131 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{
+    DocumentOnTypeFormattingParams, Position, Range, TextDocumentIdentifier, TextEdit,
+};
+
+use crate::{
+    common::error::Result,
+    parser::{Block, Node},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Indentation per nesting level, as `gn format` uses.
+const INDENT_WIDTH: usize = 2;
+
+/// Counts blocks and lists enclosing `offset`. Closing brackets are at the
+/// level of their opening line.
+fn nesting_depth(ast: &Block, offset: usize) -> usize {
+    // Skip the root block spanning the whole file.
+    ast.walk()
+        .skip(1)
+        .filter_map(|node| {
+            node.as_block()
+                .map(|block| block.span)
+                .or_else(|| node.as_list().map(|list| list.span))
+        })
+        .filter(|span| span.start() < offset && offset < span.end() - 1)
+        .count()
+}
+
+pub async fn on_type_formatting(
+    context: &RequestContext,
+    params: DocumentOnTypeFormattingParams,
+) -> Result<Option<Vec<TextEdit>>> {
+    let path = get_text_document_path(&TextDocumentIdentifier {
+        uri: params.text_document_position.text_document.uri,
+    })?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+    let data: &str = &current_file.document.data;
+    let line = params.text_document_position.position.line;
+    let Some(line_start) = current_file
+        .document
+        .line_index
+        .offset(Position::new(line, 0))
+    else {
+        return Ok(None);
+    };
+
+    let line_end = data[line_start..]
+        .find('\n')
+        .map_or(data.len(), |pos| line_start + pos);
+    let text = &data[line_start..line_end];
+    let content = text.trim_start_matches([' ', '\t']);
+    // Re-indent only closing braces starting a line, not inline blocks.
+    if params.ch == "}" && !content.starts_with('}') {
+        return Ok(None);
+    }
+
+    let current_indent = &text[..text.len() - content.len()];
+    let first = line_start + current_indent.len();
+    let indent = " ".repeat(nesting_depth(&current_file.ast, first) * INDENT_WIDTH);
+    if current_indent == indent {
+        return Ok(Some(Vec::new()));
+    }
+    Ok(Some(vec![TextEdit {
+        range: Range::new(
+            Position::new(line, 0),
+            current_file.document.line_index.position(first),
+        ),
+        new_text: indent,
+    }]))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{FormattingOptions, TextDocumentPositionParams, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_on_type_formatting() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/smoke/on_type_formatting.gn");
+        context.storage.lock().unwrap().load_to_memory(
+            &path,
+            "group(\"a\") {\ndeps = [\n\":b\",\n  ]\n    }\nx = {}\n",
+            1,
+        );
+        let format_at = |line: u32, character: u32, ch: &str| {
+            let params = DocumentOnTypeFormattingParams {
+                text_document_position: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: Url::from_file_path(&path).unwrap(),
+                    },
+                    position: Position::new(line, character),
+                },
+                ch: ch.to_string(),
+                options: FormattingOptions::default(),
+            };
+            let context = context.clone();
+            async move { on_type_formatting(&context, params).await.unwrap() }
+        };
+        let edit = |line: u32, end: u32, new_text: &str| TextEdit {
+            range: Range::new(Position::new(line, 0), Position::new(line, end)),
+            new_text: new_text.to_string(),
+        };
+
+        assert_eq!(format_at(1, 0, "\n").await, Some(vec![edit(1, 0, "  ")]));
+        assert_eq!(format_at(2, 0, "\n").await, Some(vec![edit(2, 0, "    ")]));
+        assert_eq!(format_at(3, 3, "\n").await, Some(Vec::new()));
+        assert_eq!(format_at(4, 5, "}").await, Some(vec![edit(4, 4, "")]));
+        // Inline blocks are left as they are.
+        assert_eq!(format_at(5, 6, "}").await, None);
+    }
+}
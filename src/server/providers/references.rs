@@ -12,17 +12,89 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::path::Path;
+
 use tower_lsp::lsp_types::{Location, ReferenceParams, Url};
 
 use crate::{
-    analyzer::{AnalyzedBlock, AnalyzedFile, AnalyzedLink},
-    common::error::{Error, Result},
+    analyzer::{AnalyzedBlock, AnalyzedFile, AnalyzedLink, Target},
+    common::{error::Result, storage::Document},
+    parser::{Identifier, Node},
     server::{
-        providers::utils::{get_text_document_path, lookup_target_name_string_at},
+        providers::utils::{
+            get_text_document_path, is_same_variable, lookup_identifier_at,
+            lookup_target_header_at, lookup_target_name_string_at,
+        },
         RequestContext,
     },
 };
 
+fn sort_and_dedup(mut locations: Vec<Location>) -> Vec<Location> {
+    locations.sort_by(|a, b| {
+        (a.uri.as_str(), a.range.start, a.range.end).cmp(&(
+            b.uri.as_str(),
+            b.range.start,
+            b.range.end,
+        ))
+    });
+    locations.dedup();
+    locations
+}
+
+fn variable_references(
+    current_file: &AnalyzedFile,
+    ident: &Identifier,
+    include_declaration: bool,
+) -> Vec<Location> {
+    let variables = current_file.variables_at(ident.span.start());
+    let Some(variable) = variables.get(ident.name) else {
+        return Vec::new();
+    };
+
+    // Assignments are the declarations of a variable.
+    let is_declaration = |other: &Identifier| {
+        variable.assignments.values().any(|assignment| {
+            assignment.document.path == current_file.document.path
+                && assignment.primary_variable.start() == other.span.start()
+                && assignment.primary_variable.end() == other.span.end()
+        })
+    };
+
+    let uri = Url::from_file_path(&current_file.document.path).unwrap();
+    let mut references: Vec<Location> = Vec::new();
+    if include_declaration {
+        references.extend(variable.assignments.values().map(|assignment| {
+            Location {
+                uri: Url::from_file_path(&assignment.document.path).unwrap(),
+                range: assignment
+                    .document
+                    .line_index
+                    .range(assignment.primary_variable),
+            }
+        }));
+    }
+
+    references.extend(
+        current_file
+            .ast
+            .identifiers()
+            .filter(|other| other.name == ident.name)
+            .filter(|other| include_declaration || !is_declaration(other))
+            .filter(|other| {
+                current_file
+                    .variables_at(other.span.start())
+                    .get(other.name)
+                    .is_some_and(|other_variable| is_same_variable(variable, other_variable))
+            })
+            .map(|other| Location {
+                uri: uri.clone(),
+                range: current_file.document.line_index.range(other.span),
+            }),
+    );
+
+    references
+}
+
 fn get_overlapping_targets<'i>(root: &AnalyzedBlock<'i, '_>, prefix: &str) -> Vec<&'i str> {
     root.targets()
         .filter(|target| target.name.len() > prefix.len() && target.name.starts_with(prefix))
@@ -30,51 +102,86 @@ fn get_overlapping_targets<'i>(root: &AnalyzedBlock<'i, '_>, prefix: &str) -> Ve
         .collect()
 }
 
+/// Collects label strings in a file that resolve to a target.
+fn collect_label_references(
+    document: &Document,
+    links: &[AnalyzedLink],
+    target_path: &Path,
+    target_name: &str,
+    bad_prefixes: &[&str],
+    references: &mut Vec<Location>,
+) {
+    for link in links {
+        let AnalyzedLink::Target { path, name, span } = link else {
+            continue;
+        };
+        if path != target_path {
+            continue;
+        }
+        if bad_prefixes
+            .iter()
+            .any(|bad_prefix| name.starts_with(bad_prefix))
+        {
+            continue;
+        }
+        if !name.starts_with(target_name) {
+            continue;
+        }
+        references.push(Location {
+            uri: Url::from_file_path(&document.path).unwrap(),
+            range: document.line_index.range(*span),
+        });
+    }
+}
+
 async fn target_references(
     context: &RequestContext,
     current_file: &AnalyzedFile,
-    target_name: &str,
+    target: &Target<'_, '_>,
+    include_declaration: bool,
 ) -> Result<Option<Vec<Location>>> {
+    let target_name = target.name;
     let bad_prefixes = get_overlapping_targets(&current_file.analyzed_root, target_name);
-
-    // Wait for the workspace indexing to finish.
-    let workspace_root = &current_file.workspace_root;
-    let Some(indexed) = context.indexed.lock().unwrap().get(workspace_root).cloned() else {
-        return Err(Error::General(format!(
-            "Indexing for {} not started",
-            workspace_root.display()
-        )));
-    };
-    indexed.wait().await;
-
-    let cached_files = context.analyzer.cached_files(workspace_root);
+    let target_path = &current_file.document.path;
 
     let mut references: Vec<Location> = Vec::new();
-    for file in cached_files {
-        for link in &file.links {
-            let AnalyzedLink::Target { path, name, span } = link else {
-                continue;
-            };
-            if path != &current_file.document.path {
-                continue;
-            }
-            if bad_prefixes
-                .iter()
-                .any(|bad_prefix| name.starts_with(bad_prefix))
-            {
-                continue;
-            }
-            if !name.starts_with(target_name) {
-                continue;
-            }
-            references.push(Location {
-                uri: Url::from_file_path(&file.document.path).unwrap(),
-                range: file.document.line_index.range(*span),
-            });
+    if include_declaration {
+        references.push(Location {
+            uri: Url::from_file_path(target_path).unwrap(),
+            range: current_file
+                .document
+                .line_index
+                .range(target.call.args[0].span()),
+        });
+    }
+    let workspace_root = &current_file.workspace_root;
+    let indexed = context.indexed.lock().unwrap().get(workspace_root).cloned();
+    if let Some(indexed) = indexed {
+        // Wait for the workspace indexing to finish.
+        indexed.wait().await;
+        for file in context.analyzer.cached_files(workspace_root) {
+            collect_label_references(
+                &file.document,
+                &file.links,
+                target_path,
+                target_name,
+                &bad_prefixes,
+                &mut references,
+            );
         }
+    } else {
+        // Without indexing, only labels in the current file are known.
+        collect_label_references(
+            &current_file.document,
+            &current_file.links,
+            target_path,
+            target_name,
+            &bad_prefixes,
+            &mut references,
+        );
     }
 
-    Ok(Some(references))
+    Ok(Some(sort_and_dedup(references)))
 }
 
 pub async fn references(
@@ -93,9 +200,218 @@ pub async fn references(
 
     let position = params.text_document_position.position;
 
-    if let Some(target) = lookup_target_name_string_at(&current_file, position) {
-        return target_references(context, &current_file, target.name).await;
+    // Label strings pointing to the target are its references, whether the
+    // cursor is on the name or elsewhere in the target header.
+    if let Some(target) = lookup_target_name_string_at(&current_file, position)
+        .or_else(|| lookup_target_header_at(&current_file, position))
+        .filter(|target| !target.implicit_name)
+    {
+        return target_references(
+            context,
+            &current_file,
+            &target,
+            params.context.include_declaration,
+        )
+        .await;
     };
 
+    if let Some(ident) = lookup_identifier_at(&current_file, position) {
+        return Ok(Some(sort_and_dedup(variable_references(
+            &current_file,
+            ident,
+            params.context.include_declaration,
+        ))));
+    }
+
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        PartialResultParams, Position, Range, ReferenceContext, TextDocumentIdentifier,
+        TextDocumentPositionParams, WorkDoneProgressParams,
+    };
+
+    use crate::{
+        common::{
+            testutils::{testdata, TestWorkspace},
+            utils::AsyncSignal,
+        },
+        server::indexing::index,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_variable_references() {
+        let uri = Url::from_file_path(testdata("workspaces/references/BUILD.gn")).unwrap();
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(15, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        };
+
+        let response = references(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+
+        // Assignments are not duplicated, and `a` in the template is a
+        // different variable.
+        let location = |line: u32, character: u32| Location {
+            uri: uri.clone(),
+            range: Range::new(
+                Position::new(line, character),
+                Position::new(line, character + 1),
+            ),
+        };
+        assert_eq!(
+            response,
+            Some(vec![
+                location(15, 0),
+                location(16, 4),
+                location(17, 2),
+                location(19, 4),
+                location(19, 8),
+            ])
+        );
+
+        // Without the declaration, only uses of the variable remain.
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(15, 0),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+        };
+        let response = references(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            Some(vec![location(16, 4), location(19, 4), location(19, 8)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_target_label_references() {
+        let context = RequestContext::new_for_testing();
+        let workspace_root = testdata("workspaces/target_references");
+        let mut signal = AsyncSignal::new();
+        context
+            .indexed
+            .lock()
+            .unwrap()
+            .insert(workspace_root.clone(), signal.clone());
+        index(&context, &workspace_root).await;
+        signal.set();
+
+        // The cursor is on the target function name, not the name string.
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(workspace_root.join("lib/BUILD.gn")).unwrap(),
+                },
+                position: Position::new(14, 2),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+        };
+        let response = references(&context, params).await.unwrap();
+
+        let location = |name: &str| Location {
+            uri: Url::from_file_path(workspace_root.join(name).join("BUILD.gn")).unwrap(),
+            range: Range::new(Position::new(15, 11), Position::new(15, 22)),
+        };
+        assert_eq!(response, Some(vec![location("app"), location("tools")]));
+    }
+
+    #[tokio::test]
+    async fn test_target_label_references_without_index() {
+        let workspace = TestWorkspace::new([
+            (
+                "other/BUILD.gn",
+                "group(\"other\") {\n  deps = [ \"//:foo\" ]\n}\n",
+            ),
+            (
+                "BUILD.gn",
+                "group(\"foo\") {\n}\n\ngroup(\"bar\") {\n  deps = [ \":foo\" ]\n}\n",
+            ),
+        ]);
+        let uri = Url::from_file_path(workspace.path("BUILD.gn")).unwrap();
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 8),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: false,
+            },
+        };
+        let response = references(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+
+        // Labels in other files are unknown until the workspace is indexed.
+        assert_eq!(
+            response,
+            Some(vec![Location {
+                uri,
+                range: Range::new(Position::new(4, 11), Position::new(4, 17)),
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_target_references_include_declaration() {
+        let workspace = TestWorkspace::new([(
+            "BUILD.gn",
+            "group(\"foo\") {\n}\n\ngroup(\"bar\") {\n  deps = [ \":foo\" ]\n}\n",
+        )]);
+        let uri = Url::from_file_path(workspace.path("BUILD.gn")).unwrap();
+        let params = ReferenceParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position: Position::new(0, 8),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        };
+        let response = references(&RequestContext::new_for_testing(), params)
+            .await
+            .unwrap();
+
+        // The name string of the target is its declaration.
+        assert_eq!(
+            response,
+            Some(vec![
+                Location {
+                    uri: uri.clone(),
+                    range: Range::new(Position::new(0, 6), Position::new(0, 11)),
+                },
+                Location {
+                    uri,
+                    range: Range::new(Position::new(4, 11), Position::new(4, 17)),
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,642 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use pest::Span;
+use tower_lsp::lsp_types::{
+    Position, PrepareRenameResponse, Range, RenameParams, TextDocumentPositionParams, TextEdit,
+    Url, WorkspaceEdit,
+};
+
+use crate::{
+    analyzer::{AnalyzedFile, AnalyzedLink, AnalyzedStatement, TopLevelStatementsExt},
+    common::{
+        builtins::BUILTINS,
+        error::{Error, Result},
+        storage::Document,
+    },
+    parser::{Block, Expr, Node},
+    server::{
+        providers::utils::{
+            get_text_document_path, is_same_variable, lookup_identifier_at,
+            lookup_target_name_string_at,
+        },
+        RequestContext,
+    },
+};
+
+/// A renameable symbol, resolved from the cursor position.
+enum Symbol {
+    /// A variable, resolved at an offset in the current file.
+    Variable { name: String, offset: usize },
+    /// A template, identified by the file and offset of its definition.
+    Template {
+        name: String,
+        path: PathBuf,
+        offset: usize,
+    },
+    /// A target defined in the current file.
+    Target { name: String },
+}
+
+impl Symbol {
+    fn name(&self) -> &str {
+        match self {
+            Symbol::Variable { name, .. }
+            | Symbol::Template { name, .. }
+            | Symbol::Target { name } => name,
+        }
+    }
+}
+
+fn is_builtin(name: &str) -> bool {
+    BUILTINS.all().any(|symbol| symbol.name == name)
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+/// Returns the range of a simple string literal excluding its quotes.
+fn string_content_range(document: &Document, expr: &Expr) -> Option<Range> {
+    expr.as_simple_string()?;
+    let span = expr.span();
+    Some(Range {
+        start: document.line_index.position(span.start() + 1),
+        end: document.line_index.position(span.end() - 1),
+    })
+}
+
+fn lookup_symbol_at(current_file: &AnalyzedFile, position: Position) -> Option<(Range, Symbol)> {
+    let document = &current_file.document;
+    let offset = document.line_index.offset(position)?;
+
+    if let Some(target) =
+        lookup_target_name_string_at(current_file, position).filter(|target| !target.implicit_name)
+    {
+        let range = string_content_range(document, &target.call.args[0])?;
+        let name = target.name.to_string();
+        return Some((range, Symbol::Target { name }));
+    }
+
+    // Template names in definitions are strings, not identifiers.
+    for statement in current_file.analyzed_root.top_level_statements() {
+        let AnalyzedStatement::Template(template) = statement else {
+            continue;
+        };
+        let span = template.name.span();
+        if span.start() <= offset && offset <= span.end() {
+            let name = template.name.as_simple_string()?;
+            let range = string_content_range(document, template.name)?;
+            return Some((
+                range,
+                Symbol::Template {
+                    name: name.to_string(),
+                    path: document.path.clone(),
+                    offset: template.call.span.start(),
+                },
+            ));
+        }
+    }
+
+    let ident = lookup_identifier_at(current_file, position)?;
+    let range = document.line_index.range(ident.span);
+    if let Some(template) = current_file
+        .templates_at(ident.span.start())
+        .get(ident.name)
+    {
+        return Some((
+            range,
+            Symbol::Template {
+                name: ident.name.to_string(),
+                path: template.document.path.clone(),
+                offset: template.call.span.start(),
+            },
+        ));
+    }
+    // Builtins are defined by GN, even if a scope assigns them.
+    if is_builtin(ident.name) {
+        return None;
+    }
+    if scope_member_starts(&current_file.ast).contains(&ident.span.start()) {
+        return None;
+    }
+    current_file
+        .variables_at(ident.span.start())
+        .get(ident.name)?;
+    Some((
+        range,
+        Symbol::Variable {
+            name: ident.name.to_string(),
+            offset: ident.span.start(),
+        },
+    ))
+}
+
+/// Returns the start offsets of scope members, e.g. `bar` in `foo.bar`,
+/// which are identifiers but not variables.
+fn scope_member_starts(ast: &Block) -> HashSet<usize> {
+    ast.walk()
+        .filter_map(|node| node.as_scope_access())
+        .map(|access| access.member.span.start())
+        .collect()
+}
+
+/// Returns files that may refer to a symbol: the current file, files
+/// defining it, and indexed files mentioning it if importers can see it.
+async fn files_to_scan(
+    context: &RequestContext,
+    current_file: &Pin<Arc<AnalyzedFile>>,
+    name: &str,
+    definition_paths: Vec<PathBuf>,
+    include_importers: bool,
+) -> Result<Vec<Pin<Arc<AnalyzedFile>>>> {
+    let mut paths: BTreeSet<PathBuf> = definition_paths.into_iter().collect();
+    paths.insert(current_file.document.path.clone());
+
+    if include_importers {
+        let workspace_root = &current_file.workspace_root;
+        let indexed = context.indexed.lock().unwrap().get(workspace_root).cloned();
+        if let Some(indexed) = indexed {
+            indexed.wait().await;
+        }
+        paths.extend(
+            context
+                .analyzer
+                .cached_files(workspace_root)
+                .into_iter()
+                .filter(|file| file.document.data.contains(name))
+                .map(|file| file.document.path.clone()),
+        );
+    }
+
+    paths
+        .into_iter()
+        .map(|path| {
+            if path == current_file.document.path {
+                Ok(current_file.clone())
+            } else {
+                context
+                    .analyzer
+                    .analyze(&path, &context.finder, context.request_time)
+            }
+        })
+        .collect()
+}
+
+/// Returns the edit renaming the target in a label, e.g. `//foo:bar`.
+fn label_edit(document: &Document, span: Span, old_name: &str, new_name: &str) -> TextEdit {
+    let content = &span.as_str()[1..span.as_str().len() - 1];
+    let content_end = span.end() - 1;
+    let end = document.line_index.position(content_end);
+    if content.ends_with(&format!(":{old_name}")) {
+        TextEdit {
+            range: Range {
+                start: document.line_index.position(content_end - old_name.len()),
+                end,
+            },
+            new_text: new_name.to_string(),
+        }
+    } else {
+        // Labels like `//foo` name the target after the directory, so spell
+        // out the new name.
+        TextEdit {
+            range: Range { start: end, end },
+            new_text: format!(":{new_name}"),
+        }
+    }
+}
+
+fn add_edit(changes: &mut HashMap<Url, Vec<TextEdit>>, path: &Path, edit: TextEdit) {
+    changes
+        .entry(Url::from_file_path(path).unwrap())
+        .or_default()
+        .push(edit);
+}
+
+fn rename_variable(
+    changes: &mut HashMap<Url, Vec<TextEdit>>,
+    current_file: &AnalyzedFile,
+    files: &[Pin<Arc<AnalyzedFile>>],
+    name: &str,
+    offset: usize,
+    new_name: &str,
+) {
+    let variables = current_file.variables_at(offset);
+    let Some(variable) = variables.get(name) else {
+        return;
+    };
+
+    for assignment in variable.assignments.values() {
+        let edit = TextEdit {
+            range: assignment
+                .document
+                .line_index
+                .range(assignment.primary_variable),
+            new_text: new_name.to_string(),
+        };
+        add_edit(changes, &assignment.document.path, edit);
+    }
+
+    for file in files {
+        let member_starts = scope_member_starts(&file.ast);
+        for ident in file
+            .ast
+            .identifiers()
+            .filter(|ident| ident.name == name)
+            .filter(|ident| !member_starts.contains(&ident.span.start()))
+        {
+            if file
+                .variables_at(ident.span.start())
+                .get(name)
+                .is_some_and(|other| is_same_variable(variable, other))
+            {
+                let edit = TextEdit {
+                    range: file.document.line_index.range(ident.span),
+                    new_text: new_name.to_string(),
+                };
+                add_edit(changes, &file.document.path, edit);
+            }
+        }
+    }
+}
+
+fn rename_template(
+    changes: &mut HashMap<Url, Vec<TextEdit>>,
+    files: &[Pin<Arc<AnalyzedFile>>],
+    name: &str,
+    path: &Path,
+    offset: usize,
+    new_name: &str,
+) {
+    for file in files {
+        if file.document.path == path {
+            for statement in file.analyzed_root.top_level_statements() {
+                let AnalyzedStatement::Template(template) = statement else {
+                    continue;
+                };
+                if template.call.span.start() != offset {
+                    continue;
+                }
+                if let Some(range) = string_content_range(&file.document, template.name) {
+                    let new_text = new_name.to_string();
+                    add_edit(changes, path, TextEdit { range, new_text });
+                }
+            }
+        }
+
+        for ident in file.ast.identifiers().filter(|ident| ident.name == name) {
+            if file
+                .templates_at(ident.span.start())
+                .get(name)
+                .is_some_and(|template| {
+                    template.document.path == path && template.call.span.start() == offset
+                })
+            {
+                let edit = TextEdit {
+                    range: file.document.line_index.range(ident.span),
+                    new_text: new_name.to_string(),
+                };
+                add_edit(changes, &file.document.path, edit);
+            }
+        }
+    }
+}
+
+fn rename_target(
+    changes: &mut HashMap<Url, Vec<TextEdit>>,
+    current_file: &AnalyzedFile,
+    files: &[Pin<Arc<AnalyzedFile>>],
+    name: &str,
+    new_name: &str,
+) {
+    let current_path = &current_file.document.path;
+    for target in current_file
+        .analyzed_root
+        .targets()
+        .filter(|target| !target.implicit_name && target.name == name)
+    {
+        if let Some(range) = string_content_range(&current_file.document, &target.call.args[0]) {
+            let new_text = new_name.to_string();
+            add_edit(changes, current_path, TextEdit { range, new_text });
+        }
+    }
+
+    for file in files {
+        for link in &file.links {
+            let AnalyzedLink::Target {
+                path,
+                name: link_name,
+                span,
+            } = link
+            else {
+                continue;
+            };
+            // Labels spelled with variables cannot be edited in place.
+            if path == current_path && *link_name == name && !span.as_str().contains('$') {
+                let edit = label_edit(&file.document, *span, name, new_name);
+                add_edit(changes, &file.document.path, edit);
+            }
+        }
+    }
+}
+
+pub async fn prepare_rename(
+    context: &RequestContext,
+    params: TextDocumentPositionParams,
+) -> Result<Option<PrepareRenameResponse>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+
+    Ok(lookup_symbol_at(&current_file, params.position)
+        .map(|(range, _)| PrepareRenameResponse::Range(range)))
+}
+
+pub async fn rename(
+    context: &RequestContext,
+    params: RenameParams,
+) -> Result<Option<WorkspaceEdit>> {
+    let path = get_text_document_path(&params.text_document_position.text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+    let position = params.text_document_position.position;
+    let new_name = params.new_name.as_str();
+
+    let Some((_, symbol)) = lookup_symbol_at(&current_file, position) else {
+        if let Some(ident) = lookup_identifier_at(&current_file, position) {
+            if is_builtin(ident.name) {
+                return Err(Error::InvalidParams(format!(
+                    "cannot rename builtin {}",
+                    ident.name
+                )));
+            }
+        }
+        return Ok(None);
+    };
+
+    let is_valid_name = match &symbol {
+        Symbol::Target { .. } => {
+            !new_name.is_empty() && !new_name.contains(['"', '$', ':', '/', '\\', '(', ')'])
+        }
+        _ => is_identifier(new_name) && !is_builtin(new_name),
+    };
+    if !is_valid_name {
+        return Err(Error::InvalidParams(format!("invalid name: {new_name}")));
+    }
+
+    let (definition_paths, include_importers) = match &symbol {
+        Symbol::Variable { name, offset } => {
+            let variables = current_file.variables_at(*offset);
+            let paths = variables
+                .get(name)
+                .map(|variable| {
+                    variable
+                        .assignments
+                        .values()
+                        .map(|assignment| assignment.document.path.clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            // Private variables are invisible to importers.
+            (paths, !name.starts_with('_'))
+        }
+        Symbol::Template { name, path, .. } => (vec![path.clone()], !name.starts_with('_')),
+        Symbol::Target { .. } => (Vec::new(), true),
+    };
+    let files = files_to_scan(
+        context,
+        &current_file,
+        symbol.name(),
+        definition_paths,
+        include_importers,
+    )
+    .await?;
+
+    let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+    match &symbol {
+        Symbol::Variable { name, offset } => {
+            rename_variable(&mut changes, &current_file, &files, name, *offset, new_name)
+        }
+        Symbol::Template { name, path, offset } => {
+            rename_template(&mut changes, &files, name, path, *offset, new_name)
+        }
+        Symbol::Target { name } => {
+            rename_target(&mut changes, &current_file, &files, name, new_name)
+        }
+    }
+    for edits in changes.values_mut() {
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        edits.dedup();
+    }
+
+    Ok(Some(WorkspaceEdit {
+        changes: Some(changes),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, WorkDoneProgressParams};
+
+    use crate::{
+        common::{
+            testutils::{testdata, TestWorkspace},
+            utils::AsyncSignal,
+        },
+        server::indexing::index,
+    };
+
+    use super::*;
+
+    fn position_params(path: &Path, line: u32, character: u32) -> TextDocumentPositionParams {
+        TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(path).unwrap(),
+            },
+            position: Position::new(line, character),
+        }
+    }
+
+    /// Renames the symbol at a position, and returns edited file names and
+    /// ranges as `(name, line, start, end)`.
+    async fn rename_at(
+        context: &RequestContext,
+        path: &Path,
+        line: u32,
+        character: u32,
+        new_name: &str,
+    ) -> Result<Vec<(String, u32, u32, u32)>> {
+        let params = RenameParams {
+            text_document_position: position_params(path, line, character),
+            new_name: new_name.to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let edit = rename(context, params).await?.unwrap();
+        let mut edits: Vec<_> = edit
+            .changes
+            .unwrap()
+            .into_iter()
+            .flat_map(|(uri, edits)| {
+                let path = uri.to_file_path().unwrap();
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                edits.into_iter().map(move |edit| {
+                    assert_eq!(edit.new_text, new_name);
+                    let Range { start, end } = edit.range;
+                    assert_eq!(start.line, end.line);
+                    (name.clone(), start.line, start.character, end.character)
+                })
+            })
+            .collect();
+        edits.sort();
+        Ok(edits)
+    }
+
+    #[tokio::test]
+    async fn test_rename_variable() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/rename/BUILD.gn");
+
+        // The assignment in the imported file is renamed too.
+        assert_eq!(
+            rename_at(&context, &path, 17, 9, "has_lib").await.unwrap(),
+            vec![
+                ("BUILD.gn".to_string(), 17, 9, 17),
+                ("BUILD.gn".to_string(), 24, 6, 14),
+                ("lib.gni".to_string(), 14, 0, 8),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_variable_not_scope_member() {
+        let workspace = TestWorkspace::new([(
+            "BUILD.gn",
+            "foo = 1\ns = {\n  foo = 2\n}\nprint(foo, s.foo)\n",
+        )]);
+        let context = RequestContext::new_for_testing();
+        let path = workspace.path("BUILD.gn");
+
+        // `s.foo` is a member of `s`, not the variable.
+        assert_eq!(
+            rename_at(&context, &path, 4, 6, "bar").await.unwrap(),
+            vec![
+                ("BUILD.gn".to_string(), 0, 0, 3),
+                ("BUILD.gn".to_string(), 4, 6, 9),
+            ]
+        );
+        let response = prepare_rename(&context, position_params(&path, 4, 13))
+            .await
+            .unwrap();
+        assert_eq!(response, None);
+    }
+
+    #[tokio::test]
+    async fn test_rename_private_variable() {
+        let context = RequestContext::new_for_testing();
+        let workspace_root = testdata("workspaces/rename");
+        let mut signal = AsyncSignal::new();
+        context
+            .indexed
+            .lock()
+            .unwrap()
+            .insert(workspace_root.clone(), signal.clone());
+        index(&context, &workspace_root).await;
+        signal.set();
+
+        // `_helper` in the importer is a different variable.
+        assert_eq!(
+            rename_at(&context, &workspace_root.join("lib.gni"), 16, 12, "_util")
+                .await
+                .unwrap(),
+            vec![
+                ("lib.gni".to_string(), 15, 0, 7),
+                ("lib.gni".to_string(), 16, 12, 19),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_template() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/rename/BUILD.gn");
+
+        assert_eq!(
+            rename_at(&context, &path, 19, 2, "wrapper").await.unwrap(),
+            vec![
+                ("BUILD.gn".to_string(), 19, 0, 11),
+                ("lib.gni".to_string(), 18, 10, 21),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_target() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/rename/BUILD.gn");
+
+        assert_eq!(
+            rename_at(&context, &path, 19, 14, "baz").await.unwrap(),
+            vec![
+                ("BUILD.gn".to_string(), 19, 13, 16),
+                ("BUILD.gn".to_string(), 23, 13, 16),
+                ("BUILD.gn".to_string(), 25, 24, 27),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_builtin() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/rename/BUILD.gn");
+
+        let response = prepare_rename(&context, position_params(&path, 23, 2))
+            .await
+            .unwrap();
+        assert_eq!(response, None);
+        assert!(matches!(
+            rename_at(&context, &path, 22, 0, "my_group").await,
+            Err(Error::InvalidParams(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_prepare_rename() {
+        let context = RequestContext::new_for_testing();
+        let path = testdata("workspaces/rename/BUILD.gn");
+
+        let response = prepare_rename(&context, position_params(&path, 19, 14))
+            .await
+            .unwrap();
+        assert_eq!(
+            response,
+            Some(PrepareRenameResponse::Range(Range::new(
+                Position::new(19, 13),
+                Position::new(19, 16)
+            )))
+        );
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    analyzer::Analyzer,
+    common::{
+        error::{Error, Result},
+        storage::DocumentStorage,
+        workspace::WorkspaceFinder,
+    },
+};
+
+const SELF_TEST_FILES: &[(&str, &str)] = &[
+    (".gn", "buildconfig = \"//BUILDCONFIG.gn\"\n"),
+    (
+        "BUILDCONFIG.gn",
+        "declare_args() {\n  is_self_test = true\n}\n",
+    ),
+    (
+        "self_test.gni",
+        "template(\"self_test_template\") {\n  group(target_name) {\n  }\n}\n",
+    ),
+    (
+        "BUILD.gn",
+        "import(\"//self_test.gni\")\n\nself_test_value = 1\n\nself_test_template(\"self_test\") {\n}\n",
+    ),
+];
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestResult {
+    pub passed: bool,
+    pub elapsed_ms: f64,
+    pub message: String,
+}
+
+/// Analyzes a synthetic workspace and checks the resulting scope.
+fn check_workspace(root: &Path) -> Result<()> {
+    for (name, contents) in SELF_TEST_FILES {
+        std::fs::write(root.join(name), contents)?;
+    }
+
+    // Use a dedicated analyzer so that the temporary workspace does not
+    // linger in the server's caches.
+    let storage = Arc::new(Mutex::new(DocumentStorage::new()));
+    let analyzer = Analyzer::new(&storage);
+    let finder = WorkspaceFinder::new(Some(root));
+    let file = analyzer.analyze(&root.join("BUILD.gn"), &finder, Instant::now())?;
+
+    let offset = file.document.data.len();
+    let variables = file.variables_at(offset);
+    if !variables
+        .get("is_self_test")
+        .is_some_and(|variable| variable.is_args)
+    {
+        return Err(Error::General(
+            "build argument from BUILDCONFIG.gn not found".to_string(),
+        ));
+    }
+    if !variables.contains("self_test_value") {
+        return Err(Error::General("local variable not found".to_string()));
+    }
+    if !file.templates_at(offset).contains("self_test_template") {
+        return Err(Error::General("imported template not found".to_string()));
+    }
+    if file
+        .analyzed_root
+        .targets()
+        .all(|target| target.name != "self_test")
+    {
+        return Err(Error::General("target not found".to_string()));
+    }
+    Ok(())
+}
+
+pub fn self_test() -> SelfTestResult {
+    let start_time = Instant::now();
+    let nonce = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let root = std::env::temp_dir().join(format!(
+        "gn-language-server-self-test-{}-{}",
+        std::process::id(),
+        nonce
+    ));
+
+    let result = std::fs::create_dir_all(&root)
+        .map_err(Error::from)
+        .and_then(|_| check_workspace(&root));
+    std::fs::remove_dir_all(&root).ok();
+
+    let elapsed_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+    match result {
+        Ok(()) => SelfTestResult {
+            passed: true,
+            elapsed_ms,
+            message: "OK".to_string(),
+        },
+        Err(err) => SelfTestResult {
+            passed: false,
+            elapsed_ms,
+            message: err.to_string(),
+        },
+    }
+}
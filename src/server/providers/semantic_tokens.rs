@@ -0,0 +1,306 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashSet;
+
+use tower_lsp::lsp_types::{
+    Position, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensLegend, SemanticTokensParams, SemanticTokensResult,
+};
+
+use crate::{
+    analyzer::{AnalyzedFile, AnalyzedStatement, TopLevelStatementsExt},
+    common::{builtins::BUILTINS, error::Result},
+    parser::{Node, StringLiteral},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+// Indices into the legend returned by `legend`.
+const FUNCTION: u32 = 0;
+const MACRO: u32 = 1;
+const VARIABLE: u32 = 2;
+const STRING: u32 = 3;
+const OPERATOR: u32 = 4;
+
+// Bits of the modifiers in the legend returned by `legend`.
+const DEFAULT_LIBRARY: u32 = 1 << 0;
+const DECLARATION: u32 = 1 << 1;
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::FUNCTION,
+            SemanticTokenType::MACRO,
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::STRING,
+            SemanticTokenType::OPERATOR,
+        ],
+        token_modifiers: vec![
+            SemanticTokenModifier::DEFAULT_LIBRARY,
+            SemanticTokenModifier::DECLARATION,
+        ],
+    }
+}
+
+/// A token before relative encoding, in byte offsets.
+struct RawToken {
+    start: usize,
+    end: usize,
+    token_type: u32,
+    modifiers: u32,
+}
+
+/// Returns the ranges of embedded expressions in a string along with their
+/// `$` or `${...}` delimiters.
+fn embedded_ranges(data: &str, string: &StringLiteral) -> Vec<(usize, usize)> {
+    string
+        .embedded_exprs
+        .iter()
+        .filter_map(|expr| {
+            let (start, end) = (expr.span().start(), expr.span().end());
+            let prefix = data[string.span.start()..start].trim_end();
+            if prefix.ends_with("${") {
+                let close = end + data[end..].find('}')?;
+                Some((string.span.start() + prefix.len() - 2, close + 1))
+            } else {
+                prefix.ends_with('$').then_some((start - 1, end))
+            }
+        })
+        .collect()
+}
+
+fn collect_tokens(current_file: &AnalyzedFile) -> Vec<RawToken> {
+    let data: &str = &current_file.document.data;
+    let mut tokens = Vec::new();
+
+    // Functions and templates being called.
+    let mut functions = HashSet::new();
+    for call in current_file.ast.walk().filter_map(|node| node.as_call()) {
+        let name = call.function.name;
+        let (token_type, modifiers) = if BUILTINS
+            .functions
+            .iter()
+            .chain(BUILTINS.targets.iter())
+            .any(|symbol| symbol.name == name)
+        {
+            (FUNCTION, DEFAULT_LIBRARY)
+        } else if current_file
+            .templates_at(call.function.span.start())
+            .get(name)
+            .is_some()
+        {
+            (MACRO, 0)
+        } else {
+            (FUNCTION, 0)
+        };
+        functions.insert(call.function.span.start());
+        tokens.push(RawToken {
+            start: call.function.span.start(),
+            end: call.function.span.end(),
+            token_type,
+            modifiers,
+        });
+    }
+
+    // Variables, including those embedded in strings.
+    for identifier in current_file.ast.identifiers() {
+        if functions.contains(&identifier.span.start())
+            || identifier.name == "true"
+            || identifier.name == "false"
+        {
+            continue;
+        }
+        let builtin = BUILTINS
+            .predefined_variables
+            .iter()
+            .chain(BUILTINS.target_variables.iter())
+            .any(|symbol| symbol.name == identifier.name);
+        tokens.push(RawToken {
+            start: identifier.span.start(),
+            end: identifier.span.end(),
+            token_type: VARIABLE,
+            modifiers: if builtin { DEFAULT_LIBRARY } else { 0 },
+        });
+    }
+
+    // Delimiters of embedded expressions.
+    for string in current_file.ast.strings() {
+        for (expr, (start, end)) in string
+            .embedded_exprs
+            .iter()
+            .zip(embedded_ranges(data, string))
+        {
+            let open = data[start..expr.span().start()].trim_end();
+            tokens.push(RawToken {
+                start,
+                end: start + open.len(),
+                token_type: OPERATOR,
+                modifiers: 0,
+            });
+            let close = data[expr.span().end()..end].trim_start();
+            if !close.is_empty() {
+                tokens.push(RawToken {
+                    start: end - close.len(),
+                    end,
+                    token_type: OPERATOR,
+                    modifiers: 0,
+                });
+            }
+        }
+    }
+
+    // Target names, excluding embedded expressions.
+    for statement in current_file.analyzed_root.top_level_statements() {
+        let AnalyzedStatement::Target(target) = statement else {
+            continue;
+        };
+        let Some(string) = target.name.as_primary_string() else {
+            continue;
+        };
+        let mut start = string.span.start();
+        let embedded = embedded_ranges(data, string);
+        for (piece_end, next_start) in embedded
+            .iter()
+            .copied()
+            .chain(std::iter::once((string.span.end(), string.span.end())))
+        {
+            if start < piece_end {
+                tokens.push(RawToken {
+                    start,
+                    end: piece_end,
+                    token_type: STRING,
+                    modifiers: DECLARATION,
+                });
+            }
+            start = next_start;
+        }
+    }
+
+    tokens.sort_by_key(|token| token.start);
+    tokens
+}
+
+fn encode_tokens(current_file: &AnalyzedFile, tokens: Vec<RawToken>) -> Vec<SemanticToken> {
+    let line_index = &current_file.document.line_index;
+    let mut encoded = Vec::new();
+    let mut last = Position::default();
+    let mut last_end = 0;
+    for token in tokens {
+        // Tokens must not overlap or span multiple lines.
+        if token.start < last_end {
+            continue;
+        }
+        let start = line_index.position(token.start);
+        let end = line_index.position(token.end);
+        if start.line != end.line || start.character == end.character {
+            continue;
+        }
+        encoded.push(SemanticToken {
+            delta_line: start.line - last.line,
+            delta_start: if start.line == last.line {
+                start.character - last.character
+            } else {
+                start.character
+            },
+            length: end.character - start.character,
+            token_type: token.token_type,
+            token_modifiers_bitset: token.modifiers,
+        });
+        last = start;
+        last_end = token.end;
+    }
+    encoded
+}
+
+pub async fn semantic_tokens_full(
+    context: &RequestContext,
+    params: SemanticTokensParams,
+) -> Result<Option<SemanticTokensResult>> {
+    let path = get_text_document_path(&params.text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+    let tokens = collect_tokens(&current_file);
+    Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+        result_id: None,
+        data: encode_tokens(&current_file, tokens),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{TextDocumentIdentifier, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_semantic_tokens() {
+        let params = SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: TextDocumentIdentifier {
+                uri: Url::from_file_path(testdata("workspaces/semantic_tokens/BUILD.gn")).unwrap(),
+            },
+        };
+        let Some(SemanticTokensResult::Tokens(tokens)) =
+            semantic_tokens_full(&RequestContext::new_for_testing(), params)
+                .await
+                .unwrap()
+        else {
+            panic!("expected full tokens");
+        };
+
+        // Decode relative positions back to (line, character, length, type,
+        // modifiers).
+        let mut line = 0;
+        let mut character = 0;
+        let decoded: Vec<_> = tokens
+            .data
+            .iter()
+            .map(|token| {
+                if token.delta_line > 0 {
+                    character = 0;
+                }
+                line += token.delta_line;
+                character += token.delta_start;
+                (
+                    line,
+                    character,
+                    token.length,
+                    token.token_type,
+                    token.token_modifiers_bitset,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            decoded,
+            vec![
+                (14, 0, 8, FUNCTION, DEFAULT_LIBRARY),
+                (15, 2, 10, FUNCTION, DEFAULT_LIBRARY),
+                (15, 13, 11, VARIABLE, DEFAULT_LIBRARY),
+                (19, 0, 9, MACRO, 0),
+                (19, 10, 5, STRING, DECLARATION),
+                (19, 15, 2, OPERATOR, 0),
+                (19, 17, 10, VARIABLE, DEFAULT_LIBRARY),
+                (19, 27, 1, OPERATOR, 0),
+                (19, 28, 1, STRING, DECLARATION),
+                (20, 2, 7, VARIABLE, DEFAULT_LIBRARY),
+                (20, 12, 10, VARIABLE, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,287 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use tower_lsp::lsp_types::{
+    Documentation, MarkupContent, MarkupKind, ParameterInformation, ParameterLabel, Position,
+    SignatureHelp, SignatureHelpParams, SignatureInformation,
+};
+
+use crate::{
+    analyzer::AnalyzedFile,
+    common::{
+        builtins::{BuiltinSymbol, BUILTINS},
+        error::Result,
+    },
+    parser::{Block, Node},
+    server::{
+        providers::utils::{format_template_help, get_text_document_path},
+        RequestContext,
+    },
+};
+
+/// The call whose argument list contains the cursor.
+struct CallSite<'a> {
+    name: &'a str,
+    active_parameter: u32,
+}
+
+/// Finds the innermost complete call whose parentheses enclose `offset`.
+fn find_call_in_ast<'i>(ast: &Block<'i>, data: &str, offset: usize) -> Option<CallSite<'i>> {
+    ast.walk()
+        .filter_map(|node| node.as_call())
+        .filter_map(|call| {
+            let function_end = call.function.span.end();
+            let open = function_end + data[function_end..].find('(')?;
+            let args_end = call.args.last().map_or(open + 1, |arg| arg.span().end());
+            let close = args_end + data[args_end..].find(')')?;
+            if offset <= open || close < offset {
+                return None;
+            }
+            let active_parameter = call
+                .args
+                .iter()
+                .filter(|arg| {
+                    arg.span().end() <= offset
+                        && data[arg.span().end()..offset].trim_start().starts_with(',')
+                })
+                .count();
+            Some(CallSite {
+                name: call.function.name,
+                active_parameter: active_parameter as u32,
+            })
+        })
+        .last()
+}
+
+/// Finds an unclosed call on the current line. Calls being typed do not
+/// parse yet, so they are recovered from the text instead.
+fn find_call_in_line(line: &str) -> Option<CallSite<'_>> {
+    let mut depth = 0;
+    let mut commas = 0;
+    let mut in_string = false;
+    for (i, c) in line.char_indices().rev() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            ')' | ']' => depth += 1,
+            '(' | '[' if depth > 0 => depth -= 1,
+            ',' if depth == 0 => commas += 1,
+            // The cursor is in a list, so the commas seen so far separate
+            // list items rather than arguments.
+            '[' => commas = 0,
+            '(' => {
+                let prefix = line[..i].trim_end();
+                let start = prefix
+                    .rfind(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .map_or(0, |pos| pos + 1);
+                let name = &prefix[start..];
+                return (!name.is_empty()).then_some(CallSite {
+                    name,
+                    active_parameter: commas,
+                });
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
+
+/// Finds the usage line of a builtin function in its docs, e.g.
+/// `get_path_info(input, what)`, and the offsets of its parameters.
+fn parse_usage<'a>(name: &str, doc: &'a str) -> Option<(&'a str, Vec<[u32; 2]>)> {
+    let prefix = format!("{name}(");
+    doc.lines().find_map(|line| {
+        let start = line.find(&prefix)?;
+        if !line[..start].trim().is_empty() && !line[..start].trim_end().ends_with('=') {
+            return None;
+        }
+        let close = start + line[start..].find(')')?;
+        let usage = &line[start..=close];
+
+        let mut parameters = Vec::new();
+        let mut pos = prefix.len();
+        for part in usage[prefix.len()..usage.len() - 1].split(',') {
+            let param = part.trim_matches(|c: char| c.is_whitespace() || c == '[' || c == ']');
+            if let Some(param_start) = part.find(param).filter(|_| !param.is_empty()) {
+                let begin = utf16_len(&usage[..pos + param_start]);
+                parameters.push([begin, begin + utf16_len(param)]);
+            }
+            pos += part.len() + 1;
+        }
+        Some((usage, parameters))
+    })
+}
+
+fn target_signature(name: &str, documentation: String) -> SignatureInformation {
+    SignatureInformation {
+        label: format!("{name}(target_name)"),
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: documentation,
+        })),
+        parameters: Some(vec![ParameterInformation {
+            label: ParameterLabel::Simple("target_name".to_string()),
+            documentation: None,
+        }]),
+        active_parameter: None,
+    }
+}
+
+fn function_signature(symbol: &BuiltinSymbol) -> SignatureInformation {
+    let (label, parameters) = match parse_usage(symbol.name, symbol.doc) {
+        Some((usage, parameters)) => (
+            usage.to_string(),
+            parameters
+                .into_iter()
+                .map(|offsets| ParameterInformation {
+                    label: ParameterLabel::LabelOffsets(offsets),
+                    documentation: None,
+                })
+                .collect(),
+        ),
+        None => (format!("{}(...)", symbol.name), Vec::new()),
+    };
+    SignatureInformation {
+        label,
+        documentation: Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: symbol.doc.to_string(),
+        })),
+        parameters: Some(parameters),
+        active_parameter: None,
+    }
+}
+
+fn signature_for(
+    current_file: &AnalyzedFile,
+    name: &str,
+    offset: usize,
+) -> Option<SignatureInformation> {
+    if let Some(template) = current_file.templates_at(offset).get(name) {
+        let documentation =
+            format_template_help(template, &current_file.workspace_root).join("\n\n");
+        return Some(target_signature(name, documentation));
+    }
+    if let Some(symbol) = BUILTINS.targets.iter().find(|symbol| symbol.name == name) {
+        return Some(target_signature(name, symbol.doc.to_string()));
+    }
+    BUILTINS
+        .functions
+        .iter()
+        .find(|symbol| symbol.name == name)
+        .map(function_signature)
+}
+
+pub async fn signature_help(
+    context: &RequestContext,
+    params: SignatureHelpParams,
+) -> Result<Option<SignatureHelp>> {
+    let path = get_text_document_path(&params.text_document_position_params.text_document)?;
+    let position = params.text_document_position_params.position;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+    let data = &current_file.document.data;
+    let line_index = &current_file.document.line_index;
+    let (Some(offset), Some(line_start)) = (
+        line_index.offset(position),
+        line_index.offset(Position::new(position.line, 0)),
+    ) else {
+        return Ok(None);
+    };
+
+    let Some(call_site) = find_call_in_ast(&current_file.ast, data, offset)
+        .or_else(|| find_call_in_line(&data[line_start..offset]))
+    else {
+        return Ok(None);
+    };
+    let Some(signature) = signature_for(&current_file, call_site.name, offset) else {
+        return Ok(None);
+    };
+
+    Ok(Some(SignatureHelp {
+        signatures: vec![signature],
+        active_signature: Some(0),
+        active_parameter: Some(call_site.active_parameter),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{
+        TextDocumentIdentifier, TextDocumentPositionParams, Url, WorkDoneProgressParams,
+    };
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    async fn signature_help_at(line: u32, character: u32) -> Option<SignatureHelp> {
+        let context = RequestContext::new_for_testing();
+        let params = SignatureHelpParams {
+            context: None,
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::from_file_path(testdata("workspaces/signature_help/BUILD.gn"))
+                        .unwrap(),
+                },
+                position: Position::new(line, character),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        signature_help(&context, params).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_template_signature() {
+        let help = signature_help_at(19, 12).await.unwrap();
+        assert_eq!(help.signatures[0].label, "my_binary(target_name)");
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_builtin_target_signature() {
+        let help = signature_help_at(15, 15).await.unwrap();
+        assert_eq!(help.signatures[0].label, "executable(target_name)");
+        assert_eq!(help.active_parameter, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_builtin_function_signature() {
+        let help = signature_help_at(20, 39).await.unwrap();
+        let signature = &help.signatures[0];
+        assert_eq!(signature.label, "get_path_info(input, what)");
+        assert_eq!(
+            signature.parameters.as_ref().unwrap()[1].label,
+            ParameterLabel::LabelOffsets([21, 25])
+        );
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_incomplete_call_signature() {
+        let help = signature_help_at(23, 13).await.unwrap();
+        assert!(help.signatures[0].label.starts_with("assert("));
+        assert_eq!(help.active_parameter, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_no_signature_outside_arguments() {
+        assert!(signature_help_at(15, 2).await.is_none());
+    }
+}
@@ -0,0 +1,75 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use tower_lsp::lsp_types::{Range, TextDocumentIdentifier};
+
+use crate::{
+    common::error::Result,
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct UnresolvedImport {
+    pub range: Range,
+    pub path: PathBuf,
+}
+
+/// Returns imports in a file whose resolved paths do not exist on disk.
+pub async fn unresolved_imports(
+    context: &RequestContext,
+    text_document: &TextDocumentIdentifier,
+) -> Result<Vec<UnresolvedImport>> {
+    let path = get_text_document_path(text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+
+    Ok(current_file
+        .analyzed_root
+        .imports()
+        .filter(|(_, path)| !matches!(path.try_exists(), Ok(true)))
+        .map(|(span, path)| UnresolvedImport {
+            range: current_file.document.line_index.range(span),
+            path: path.to_path_buf(),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{Position, Url};
+
+    use crate::common::testutils::testdata;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unresolved_imports() {
+        let text_document = TextDocumentIdentifier {
+            uri: Url::from_file_path(testdata("workspaces/unresolved_imports/BUILD.gn")).unwrap(),
+        };
+        let imports = unresolved_imports(&RequestContext::new_for_testing(), &text_document)
+            .await
+            .unwrap();
+        assert_eq!(
+            imports,
+            vec![UnresolvedImport {
+                range: Range::new(Position::new(15, 0), Position::new(15, 23)),
+                path: testdata("workspaces/unresolved_imports/missing.gni"),
+            }]
+        );
+    }
+}
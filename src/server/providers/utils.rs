@@ -19,16 +19,54 @@ use itertools::Itertools;
 use tower_lsp::lsp_types::{Position, TextDocumentIdentifier, Url};
 
 use crate::{
-    analyzer::{AnalyzedFile, ShallowAnalyzedFile, Target, Template, Variable},
-    common::error::{Error, Result},
+    analyzer::{
+        common_scope_members, infer_element_type, AnalyzedFile, AnalyzedStatement,
+        ShallowAnalyzedFile, Target, Template, TopLevelStatementsExt, Variable,
+    },
+    common::{
+        binary::find_gn_binary,
+        builtins::{FOREACH, FORWARD_VARIABLES_FROM},
+        config::LabelStyle,
+        error::{Error, Result},
+        storage::Document,
+    },
     parser::{Identifier, Node},
+    server::RequestContext,
 };
 
 pub fn get_text_document_path(text_document: &TextDocumentIdentifier) -> Result<PathBuf> {
     text_document
         .uri
         .to_file_path()
-        .map_err(|_| Error::General(format!("invalid file URI: {}", text_document.uri)))
+        .map_err(|_| Error::InvalidParams(format!("invalid file URI: {}", text_document.uri)))
+}
+
+/// Returns the gn binary to run for a file, as configured by gn.binaryPath or
+/// auto-detected.
+pub async fn resolve_gn_binary(context: &RequestContext, file_path: &Path) -> Result<PathBuf> {
+    let configs = context.client.configurations().await;
+    if let Some(gn_path) = &configs.binary_path {
+        if gn_path.exists() {
+            Ok(gn_path.to_path_buf())
+        } else {
+            Err(Error::General(format!(
+                "gn binary not found at {}; check configuration value gn.binaryPath",
+                gn_path.display()
+            )))
+        }
+    } else if let Some(gn_path) = find_gn_binary(
+        context
+            .analyzer
+            .workspace_root(file_path, &context.finder)
+            .ok()
+            .as_deref(),
+    ) {
+        Ok(gn_path)
+    } else {
+        Err(Error::General(
+            "gn binary not found; specify configuration value gn.binaryPath".to_string(),
+        ))
+    }
 }
 
 pub fn lookup_identifier_at(file: &AnalyzedFile, position: Position) -> Option<&Identifier> {
@@ -38,13 +76,50 @@ pub fn lookup_identifier_at(file: &AnalyzedFile, position: Position) -> Option<&
         .find(|ident| ident.span.start() <= offset && offset <= ident.span.end())
 }
 
+/// Returns whether two resolved variables share any assignment, i.e. they
+/// refer to the same variable in the same scope.
+pub fn is_same_variable(a: &Variable, b: &Variable) -> bool {
+    a.assignments
+        .keys()
+        .any(|key| b.assignments.contains_key(key))
+}
+
+/// Returns targets at the top level of a file and of its template bodies.
+fn targets_with_templates<'a>(
+    file: &'a AnalyzedFile,
+) -> impl Iterator<Item = Target<'static, 'static>> + 'a {
+    let template_targets = file
+        .analyzed_root
+        .top_level_statements()
+        .filter_map(|statement| match statement {
+            AnalyzedStatement::Template(template) => Some(template.targets()),
+            _ => None,
+        })
+        .flatten();
+    file.analyzed_root.targets().chain(template_targets)
+}
+
 pub fn lookup_target_name_string_at(file: &AnalyzedFile, position: Position) -> Option<Target> {
     let offset = file.document.line_index.offset(position)?;
-    file.analyzed_root.targets().find(|target| {
+    targets_with_templates(file).find(|target| {
         target.call.args[0].span().start() <= offset && offset <= target.call.args[0].span().end()
     })
 }
 
+/// Finds a target whose header, e.g. `executable("foo")`, contains the
+/// position.
+pub fn lookup_target_header_at(file: &AnalyzedFile, position: Position) -> Option<Target<'_, '_>> {
+    let offset = file.document.line_index.offset(position)?;
+    targets_with_templates(file).find(|target| {
+        let header_end = target
+            .call
+            .block
+            .as_ref()
+            .map_or(target.call.span.end(), |block| block.span.start());
+        target.call.span.start() <= offset && offset < header_end
+    })
+}
+
 pub fn find_target<'a>(
     file: &'a ShallowAnalyzedFile,
     name: &str,
@@ -59,7 +134,9 @@ pub fn find_target<'a>(
 
     // Try target name prefixes.
     for name in (1..=name.len()).rev().map(|len| &name[..len]) {
-        if let Some(target) = targets.iter().find(|t| t.name == name) {
+        if let Some(target) = targets.iter().find(|t| {
+            t.name == name || (file.case_insensitive && t.name.eq_ignore_ascii_case(name))
+        }) {
             return Some(target);
         }
     }
@@ -75,19 +152,27 @@ pub fn format_path(path: &Path, workspace_root: &Path) -> String {
     }
 }
 
+/// Maximum number of assignment locations listed for a variable assigned in
+/// many places.
+const MAX_LISTED_ASSIGNMENTS: usize = 5;
+
+/// Formats a link to a location, e.g. `[//foo/BUILD.gn:3:1](file:///...)`.
+fn format_location_link(document: &Document, offset: usize, workspace_root: &Path) -> String {
+    let position = document.line_index.position(offset);
+    format!(
+        "[{}:{}:{}]({}#L{},{})",
+        format_path(&document.path, workspace_root),
+        position.line + 1,
+        position.character + 1,
+        Url::from_file_path(&document.path).unwrap(),
+        position.line + 1,
+        position.character + 1,
+    )
+}
+
 pub fn format_variable_help(variable: &Variable, workspace_root: &Path) -> Vec<String> {
-    let first_assignment = variable
-        .assignments
-        .values()
-        .sorted_by_key(|a| {
-            let span = match &a.assignment_or_call {
-                either::Either::Left(assignment) => assignment.span,
-                either::Either::Right(call) => call.span,
-            };
-            (&a.document.path, span.start())
-        })
-        .next()
-        .unwrap();
+    let listed_assignments = variable.first_assignments(MAX_LISTED_ASSIGNMENTS);
+    let first_assignment = listed_assignments[0];
     let single_assignment = variable.assignments.len() == 1;
 
     let snippet = if single_assignment {
@@ -106,8 +191,21 @@ pub fn format_variable_help(variable: &Variable, workspace_root: &Path) -> Vec<S
                     display_value
                 )
             }
+            Either::Right(call) if call.function.name == FOREACH => {
+                let raw_items = call.args[1].span().as_str();
+                let display_items = if raw_items.lines().count() <= 5 {
+                    raw_items
+                } else {
+                    "..."
+                };
+                format!(
+                    "foreach({}, {})",
+                    call.args[0].span().as_str(),
+                    display_items
+                )
+            }
             Either::Right(call) => {
-                assert_eq!(call.function.name, "forward_variables_from");
+                assert_eq!(call.function.name, FORWARD_VARIABLES_FROM);
                 call.span.as_str().to_string()
             }
         }
@@ -117,36 +215,104 @@ pub fn format_variable_help(variable: &Variable, workspace_root: &Path) -> Vec<S
 
     let mut paragraphs = vec![format!("```gn\n{snippet}\n```")];
 
+    // Describe items iterated by a loop variable.
+    if let Either::Right(call) = first_assignment.assignment_or_call {
+        if single_assignment && call.function.name == FOREACH {
+            if let Some(element_type) = infer_element_type(&call.args[1]) {
+                let mut paragraph = format!("Iterates over `{}` items", element_type.name());
+                let members = common_scope_members(&call.args[1]);
+                if !members.is_empty() {
+                    paragraph.push_str(&format!(
+                        " with members {}",
+                        members.iter().map(|name| format!("`{name}`")).join(", ")
+                    ));
+                }
+                paragraphs.push(paragraph);
+            }
+        }
+    }
+
     if single_assignment {
         paragraphs.push(format!(
             "```text\n{}\n```",
             first_assignment.comments.to_string().trim()
         ));
+        paragraphs.push(format!(
+            "Defined at {}",
+            format_location_link(
+                first_assignment.document,
+                first_assignment.span().start(),
+                workspace_root
+            )
+        ));
+        return paragraphs;
     }
 
-    let span = match &first_assignment.assignment_or_call {
-        Either::Left(assignment) => assignment.span,
-        Either::Right(call) => call.span,
-    };
-    let position = first_assignment.document.line_index.position(span.start());
-    paragraphs.push(if single_assignment {
+    let mut paragraph = format!(
+        "Defined and modified in {} locations:\n",
+        variable.assignments.len()
+    );
+    for assignment in &listed_assignments {
+        paragraph.push_str(&format!(
+            "\n- {}",
+            format_location_link(
+                assignment.document,
+                assignment.span().start(),
+                workspace_root
+            )
+        ));
+    }
+    let unlisted = variable.assignments.len() - listed_assignments.len();
+    if unlisted > 0 {
+        paragraph.push_str(&format!("\n- and {unlisted} more"));
+    }
+    paragraphs.push(paragraph);
+
+    paragraphs
+}
+
+/// Formats the label of a target defined in `build_path`. Relative labels are
+/// used only for targets in the current directory or its subdirectories.
+pub fn format_label(
+    build_path: &Path,
+    name: &str,
+    workspace_root: &Path,
+    current_dir: &Path,
+    style: LabelStyle,
+) -> String {
+    let dir = build_path.parent().unwrap_or(workspace_root);
+    if style == LabelStyle::Relative {
+        if let Ok(relative_dir) = dir.strip_prefix(current_dir) {
+            return format!("{}:{name}", relative_dir.to_string_lossy());
+        }
+    }
+    match dir.strip_prefix(workspace_root) {
+        Ok(relative_dir) => format!("//{}:{name}", relative_dir.to_string_lossy()),
+        Err(_) => format!("{}:{name}", dir.to_string_lossy()),
+    }
+}
+
+pub fn format_target_help(target: &Target, label: &str, workspace_root: &Path) -> Vec<String> {
+    let position = target
+        .document
+        .line_index
+        .position(target.call.span.start());
+    vec![
+        format!(
+            "```gn\n{}(\"{}\") {{ ... }}\n```",
+            target.call.function.name, target.name
+        ),
+        format!("Label: `{label}`"),
         format!(
             "Defined at [{}:{}:{}]({}#L{},{})",
-            format_path(&first_assignment.document.path, workspace_root),
+            format_path(&target.document.path, workspace_root),
             position.line + 1,
             position.character + 1,
-            Url::from_file_path(&first_assignment.document.path).unwrap(),
+            Url::from_file_path(&target.document.path).unwrap(),
             position.line + 1,
             position.character + 1,
-        )
-    } else {
-        format!(
-            "Defined and modified in {} locations",
-            variable.assignments.len()
-        )
-    });
-
-    paragraphs
+        ),
+    ]
 }
 
 pub fn format_template_help(template: &Template, workspace_root: &Path) -> Vec<String> {
@@ -154,6 +320,9 @@ pub fn format_template_help(template: &Template, workspace_root: &Path) -> Vec<S
         "```gn\ntemplate(\"{}\") {{ ... }}\n```",
         template.name
     )];
+    if template.comments.is_deprecated() {
+        paragraphs.push(format!("~~{}~~ is deprecated.", template.name));
+    }
     if !template.comments.is_empty() {
         paragraphs.push(format!(
             "```text\n{}\n```",
@@ -0,0 +1,152 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{path::PathBuf, sync::Arc};
+
+use futures::{stream, StreamExt};
+use tower_lsp::lsp_types::{Diagnostic, TextDocumentIdentifier, Url};
+
+use crate::{
+    common::{
+        config::Configurations,
+        error::{Error, Result},
+    },
+    diagnostics::{compute_diagnostics, DiagnosticsCache},
+    server::{providers::utils::get_text_document_path, RequestContext},
+};
+
+/// Maximum number of files analyzed at the same time.
+const MAX_CONCURRENT_FILES: usize = 8;
+
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct FileDiagnostics {
+    pub uri: Url,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+fn diagnose_file(
+    context: &RequestContext,
+    config: &Configurations,
+    path: PathBuf,
+) -> Option<FileDiagnostics> {
+    let file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)
+        .ok()?;
+    // Use a private cache so that exporting does not retain results for every
+    // file in the workspace.
+    let diagnostics = compute_diagnostics(&file, config, &mut DiagnosticsCache::default());
+    if diagnostics.is_empty() {
+        return None;
+    }
+    Some(FileDiagnostics {
+        uri: Url::from_file_path(&path).ok()?,
+        diagnostics,
+    })
+}
+
+/// Returns diagnostics of all indexed files in the workspace containing a
+/// file. Files without diagnostics are omitted.
+pub async fn workspace_diagnostics(
+    context: &RequestContext,
+    text_document: &TextDocumentIdentifier,
+) -> Result<Vec<FileDiagnostics>> {
+    let path = get_text_document_path(text_document)?;
+    let current_file = context
+        .analyzer
+        .analyze(&path, &context.finder, context.request_time)?;
+
+    // Wait for the workspace indexing to finish.
+    let workspace_root = &current_file.workspace_root;
+    let Some(indexed) = context.indexed.lock().unwrap().get(workspace_root).cloned() else {
+        return Err(Error::General(format!(
+            "Indexing for {} not started",
+            workspace_root.display()
+        )));
+    };
+    indexed.wait().await;
+
+    let mut paths: Vec<PathBuf> = context
+        .analyzer
+        .cached_files(workspace_root)
+        .into_iter()
+        .map(|file| file.document.path.clone())
+        .collect();
+    paths.sort();
+
+    let config = Arc::new(context.client.configurations().await);
+
+    let results: Vec<Option<FileDiagnostics>> = stream::iter(paths)
+        .map(|path| {
+            let context = context.clone();
+            let config = config.clone();
+            async move {
+                tokio::task::spawn_blocking(move || diagnose_file(&context, &config, path))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+        })
+        .buffered(MAX_CONCURRENT_FILES)
+        .collect()
+        .await;
+    Ok(results.into_iter().flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+
+    use crate::{
+        common::{testutils::testdata, utils::AsyncSignal},
+        server::indexing::index,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_workspace_diagnostics() {
+        let context = RequestContext::new_for_testing();
+        let workspace_root = testdata("workspaces/workspace_diagnostics");
+        let mut signal = AsyncSignal::new();
+        context
+            .indexed
+            .lock()
+            .unwrap()
+            .insert(workspace_root.clone(), signal.clone());
+        index(&context, &workspace_root).await;
+        signal.set();
+
+        let text_document = TextDocumentIdentifier {
+            uri: Url::from_file_path(workspace_root.join("BUILD.gn")).unwrap(),
+        };
+        let results = workspace_diagnostics(&context, &text_document)
+            .await
+            .unwrap();
+
+        // Only the broken file is reported, even though it is not the
+        // requested one.
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].uri,
+            Url::from_file_path(workspace_root.join("broken/BUILD.gn")).unwrap()
+        );
+        assert_eq!(results[0].diagnostics.len(), 1);
+        assert_eq!(
+            results[0].diagnostics[0].severity,
+            Some(DiagnosticSeverity::ERROR)
+        );
+        assert_eq!(results[0].diagnostics[0].range.start.line, 16);
+    }
+}
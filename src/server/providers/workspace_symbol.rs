@@ -37,17 +37,9 @@ pub async fn workspace_symbol(
     let query = params.query.to_lowercase();
     let workspace_roots = context.analyzer.workspace_roots();
 
+    // Do not wait for background indexing to finish; files are cached as they
+    // are indexed, so partial results are available while indexing continues.
     for workspace_root in workspace_roots {
-        let signal = context
-            .indexed
-            .lock()
-            .unwrap()
-            .get(&workspace_root)
-            .cloned();
-        if let Some(signal) = signal {
-            signal.wait().await;
-        }
-
         let files = context.analyzer.cached_files(&workspace_root);
         for file in files {
             symbols.extend(extract_symbols(&file, &query));
@@ -78,7 +70,7 @@ fn extract_symbols(file: &ShallowAnalyzedFile, query: &str) -> Vec<SymbolInforma
         if !name.to_lowercase().contains(query) {
             continue;
         }
-        if let Some(assignment) = variable.assignments.values().next() {
+        if let Some(assignment) = variable.sorted_assignments().first() {
             let span = match assignment.assignment_or_call {
                 Either::Left(assignment) => assignment.span,
                 Either::Right(call) => call.span,
@@ -120,3 +112,54 @@ fn extract_symbols(file: &ShallowAnalyzedFile, query: &str) -> Vec<SymbolInforma
 
     symbols
 }
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::{PartialResultParams, WorkDoneProgressParams};
+
+    use crate::common::{
+        config::{Configurations, ExperimentalConfigurations},
+        testutils::testdata,
+        utils::AsyncSignal,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_workspace_symbol_while_indexing() {
+        let context = RequestContext::new_for_testing();
+        context.client.set_configurations(Configurations {
+            experimental: ExperimentalConfigurations {
+                workspace_symbols: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        // Simulate indexing in progress that has processed one file so far.
+        let workspace_root = testdata("workspaces/completion");
+        context
+            .indexed
+            .lock()
+            .unwrap()
+            .insert(workspace_root.clone(), AsyncSignal::new());
+        context
+            .analyzer
+            .analyze_shallow(
+                &workspace_root.join("build/imported.gni"),
+                &context.finder,
+                context.request_time,
+            )
+            .unwrap();
+
+        let params = WorkspaceSymbolParams {
+            query: "imported_template".to_string(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let symbols = workspace_symbol(&context, params).await.unwrap().unwrap();
+        assert!(symbols.iter().any(
+            |symbol| symbol.name == "imported_template" && symbol.kind == SymbolKind::FUNCTION
+        ));
+    }
+}